@@ -1,5 +1,18 @@
 use std::io::{self, Read};
-use clap::{CommandFactory, FromArgMatches, Arg, ArgAction};
+use clap::{CommandFactory, FromArgMatches, Arg, ArgAction, ValueEnum};
+use cathon_core::ast::{Lexer, Parser};
+use cathon_core::{Diagnostic, SourceMap, ToJson};
+use cathon_compiler::{Compiler, disassemble};
+use cathon_runtime::VM;
+
+/// 错误输出格式
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorFormat {
+  /// readable text with the offending source lines
+  Human,
+  /// one JSON object per error, one per line
+  Json,
+}
 
 #[derive(clap::Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -7,13 +20,21 @@ struct Cli {
   /// program passed in as a string (terminates option list)
   #[arg(short = 'c', value_name = "cmd")]
   command: Option<String>,
-  
+
   /// program read from script file
   #[arg(value_name = "file")]
   file: Option<String>,
+
+  /// how errors are reported
+  #[arg(long = "error-format", value_enum, default_value = "human")]
+  error_format: ErrorFormat,
+
+  /// print the bytecode disassembly before running
+  #[arg(long = "dis")]
+  dis: bool,
 }
 
-fn cli() {
+pub fn cli() {
   let cli = Cli::command()
     .version(env!("CARGO_PKG_VERSION"))
     .disable_help_flag(true)
@@ -33,45 +54,83 @@ fn cli() {
         .help("print this help message and exit (also -?)")
         .action(ArgAction::Help)
     );
-  
+
   let matches = cli.get_matches();
   let args = Cli::from_arg_matches(&matches).unwrap();
-  
-  let mut piped_cmd: Option<String> = None;
+
+  if let Some(cmd) = &args.command {
+    std::process::exit(run_command(cmd, &args));
+  }
+
+  if let Some(file) = &args.file {
+    std::process::exit(run_file(file, &args));
+  }
+
   if !atty::is(atty::Stream::Stdin) {
     let mut buffer = String::new();
     let _ = io::stdin().read_to_string(&mut buffer);
-    let trimmed = buffer.trim().to_string();
-    if !trimmed.is_empty() {
-      piped_cmd = Some(trimmed);
-    }
+    std::process::exit(run_source("<stdin>", buffer, &args));
   }
-  
-  if let Some(s) = piped_cmd {
-    run_command(&s);
-    std::process::exit(0);
+}
+
+fn run_file(path: &str, args: &Cli) -> i32 {
+  match std::fs::read_to_string(path) {
+    Ok(source) => run_source(path, source, args),
+    Err(err) => {
+      let diagnostic = Diagnostic::error(format!("can't open file '{}': {}", path, err)).with_code("OSError");
+      report(&diagnostic, &SourceMap::new(), args.error_format);
+      2
+    },
   }
-  
-  if let Some(cmd) = args.file {
-    run_command(&cmd);
-    std::process::exit(0);
+}
+
+fn run_command(cmd: &str, args: &Cli) -> i32 {
+  run_source("<string>", cmd.to_string(), args)
+}
+
+/// 词法分析 -> 语法分析 -> 编译 -> 执行，任一阶段出错时按 error_format 输出并返回退出码
+fn run_source(name: &str, source: String, args: &Cli) -> i32 {
+  let mut map = SourceMap::new();
+  let file = map.add_file(name, source);
+
+  // 1. 词法与语法分析
+  let mut lexer = Lexer::from_file(file);
+  let mut parser = Parser::new(&mut lexer);
+  let module = match parser.parse() {
+    Ok(module) => module,
+    Err(err) => {
+      report(&Diagnostic::from(&err), &map, args.error_format);
+      return 1;
+    },
+  };
+  let arena = parser.arena;
+
+  // 2. 编译为字节码
+  let code = match Compiler::new().compile(&arena, module) {
+    Ok(code) => code,
+    Err(err) => {
+      report(&err.diagnostic(), &map, args.error_format);
+      return 1;
+    },
+  };
+  if args.dis {
+    disassemble(&code);
   }
-  
-  match &args.command {
-    Some(file) => {
-      run_file(file);
-      std::process::exit(0);
-    }
-    &None => {}
+
+  // 3. 执行字节码
+  let mut vm = VM::new();
+  match vm.run(code) {
+    Ok(_) => 0,
+    Err(err) => {
+      report(&err.diagnostic(), &map, args.error_format);
+      1
+    },
   }
 }
 
-fn run_file(path: &str) {
-  // TODO: 实现文件执行逻辑
-  println!("正在读取并执行文件: {}", path);
+fn report(diagnostic: &Diagnostic, map: &SourceMap, format: ErrorFormat) {
+  match format {
+    ErrorFormat::Human => eprint!("{}", diagnostic.render(map, atty::is(atty::Stream::Stderr))),
+    ErrorFormat::Json => eprintln!("{}", diagnostic.to_json(map)),
+  }
 }
-
-fn run_command(cmd: &str) {
-  // TODO: 实现命令执行逻辑
-  println!("正在执行: {}", cmd);
-}
\ No newline at end of file
@@ -1,3 +1,4 @@
+use cathon_core::Span;
 use crate::opcode::OpCode;
 
 /// 代码对象 - 类似 CPython 的 PyCodeObject
@@ -13,10 +14,33 @@ pub struct CodeObject {
   pub names: Vec<String>,
   /// 局部变量名 (用于 LOAD_FAST/STORE_FAST)
   pub varnames: Vec<String>,
-  /// 参数数量
+  /// 被内层函数引用的局部变量 (cell)，可能与 varnames 中的参数同名
+  pub cellvars: Vec<String>,
+  /// 从外层函数捕获的变量；LOAD_DEREF 等的下标先数 cellvars 再数 freevars
+  pub freevars: Vec<String>,
+  /// 位置参数数量，varnames 的前 arg_count 个为位置参数
   pub arg_count: usize,
-  /// 行号表 (字节码偏移 -> 源码行号)
-  pub line_table: Vec<(usize, usize)>,
+  /// 仅关键字参数数量，在 varnames 中紧随位置参数
+  pub kwonly_arg_count: usize,
+  /// 是否有 *args 参数，位于仅关键字参数之后
+  pub varargs: bool,
+  /// 是否有 **kwargs 参数，位于 *args 之后
+  pub varkeywords: bool,
+  /// 调用时不执行函数体，而是返回挂起的生成器 (生成器表达式)
+  pub generator: bool,
+  /// 位置表 (字节码偏移 -> 源码位置)，按偏移递增；每项覆盖到下一项之前的指令
+  pub line_table: Vec<(usize, Span)>,
+  /// 异常表，同一偏移被多项覆盖时内层的项在前
+  pub exception_table: Vec<ExceptionEntry>,
+}
+
+/// 异常表项：[start, end) 内的指令出错时，操作数栈截断到 depth，压入异常并跳到 handler
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionEntry {
+  pub start: usize,
+  pub end: usize,
+  pub handler: usize,
+  pub depth: usize,
 }
 
 /// 常量类型
@@ -27,6 +51,7 @@ pub enum Constant {
   Int(i64),
   Float(f64),
   String(String),
+  Bytes(Vec<u8>),
   /// 嵌套的代码对象 (用于函数定义)
   Code(Box<CodeObject>),
 }
@@ -39,8 +64,15 @@ impl CodeObject {
       constants: Vec::new(),
       names: Vec::new(),
       varnames: Vec::new(),
+      cellvars: Vec::new(),
+      freevars: Vec::new(),
       arg_count: 0,
+      kwonly_arg_count: 0,
+      varargs: false,
+      varkeywords: false,
+      generator: false,
       line_table: Vec::new(),
+      exception_table: Vec::new(),
     }
   }
 
@@ -92,15 +124,72 @@ impl CodeObject {
     idx as u16
   }
 
+  /// cell 或自由变量在 cellvars + freevars 中的下标
+  pub fn deref_index(&self, name: &str) -> Option<u16> {
+    self.cellvars.iter()
+      .chain(&self.freevars)
+      .position(|n| n == name)
+      .map(|idx| idx as u16)
+  }
+
   /// 当前字节码偏移
   pub fn offset(&self) -> usize {
     self.code.len()
   }
 
+  /// 此后写入的指令对应源码中的 span
+  pub fn set_location(&mut self, span: Span) {
+    let offset = self.offset();
+    match self.line_table.last_mut() {
+      Some((_, last)) if *last == span => {},
+      Some((start, last)) if *start == offset => *last = span,
+      _ => self.line_table.push((offset, span)),
+    }
+  }
+
+  /// 偏移处的指令对应的源码位置
+  pub fn location(&self, offset: usize) -> Option<Span> {
+    let idx = self.line_table.partition_point(|(start, _)| *start <= offset);
+    idx.checked_sub(1).map(|idx| self.line_table[idx].1)
+  }
+
+  /// 覆盖偏移处指令的最内层异常表项
+  pub fn handler(&self, offset: usize) -> Option<&ExceptionEntry> {
+    self.exception_table.iter().find(|entry| (entry.start..entry.end).contains(&offset))
+  }
+
   /// 修补跳转地址
   pub fn patch_jump(&mut self, offset: usize) {
     let jump_to = self.code.len() as u16;
     self.code[offset + 1] = (jump_to >> 8) as u8;
     self.code[offset + 2] = (jump_to & 0xFF) as u8;
   }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn locations_and_handlers() {
+    let mut code = CodeObject::new("f");
+    code.set_location(Span::new(0, 1));
+    code.emit_op(OpCode::Nop);
+    code.set_location(Span::new(0, 1));
+    code.emit_op_arg(OpCode::LoadConst, 0);
+    // 同一偏移的位置被后来者替换
+    code.set_location(Span::new(2, 3));
+    code.set_location(Span::new(4, 5));
+    code.emit_op(OpCode::Pop);
+    assert_eq!(code.line_table, vec![(0, Span::new(0, 1)), (4, Span::new(4, 5))]);
+    assert_eq!(code.location(3), Some(Span::new(0, 1)));
+    assert_eq!(code.location(4), Some(Span::new(4, 5)));
+    assert_eq!(CodeObject::new("g").location(0), None);
+
+    code.exception_table.push(ExceptionEntry { start: 1, end: 4, handler: 10, depth: 1 });
+    code.exception_table.push(ExceptionEntry { start: 0, end: 5, handler: 20, depth: 0 });
+    assert_eq!(code.handler(3).map(|entry| entry.handler), Some(10));
+    assert_eq!(code.handler(4).map(|entry| entry.handler), Some(20));
+    assert_eq!(code.handler(5), None);
+  }
+}
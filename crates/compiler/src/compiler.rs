@@ -1,11 +1,68 @@
-use cathon_core::ast::{Arena, NodeId, NodeKind, TokenKind};
-use crate::code::{CodeObject, Constant};
+use cathon_core::ast::{Arena, Arguments, Comprehension, NodeId, NodeKind, TokenKind, Keyword};
+use cathon_core::{Diagnostic, ErrorKind, Span};
+use std::fmt;
+use crate::code::{CodeObject, Constant, ExceptionEntry};
 use crate::opcode::OpCode;
+use crate::symtable::{Scope, ScopeKind, SymbolTable, SymbolScope};
 
 pub struct Compiler<'a> {
   arena: Option<&'a Arena>,
   /// 代码对象栈 (用于嵌套函数)
   code_stack: Vec<CodeObject>,
+  /// 编译前由符号表分析得到的名字分类
+  symtable: Option<SymbolTable>,
+  /// 与 code_stack 对应的作用域节点 (模块、函数定义或类定义)
+  scopes: Vec<NodeId>,
+  /// 当前代码对象中由内到外的控制块 (循环与 try)，最内层在末尾
+  blocks: Vec<Block<'a>>,
+  /// 语句之间保留在操作数栈上的值的个数 (如外层 for 循环的迭代器)，异常处理器将栈截断到此深度
+  depth: usize,
+  /// 此后写入的指令对应的源码位置
+  location: Option<Span>,
+}
+
+/// 控制块: break / continue / return 跳出时由内到外依次清理
+enum Block<'a> {
+  /// 循环: continue 跳回 start，break 的跳转在 else 子句之后统一修补；
+  /// for 循环的迭代器在循环体执行期间位于栈顶之下，break 时需要先弹出
+  Loop { start: usize, breaks: Vec<usize>, iterator: bool },
+  /// try 主体；跳出带 finally 的 try 主体时内联执行 finally 子句
+  Try { range: Protected, finally: Option<&'a [NodeId]> },
+  /// 处理异常的代码 (except 子句或异常路径上的 finally 子句)，跳出时结束对异常的处理；
+  /// exception 表示异常本身仍在栈上，需要一并弹出
+  Handler { range: Protected, exception: bool },
+  /// except 子句中 as 目标绑定的异常；离开子句时清除该名字
+  Binding { range: Protected, name: String },
+}
+
+impl Block<'_> {
+  fn range_mut(&mut self) -> Option<&mut Protected> {
+    match self {
+      Block::Loop { .. } => None,
+      Block::Try { range, .. } | Block::Handler { range, .. } | Block::Binding { range, .. } => Some(range),
+    }
+  }
+}
+
+/// 由异常表保护的代码区域；跳出时关闭当前区间，内联的清理代码不受保护，跳转之后重新打开
+struct Protected {
+  start: usize,
+  ranges: Vec<(usize, usize)>,
+  depth: usize,
+}
+
+impl Protected {
+  fn close(&mut self, offset: usize) {
+    if self.start < offset {
+      self.ranges.push((self.start, offset));
+    }
+  }
+}
+
+impl Default for Compiler<'_> {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 impl<'a> Compiler<'a> {
@@ -13,6 +70,11 @@ impl<'a> Compiler<'a> {
     Self {
       arena: None,
       code_stack: vec![CodeObject::new("<module>")],
+      symtable: None,
+      scopes: Vec::new(),
+      blocks: Vec::new(),
+      depth: 0,
+      location: None,
     }
   }
 
@@ -24,6 +86,8 @@ impl<'a> Compiler<'a> {
   /// 编译整个程序
   pub fn compile(mut self, arena: &'a Arena, module: NodeId) -> Result<CodeObject, CompileError> {
     self.arena = Some(arena);
+    self.symtable = Some(SymbolTable::build(arena, module)?);
+    self.scopes.push(module);
     let node = arena.get(module);
     match node.kind() {
       NodeKind::Module { body } => {
@@ -35,9 +99,7 @@ impl<'a> Compiler<'a> {
     }
     
     // 确保返回 None
-    self.emit_op(OpCode::LoadConst);
-    let idx = self.code().add_const(Constant::None);
-    self.emit_arg(idx);
+    self.load_const(Constant::None);
     self.emit_op(OpCode::Return);
     
     Ok(self.code_stack.pop().unwrap())
@@ -47,32 +109,749 @@ impl<'a> Compiler<'a> {
   fn compile_stmt(&mut self, node_id: NodeId) -> Result<(), CompileError> {
     let arena = self.arena.expect("a");
     let node = arena.get(node_id);
+    let outer = self.enter_location(*node.span());
     match node.kind() {
       // 表达式语句
       NodeKind::Expr {value} => {
         self.compile_expr(*value)?;
         self.emit_op(OpCode::Pop);
       },
-      _ => todo!(),
+
+      // 链式赋值: 除最后一个目标外，每个目标之前复制一次栈顶
+      NodeKind::Assign { targets, value } => {
+        self.compile_expr(*value)?;
+        for (i, target) in targets.iter().enumerate() {
+          if i + 1 < targets.len() {
+            self.emit_op(OpCode::Dup);
+          }
+          self.compile_store(*target)?;
+        }
+      },
+
+      // 增量赋值: 目标只求值一次，InplaceOp 先尝试就地运算的特殊方法 (如 __iadd__)
+      NodeKind::AugAssign { target, op, value } => {
+        let opcode = aug_opcode(op.kind()) as u16;
+        match arena.get(*target).kind() {
+          NodeKind::Name { id } => {
+            let name = arena.interner.resolve(*id).to_string();
+            self.load_name(name.clone());
+            self.compile_expr(*value)?;
+            self.emit_op(OpCode::InplaceOp);
+            self.emit_arg(opcode);
+            self.store_name(name);
+          },
+          NodeKind::Attribute { value: obj, attr } => {
+            self.compile_expr(*obj)?;
+            self.emit_op(OpCode::Dup);
+            let idx = self.code().add_name(arena.interner.resolve(*attr).to_string());
+            self.emit_op(OpCode::GetAttr);
+            self.emit_arg(idx);
+            self.compile_expr(*value)?;
+            self.emit_op(OpCode::InplaceOp);
+            self.emit_arg(opcode);
+            self.emit_op(OpCode::Swap);
+            self.emit_op(OpCode::SetAttr);
+            self.emit_arg(idx);
+          },
+          NodeKind::Subscript { value: obj, slice } => {
+            self.compile_expr(*obj)?;
+            self.compile_expr(*slice)?;
+            self.emit_op(OpCode::DupTwo);
+            self.emit_op(OpCode::BinarySubscr);
+            self.compile_expr(*value)?;
+            self.emit_op(OpCode::InplaceOp);
+            self.emit_arg(opcode);
+            self.emit_op(OpCode::RotThree);
+            self.emit_op(OpCode::StoreSubscr);
+          },
+          _ => return Err(CompileError::new("illegal expression for augmented assignment", *arena.get(*target).span())),
+        }
+      },
+
+      // if 语句: JumpIfFalse 只查看栈顶，因此两个分支各自弹出条件
+      NodeKind::If { test, body, orelse } => {
+        self.compile_expr(*test)?;
+        let else_jump = self.emit_jump(OpCode::JumpIfFalse);
+        self.emit_op(OpCode::Pop);
+        self.compile_body(body)?;
+        let end_jump = self.emit_jump(OpCode::Jump);
+        self.code().patch_jump(else_jump);
+        self.emit_op(OpCode::Pop);
+        self.compile_body(orelse)?;
+        self.code().patch_jump(end_jump);
+      },
+
+      // while 循环: 条件为假时跳到 else 子句，break 跳过 else 子句
+      NodeKind::While { test, body, orelse } => {
+        let start = self.code().offset();
+        self.compile_expr(*test)?;
+        let exit_jump = self.emit_jump(OpCode::JumpIfFalse);
+        self.emit_op(OpCode::Pop);
+        let breaks = self.compile_loop_body(start, false, body)?;
+        self.code().patch_jump(exit_jump);
+        self.emit_op(OpCode::Pop);
+        self.compile_body(orelse)?;
+        for jump in breaks {
+          self.code().patch_jump(jump);
+        }
+      },
+
+      // for 循环: ForIter 在迭代器耗尽时弹出迭代器并跳到 else 子句
+      NodeKind::For { target, iter, body, orelse } => {
+        self.compile_expr(*iter)?;
+        let header = self.enter_location(*arena.get(*iter).span());
+        self.emit_op(OpCode::GetIter);
+        let start = self.code().offset();
+        let exit_jump = self.emit_jump(OpCode::ForIter);
+        self.compile_store(*target)?;
+        let breaks = self.compile_loop_body(start, true, body)?;
+        self.code().patch_jump(exit_jump);
+        self.leave_location(header);
+        self.compile_body(orelse)?;
+        for jump in breaks {
+          self.code().patch_jump(jump);
+        }
+      },
+
+      NodeKind::Break => {
+        let Some(index) = self.innermost_loop() else {
+          return Err(CompileError::new("'break' outside loop", *node.span()));
+        };
+        let exited = self.unwind(index + 1, false)?;
+        if let Block::Loop { iterator: true, .. } = self.blocks[index] {
+          self.emit_op(OpCode::Pop);
+        }
+        let jump = self.emit_jump(OpCode::Jump);
+        if let Block::Loop { breaks, .. } = &mut self.blocks[index] {
+          breaks.push(jump);
+        }
+        self.reenter(exited);
+      },
+
+      NodeKind::Continue => {
+        let Some(index) = self.innermost_loop() else {
+          return Err(CompileError::new("'continue' not properly in loop", *node.span()));
+        };
+        let exited = self.unwind(index + 1, false)?;
+        let Block::Loop { start, .. } = self.blocks[index] else { unreachable!("loop") };
+        self.emit_op(OpCode::Loop);
+        self.emit_arg(start as u16);
+        self.reenter(exited);
+      },
+
+      NodeKind::FunctionDef { name, args, body } => {
+        let name = arena.interner.resolve(*name).to_string();
+        self.compile_function(node_id, &name, args, |compiler| {
+          compiler.compile_body(body)?;
+          compiler.load_const(Constant::None);
+          compiler.emit_op(OpCode::Return);
+          Ok(())
+        })?;
+        self.store_name(name);
+      },
+
+      // 类体编译为函数，由 BuildClass 在类的命名空间中执行后创建类
+      NodeKind::ClassDef { name, bases, body } => {
+        let name = arena.interner.resolve(*name).to_string();
+        self.make_function(node_id, CodeObject::new(name.as_str()), 0, |compiler| {
+          compiler.compile_body(body)?;
+          // 方法中的 super() 所需的 __class__ cell 以 __classcell__ 交给 BuildClass，类创建后填入
+          if let Some(idx) = compiler.code().cellvars.iter().position(|name| name == "__class__") {
+            compiler.emit_op(OpCode::LoadClosure);
+            compiler.emit_arg(idx as u16);
+            let idx = compiler.code().add_name("__classcell__".to_string());
+            compiler.emit_op(OpCode::StoreName);
+            compiler.emit_arg(idx);
+          }
+          compiler.load_const(Constant::None);
+          compiler.emit_op(OpCode::Return);
+          Ok(())
+        })?;
+        self.load_const(Constant::String(name.clone()));
+        for base in bases {
+          self.compile_expr(*base)?;
+        }
+        self.emit_op(OpCode::BuildClass);
+        self.emit_arg(bases.len() as u16);
+        self.store_name(name);
+      },
+
+      NodeKind::Return { value } => {
+        if self.scope().kind != ScopeKind::Function {
+          return Err(CompileError::new("'return' outside function", *node.span()));
+        }
+        match value {
+          Some(value) => self.compile_expr(*value)?,
+          None => self.load_const(Constant::None),
+        }
+        let exited = self.unwind(0, true)?;
+        self.emit_op(OpCode::Return);
+        self.reenter(exited);
+      },
+
+      NodeKind::Raise { exc } => {
+        let argc = match exc {
+          Some(exc) => {
+            self.compile_expr(*exc)?;
+            1
+          },
+          None => 0,
+        };
+        self.emit_op(OpCode::Raise);
+        self.emit_arg(argc);
+      },
+
+      // try/except/finally 按 try/finally 包裹 try/except 编译
+      NodeKind::Try { body, handlers, orelse, finalbody } => {
+        if finalbody.is_empty() {
+          self.compile_try_except(body, handlers, orelse)?;
+        } else {
+          self.compile_try_finally(finalbody, |compiler| {
+            if handlers.is_empty() {
+              compiler.compile_body(body)
+            } else {
+              compiler.compile_try_except(body, handlers, orelse)
+            }
+          })?;
+        }
+      },
+
+      // 声明只影响符号表分析，不产生指令
+      NodeKind::Pass | NodeKind::Global { .. } | NodeKind::Nonlocal { .. } => {},
+      _ => return Err(CompileError::unsupported("this statement is not supported yet", *node.span())),
+    }
+    self.leave_location(outer);
+    Ok(())
+  }
+
+  /// 编译语句块
+  fn compile_body(&mut self, body: &[NodeId]) -> Result<(), CompileError> {
+    for stmt in body {
+      self.compile_stmt(*stmt)?;
     }
     Ok(())
   }
 
+  /// 在当前作用域求值默认值，然后在新的代码对象中由 body 编译函数体，最后留下 MakeFunction 的结果
+  fn compile_function<F>(&mut self, node_id: NodeId, name: &str, args: &Arguments, body: F) -> Result<(), CompileError>
+  where
+    F: FnOnce(&mut Self) -> Result<(), CompileError>,
+  {
+    let arena = self.arena.expect("a");
+    let mut flags = 0;
+    if !args.defaults.is_empty() {
+      for default in &args.defaults {
+        self.compile_expr(*default)?;
+      }
+      self.emit_op(OpCode::BuildTuple);
+      self.emit_arg(args.defaults.len() as u16);
+      flags |= 1;
+    }
+    let kw_defaults: Vec<_> = args.kwonlyargs.iter().zip(&args.kw_defaults)
+      .filter_map(|(name, default)| default.map(|default| (*name, default)))
+      .collect();
+    if !kw_defaults.is_empty() {
+      for (name, default) in &kw_defaults {
+        self.load_const(Constant::String(arena.interner.resolve(*name).to_string()));
+        self.compile_expr(*default)?;
+      }
+      self.emit_op(OpCode::BuildDict);
+      self.emit_arg(kw_defaults.len() as u16);
+      flags |= 2;
+    }
+
+    // 形参依次占据 varnames 的开头: 位置参数、仅关键字参数、*args、**kwargs
+    let mut code = CodeObject::new(name);
+    code.arg_count = args.args.len();
+    code.kwonly_arg_count = args.kwonlyargs.len();
+    code.varargs = args.vararg.is_some();
+    code.varkeywords = args.kwarg.is_some();
+    let params = args.args.iter()
+      .chain(&args.kwonlyargs)
+      .chain(&args.vararg)
+      .chain(&args.kwarg);
+    for param in params {
+      code.add_varname(arena.interner.resolve(*param).to_string());
+    }
+    self.make_function(node_id, code, flags, body)
+  }
+
+  /// 在新的代码对象中由 body 编译函数体，然后留下 MakeFunction 的结果；
+  /// flags 为已压栈的默认值对应的标志，闭包元组由本函数加载
+  fn make_function<F>(&mut self, node_id: NodeId, mut code: CodeObject, mut flags: u16, body: F) -> Result<(), CompileError>
+  where
+    F: FnOnce(&mut Self) -> Result<(), CompileError>,
+  {
+    let scope = self.symtable.as_ref().expect("symtable").scope(node_id);
+    code.cellvars = scope.cellvars();
+    code.freevars = scope.freevars();
+    code.generator = scope.generator;
+
+    // 闭包元组: 按内层 freevars 的顺序加载当前作用域中对应的 cell
+    if !code.freevars.is_empty() {
+      for name in &code.freevars {
+        let idx = self.code().deref_index(name).expect("enclosing cell");
+        self.emit_op(OpCode::LoadClosure);
+        self.emit_arg(idx);
+      }
+      self.emit_op(OpCode::BuildTuple);
+      self.emit_arg(code.freevars.len() as u16);
+      flags |= 4;
+    }
+
+    self.code_stack.push(code);
+    self.scopes.push(node_id);
+    let blocks = std::mem::take(&mut self.blocks);
+    let depth = std::mem::take(&mut self.depth);
+    let outer = self.enter_location(*self.arena.expect("a").get(node_id).span());
+    let result = body(self);
+    self.blocks = blocks;
+    self.depth = depth;
+    self.scopes.pop();
+    result?;
+    let code = self.code_stack.pop().expect("function code");
+    self.leave_location(outer);
+
+    self.load_const(Constant::Code(Box::new(code)));
+    self.emit_op(OpCode::MakeFunction);
+    self.emit_arg(flags);
+    Ok(())
+  }
+
+  /// 推导式编译为以 .0 为唯一参数的函数并立即调用，.0 为最外层可迭代对象的迭代器；
+  /// 生成器表达式的函数调用时返回生成器
+  fn compile_comprehension(&mut self, node_id: NodeId, kind: &NodeKind) -> Result<(), CompileError> {
+    let (name, generators) = match kind {
+      NodeKind::ListComp { generators, .. } => ("<listcomp>", generators),
+      NodeKind::SetComp { generators, .. } => ("<setcomp>", generators),
+      NodeKind::DictComp { generators, .. } => ("<dictcomp>", generators),
+      NodeKind::GeneratorExp { generators, .. } => ("<genexpr>", generators),
+      _ => unreachable!("not a comprehension"),
+    };
+    let mut code = CodeObject::new(name);
+    code.arg_count = 1;
+    code.add_varname(".0".to_string());
+    self.make_function(node_id, code, 0, |compiler| {
+      let build = match kind {
+        NodeKind::ListComp { .. } => Some(OpCode::BuildList),
+        NodeKind::SetComp { .. } => Some(OpCode::BuildSet),
+        NodeKind::DictComp { .. } => Some(OpCode::BuildDict),
+        _ => None,
+      };
+      if let Some(build) = build {
+        compiler.emit_op(build);
+        compiler.emit_arg(0);
+      }
+      compiler.compile_generators(kind, generators, 0)?;
+      if build.is_none() {
+        compiler.load_const(Constant::None);
+      }
+      compiler.emit_op(OpCode::Return);
+      Ok(())
+    })?;
+    self.compile_expr(generators[0].iter)?;
+    self.emit_op(OpCode::GetIter);
+    self.emit_op(OpCode::Call);
+    self.emit_arg(1);
+    Ok(())
+  }
+
+  /// 编译第 index 个 for 子句，最内层的子句之内产出元素；
+  /// 条件为假时弹出条件并进入下一次迭代。产出元素时结果容器之上是每层的迭代器
+  fn compile_generators(&mut self, kind: &NodeKind, generators: &[Comprehension], index: usize) -> Result<(), CompileError> {
+    let generator = &generators[index];
+    if index == 0 {
+      self.load_name(".0".to_string());
+    } else {
+      self.compile_expr(generator.iter)?;
+      self.emit_op(OpCode::GetIter);
+    }
+    let start = self.code().offset();
+    let exit_jump = self.emit_jump(OpCode::ForIter);
+    self.compile_store(generator.target)?;
+    let mut skips = Vec::new();
+    for cond in &generator.ifs {
+      self.compile_expr(*cond)?;
+      skips.push(self.emit_jump(OpCode::JumpIfFalse));
+      self.emit_op(OpCode::Pop);
+    }
+
+    if index + 1 < generators.len() {
+      self.compile_generators(kind, generators, index + 1)?;
+    } else {
+      let depth = generators.len() as u16 + 1;
+      match kind {
+        NodeKind::ListComp { elt, .. } => {
+          self.compile_expr(*elt)?;
+          self.emit_op(OpCode::ListAppend);
+          self.emit_arg(depth);
+        },
+        NodeKind::SetComp { elt, .. } => {
+          self.compile_expr(*elt)?;
+          self.emit_op(OpCode::SetAdd);
+          self.emit_arg(depth);
+        },
+        NodeKind::DictComp { key, value, .. } => {
+          self.compile_expr(*key)?;
+          self.compile_expr(*value)?;
+          self.emit_op(OpCode::MapAdd);
+          self.emit_arg(depth);
+        },
+        NodeKind::GeneratorExp { elt, .. } => {
+          self.compile_expr(*elt)?;
+          self.emit_op(OpCode::YieldValue);
+          self.emit_op(OpCode::Pop);
+        },
+        _ => unreachable!("not a comprehension"),
+      }
+    }
+
+    if !skips.is_empty() {
+      let end_jump = self.emit_jump(OpCode::Jump);
+      for jump in skips {
+        self.code().patch_jump(jump);
+      }
+      self.emit_op(OpCode::Pop);
+      self.code().patch_jump(end_jump);
+    }
+    self.emit_op(OpCode::Loop);
+    self.emit_arg(start as u16);
+    self.code().patch_jump(exit_jump);
+    Ok(())
+  }
+
+  /// 编译循环体并跳回循环开头，返回待修补的 break 跳转
+  /// else 子句中的 break 属于外层循环，因此循环上下文在编译 else 子句之前弹出
+  fn compile_loop_body(&mut self, start: usize, iterator: bool, body: &[NodeId]) -> Result<Vec<usize>, CompileError> {
+    self.blocks.push(Block::Loop { start, breaks: Vec::new(), iterator });
+    self.depth += iterator as usize;
+    self.compile_body(body)?;
+    self.depth -= iterator as usize;
+    self.emit_op(OpCode::Loop);
+    self.emit_arg(start as u16);
+    match self.blocks.pop() {
+      Some(Block::Loop { breaks, .. }) => Ok(breaks),
+      _ => unreachable!("loop"),
+    }
+  }
+
+  /// try/except/else: 出错时依次匹配 except 子句，都不匹配时重新抛出；
+  /// except 子句执行期间出错时先结束对原异常的处理
+  fn compile_try_except(&mut self, body: &[NodeId], handlers: &[NodeId], orelse: &[NodeId]) -> Result<(), CompileError> {
+    let arena = self.arena.expect("a");
+    let range = self.protect();
+    self.blocks.push(Block::Try { range, finally: None });
+    self.compile_body(body)?;
+    let range = self.pop_protected();
+    self.compile_body(orelse)?;
+    let mut end_jumps = vec![self.emit_jump(OpCode::Jump)];
+
+    let handler = self.code().offset();
+    self.add_handler(range, handler);
+    self.emit_op(OpCode::PushExcInfo);
+    let range = self.protect();
+    self.blocks.push(Block::Handler { range, exception: false });
+    let mut bare = false;
+    for handler in handlers {
+      let NodeKind::ExceptHandler { ty, name, body } = arena.get(*handler).kind() else {
+        unreachable!("except handler");
+      };
+      // 栈顶为异常，CheckExcMatch 保留异常并压入匹配结果
+      let next = match ty {
+        Some(ty) => {
+          self.compile_expr(*ty)?;
+          let outer = self.enter_location(*arena.get(*ty).span());
+          self.emit_op(OpCode::CheckExcMatch);
+          self.leave_location(outer);
+          let next = self.emit_jump(OpCode::JumpIfFalse);
+          self.emit_op(OpCode::Pop);
+          Some(next)
+        },
+        None => {
+          bare = true;
+          None
+        },
+      };
+      match name {
+        Some(name) => {
+          let name = arena.interner.resolve(*name).to_string();
+          self.compile_binding_handler(name, body)?;
+        },
+        None => {
+          self.emit_op(OpCode::Pop);
+          self.compile_body(body)?;
+        },
+      }
+      self.emit_op(OpCode::PopExcept);
+      end_jumps.push(self.emit_jump(OpCode::Jump));
+      if let Some(next) = next {
+        self.code().patch_jump(next);
+        self.emit_op(OpCode::Pop);
+      }
+    }
+    if !bare {
+      self.emit_op(OpCode::Reraise);
+    }
+    let range = self.pop_protected();
+    let cleanup = self.code().offset();
+    self.add_handler(range, cleanup);
+    self.emit_op(OpCode::PopExcept);
+    self.emit_op(OpCode::Reraise);
+    for jump in end_jumps {
+      self.code().patch_jump(jump);
+    }
+    Ok(())
+  }
+
+  /// 带 as 目标的 except 子句体: 与 CPython 一致，无论正常结束、跳出还是出错都清除绑定的名字，
+  /// 出错时清除后重新抛出，交给外层的处理器结束对原异常的处理
+  fn compile_binding_handler(&mut self, name: String, body: &[NodeId]) -> Result<(), CompileError> {
+    self.store_name(name.clone());
+    let range = self.protect();
+    self.blocks.push(Block::Binding { range, name: name.clone() });
+    self.compile_body(body)?;
+    let range = self.pop_protected();
+    self.unbind_name(name.clone());
+    let end_jump = self.emit_jump(OpCode::Jump);
+
+    let handler = self.code().offset();
+    self.add_handler(range, handler);
+    self.unbind_name(name);
+    self.emit_op(OpCode::Reraise);
+    self.code().patch_jump(end_jump);
+    Ok(())
+  }
+
+  /// try/finally: 正常结束或跳出 try 主体时内联执行 finally 子句；
+  /// 出错时异常留在栈上执行 finally 子句，然后重新抛出
+  fn compile_try_finally<F>(&mut self, finalbody: &'a [NodeId], body: F) -> Result<(), CompileError>
+  where
+    F: FnOnce(&mut Self) -> Result<(), CompileError>,
+  {
+    let range = self.protect();
+    self.blocks.push(Block::Try { range, finally: Some(finalbody) });
+    body(self)?;
+    let range = self.pop_protected();
+    self.compile_body(finalbody)?;
+    let end_jump = self.emit_jump(OpCode::Jump);
+
+    let handler = self.code().offset();
+    self.add_handler(range, handler);
+    self.emit_op(OpCode::PushExcInfo);
+    let range = self.protect();
+    self.blocks.push(Block::Handler { range, exception: true });
+    self.depth += 1;
+    self.compile_body(finalbody)?;
+    self.depth -= 1;
+    self.emit_op(OpCode::Reraise);
+    let range = self.pop_protected();
+    let cleanup = self.code().offset();
+    self.add_handler(range, cleanup);
+    self.emit_op(OpCode::PopExcept);
+    self.emit_op(OpCode::Reraise);
+    self.code().patch_jump(end_jump);
+    Ok(())
+  }
+
+  /// 从当前偏移开始一个受保护的区域
+  fn protect(&mut self) -> Protected {
+    Protected { start: self.code().offset(), ranges: Vec::new(), depth: self.depth }
+  }
+
+  /// 弹出最内层的 try 或处理器块，关闭其保护区间
+  fn pop_protected(&mut self) -> Protected {
+    let offset = self.code().offset();
+    match self.blocks.pop() {
+      Some(Block::Try { mut range, .. } | Block::Handler { mut range, .. } | Block::Binding { mut range, .. }) => {
+        range.close(offset);
+        range
+      },
+      _ => unreachable!("protected block"),
+    }
+  }
+
+  /// 区域内的指令出错时跳到 handler；内层区域先于外层写入异常表
+  fn add_handler(&mut self, range: Protected, handler: usize) {
+    for (start, end) in range.ranges {
+      self.code().exception_table.push(ExceptionEntry { start, end, handler, depth: range.depth });
+    }
+  }
+
+  fn innermost_loop(&self) -> Option<usize> {
+    self.blocks.iter().rposition(|block| matches!(block, Block::Loop { .. }))
+  }
+
+  /// 跳出 blocks[target..] 之前由内到外清理这些块: 关闭保护区间，结束异常处理或内联 finally 子句；
+  /// preserve 表示栈顶有待返回的值。返回移出的块，写入跳转之后由 reenter 放回
+  fn unwind(&mut self, target: usize, preserve: bool) -> Result<Vec<Block<'a>>, CompileError> {
+    let mut exited = Vec::new();
+    while self.blocks.len() > target {
+      let mut block = self.blocks.pop().expect("block");
+      let offset = self.code().offset();
+      if let Some(range) = block.range_mut() {
+        range.close(offset);
+      }
+      match block {
+        Block::Loop { .. } | Block::Try { finally: None, .. } => {},
+        // finally 子句中的 break / return 只清理更外层的块
+        Block::Try { finally: Some(finalbody), .. } => {
+          self.depth += preserve as usize;
+          self.compile_body(finalbody)?;
+          self.depth -= preserve as usize;
+        },
+        Block::Handler { exception, .. } => {
+          self.emit_op(OpCode::PopExcept);
+          if exception {
+            if preserve {
+              self.emit_op(OpCode::Swap);
+            }
+            self.emit_op(OpCode::Pop);
+          }
+        },
+        Block::Binding { ref name, .. } => self.unbind_name(name.clone()),
+      }
+      exited.push(block);
+    }
+    Ok(exited)
+  }
+
+  /// 放回 unwind 移出的块，从当前偏移重新打开它们的保护区间
+  fn reenter(&mut self, exited: Vec<Block<'a>>) {
+    let offset = self.code().offset();
+    for mut block in exited.into_iter().rev() {
+      if let Some(range) = block.range_mut() {
+        range.start = offset;
+      }
+      self.blocks.push(block);
+    }
+  }
+
   /// 编译表达式
   fn compile_expr(&mut self, expr_id: NodeId) -> Result<(), CompileError> {
     let arena = self.arena.expect("a");
     let node = arena.get(expr_id);
+    let outer = self.enter_location(*node.span());
     match node.kind() {
       NodeKind::Constant {value} => {
         let idx = match value.kind() {
           TokenKind::Int(v) => self.code().add_const(Constant::Int(*v)),
-          _ => todo!(),
+          TokenKind::Float(v) => self.code().add_const(Constant::Float(*v)),
+          TokenKind::String(s) => self.code().add_const(Constant::String(s.clone())),
+          TokenKind::Bytes(b) => self.code().add_const(Constant::Bytes(b.clone())),
+          TokenKind::Keyword(Keyword::True) => self.code().add_const(Constant::Bool(true)),
+          TokenKind::Keyword(Keyword::False) => self.code().add_const(Constant::Bool(false)),
+          TokenKind::Keyword(Keyword::Null) => self.code().add_const(Constant::None),
+          TokenKind::Keyword(Keyword::Inf) => self.code().add_const(Constant::Float(f64::INFINITY)),
+          TokenKind::Keyword(Keyword::NaN) => self.code().add_const(Constant::Float(f64::NAN)),
+          _ => return Err(CompileError::unsupported("this constant is not supported yet", value.span())),
         };
         
         self.emit_op(OpCode::LoadConst);
         self.emit_arg(idx);
       }
 
+      NodeKind::Name { id } => {
+        let name = arena.interner.resolve(*id).to_string();
+        self.load_name(name);
+      }
+
+      NodeKind::Attribute { value, attr } => {
+        self.compile_expr(*value)?;
+        let idx = self.code().add_name(arena.interner.resolve(*attr).to_string());
+        self.emit_op(OpCode::GetAttr);
+        self.emit_arg(idx);
+      },
+
+      NodeKind::Subscript { value, slice } => {
+        self.compile_expr(*value)?;
+        self.compile_expr(*slice)?;
+        self.emit_op(OpCode::BinarySubscr);
+      },
+
+      NodeKind::Call { func, args, keywords } => {
+        self.compile_expr(*func)?;
+        // 方法中的零参数 super() 等价于 super(__class__, self)，self 为方法的第一个形参
+        let mut argc = args.len() + keywords.len();
+        if argc == 0 && let Some(receiver) = self.super_receiver(*func) {
+          self.load_name("__class__".to_string());
+          self.load_name(receiver);
+          argc = 2;
+        }
+        for arg in args {
+          self.compile_expr(*arg)?;
+        }
+        for (_, value) in keywords {
+          self.compile_expr(*value)?;
+        }
+        if keywords.is_empty() {
+          self.emit_op(OpCode::Call);
+        } else {
+          for (name, _) in keywords {
+            self.load_const(Constant::String(arena.interner.resolve(*name).to_string()));
+          }
+          self.emit_op(OpCode::BuildTuple);
+          self.emit_arg(keywords.len() as u16);
+          self.emit_op(OpCode::CallKw);
+        }
+        self.emit_arg(argc as u16);
+      },
+
+      // 省略的部分以 None 代替；没有 step 时只构建两项
+      NodeKind::Slice { lower, upper, step } => {
+        for part in [lower, upper] {
+          match part {
+            Some(part) => self.compile_expr(*part)?,
+            None => self.load_const(Constant::None),
+          }
+        }
+        let count = match step {
+          Some(step) => {
+            self.compile_expr(*step)?;
+            3
+          },
+          None => 2,
+        };
+        self.emit_op(OpCode::BuildSlice);
+        self.emit_arg(count);
+      },
+
+      NodeKind::Tuple { elts } | NodeKind::List { elts } | NodeKind::Set { elts } => {
+        self.compile_display(node.kind(), elts)?;
+      },
+
+      // ** 之前的键值对一次性构建，之后逐个加入或合并
+      NodeKind::Dict { keys, values } => {
+        let split = keys.iter().position(Option::is_none).unwrap_or(keys.len());
+        for (key, value) in keys[..split].iter().zip(values) {
+          self.compile_expr(key.expect("key"))?;
+          self.compile_expr(*value)?;
+        }
+        self.emit_op(OpCode::BuildDict);
+        self.emit_arg(split as u16);
+        for (key, value) in keys[split..].iter().zip(&values[split..]) {
+          match key {
+            Some(key) => {
+              self.compile_expr(*key)?;
+              self.compile_expr(*value)?;
+              self.emit_op(OpCode::MapAdd);
+            },
+            None => {
+              self.compile_expr(*value)?;
+              self.emit_op(OpCode::DictUpdate);
+            },
+          }
+          self.emit_arg(1);
+        }
+      },
+
+      NodeKind::Starred { .. } => {
+        return Err(CompileError::new("can't use starred expression here", *node.span()));
+      },
+
+      NodeKind::ListComp { .. } | NodeKind::SetComp { .. } | NodeKind::DictComp { .. } | NodeKind::GeneratorExp { .. } => {
+        self.compile_comprehension(expr_id, node.kind())?;
+      },
+
       NodeKind::BinOp { left, op, right } => {
         self.compile_expr(*left)?;
         self.compile_expr(*right)?;
@@ -80,27 +859,344 @@ impl<'a> Compiler<'a> {
         let opcode = match op.kind() {
           TokenKind::Plus => OpCode::BinaryAdd,
           TokenKind::Minus => OpCode::BinarySub,
-          /*BinOp::Mul => OpCode::BinaryMul,
-          BinOp::Div => OpCode::BinaryDiv,
-          BinOp::FloorDiv => OpCode::BinaryFloorDiv,
-          BinOp::Mod => OpCode::BinaryMod,
-          BinOp::Pow => OpCode::BinaryPow,
-          BinOp::Eq => OpCode::CompareEq,
-          BinOp::Ne => OpCode::CompareNe,
-          BinOp::Lt => OpCode::CompareLt,
-          BinOp::Le => OpCode::CompareLe,
-          BinOp::Gt => OpCode::CompareGt,
-          BinOp::Ge => OpCode::CompareGe,*/
-          _ => todo!(),
+          TokenKind::Star => OpCode::BinaryMul,
+          TokenKind::Slash => OpCode::BinaryDiv,
+          TokenKind::DoubleSlash => OpCode::BinaryFloorDiv,
+          TokenKind::Percent => OpCode::BinaryMod,
+          TokenKind::At => OpCode::BinaryMatMul,
+          TokenKind::DoubleStar => OpCode::BinaryPow,
+          TokenKind::LeftShift => OpCode::BinaryLShift,
+          TokenKind::RightShift => OpCode::BinaryRShift,
+          TokenKind::Amper => OpCode::BinaryAnd,
+          TokenKind::VBar => OpCode::BinaryOr,
+          TokenKind::Circumflex => OpCode::BinaryXor,
+          _ => return Err(CompileError::unsupported("this binary operator is not supported yet", op.span())),
+        };
+        self.emit_op(opcode);
+      },
+
+      NodeKind::UnaryOp { op, operand } => {
+        self.compile_expr(*operand)?;
+        let opcode = match op.kind() {
+          TokenKind::Minus => OpCode::UnaryNeg,
+          TokenKind::Plus => OpCode::UnaryPos,
+          TokenKind::Tilde => OpCode::UnaryInvert,
+          _ => return Err(CompileError::unsupported("this unary operator is not supported yet", op.span())),
         };
         self.emit_op(opcode);
       },
+
+      // 短路求值: JumpIfFalse / JumpIfTrue 只查看栈顶，跳转时留下的操作数即为结果
+      NodeKind::BoolOp { op, values } => {
+        let jump = match op.kind() {
+          TokenKind::DoubleAmper | TokenKind::Keyword(Keyword::And) => OpCode::JumpIfFalse,
+          _ => OpCode::JumpIfTrue,
+        };
+        let mut jumps = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+          if i > 0 {
+            self.emit_op(OpCode::Pop);
+          }
+          self.compile_expr(*value)?;
+          if i + 1 < values.len() {
+            jumps.push(self.emit_jump(jump));
+          }
+        }
+        for jump in jumps {
+          self.code().patch_jump(jump);
+        }
+      },
+
+      NodeKind::Not { operand } => {
+        self.compile_expr(*operand)?;
+        self.emit_op(OpCode::UnaryNot);
+      },
+
+      // 链式比较: 中间的操作数复制一份留作下一次比较的左侧；
+      // 某次比较为假时弹出留下的操作数，以 false 作为结果
+      NodeKind::Compare { left, ops, comparators } => {
+        self.compile_expr(*left)?;
+        let last = ops.len() - 1;
+        let mut cleanups = Vec::new();
+        for (op, comparator) in ops[..last].iter().zip(comparators) {
+          self.compile_expr(*comparator)?;
+          self.emit_op(OpCode::Dup);
+          self.emit_op(OpCode::RotThree);
+          self.emit_op(compare_opcode(op.kind()));
+          cleanups.push(self.emit_jump(OpCode::JumpIfFalse));
+          self.emit_op(OpCode::Pop);
+        }
+        self.compile_expr(comparators[last])?;
+        self.emit_op(compare_opcode(ops[last].kind()));
+        if !cleanups.is_empty() {
+          let end_jump = self.emit_jump(OpCode::Jump);
+          for jump in cleanups {
+            self.code().patch_jump(jump);
+          }
+          self.emit_op(OpCode::Swap);
+          self.emit_op(OpCode::Pop);
+          self.code().patch_jump(end_jump);
+        }
+      },
+
+      // 条件表达式: 与 if 语句相同，两个分支各自弹出条件
+      NodeKind::IfExp { test, body, orelse } => {
+        self.compile_expr(*test)?;
+        let else_jump = self.emit_jump(OpCode::JumpIfFalse);
+        self.emit_op(OpCode::Pop);
+        self.compile_expr(*body)?;
+        let end_jump = self.emit_jump(OpCode::Jump);
+        self.code().patch_jump(else_jump);
+        self.emit_op(OpCode::Pop);
+        self.compile_expr(*orelse)?;
+        self.code().patch_jump(end_jump);
+      },
+
+      // yield 表达式的值为恢复时传入的值
+      NodeKind::Yield { value } => {
+        match value {
+          Some(value) => self.compile_expr(*value)?,
+          None => self.load_const(Constant::None),
+        }
+        self.emit_op(OpCode::YieldValue);
+      },
+
+      // yield from: 子迭代器产出的值经由 Send 转交给调用方，子迭代器的返回值为表达式的值
+      NodeKind::YieldFrom { value } => {
+        self.compile_expr(*value)?;
+        self.emit_op(OpCode::GetIter);
+        self.load_const(Constant::None);
+        let start = self.code().offset();
+        let send = self.emit_jump(OpCode::Send);
+        self.emit_op(OpCode::YieldValue);
+        self.emit_op(OpCode::Loop);
+        self.emit_arg(start as u16);
+        self.code().patch_jump(send);
+      },
+
+      NodeKind::Lambda { args, body } => {
+        self.compile_function(expr_id, "<lambda>", args, |compiler| {
+          compiler.compile_expr(*body)?;
+          compiler.emit_op(OpCode::Return);
+          Ok(())
+        })?;
+      },
+
+      NodeKind::JoinedStr { values } => {
+        for value in values {
+          self.compile_expr(*value)?;
+        }
+        self.emit_op(OpCode::BuildString);
+        self.emit_arg(values.len() as u16);
+      },
+
+      NodeKind::FormattedValue { value, conversion, format_spec } => {
+        self.compile_expr(*value)?;
+        let mut flags = match conversion {
+          Some('s') => 1,
+          Some('r') => 2,
+          Some('a') => 3,
+          _ => 0,
+        };
+        if let Some(spec) = format_spec {
+          self.compile_expr(*spec)?;
+          flags |= 4;
+        }
+        self.emit_op(OpCode::FormatValue);
+        self.emit_arg(flags);
+      },
+
+      NodeKind::TemplateStr { .. } | NodeKind::Interpolation { .. } => {
+        return Err(CompileError::unsupported("t-string literals are not supported yet", *node.span()));
+      },
       
-      _ => todo!(),
+      _ => return Err(CompileError::unsupported("this expression is not supported yet", *node.span())),
     }
+    self.leave_location(outer);
     Ok(())
   }
 
+  /// 元组、列表、集合字面量；出现 *iterable 时，之前的元素一次性构建，之后逐个追加或展开，
+  /// 元组先按列表构建再转换
+  fn compile_display(&mut self, kind: &NodeKind, elts: &[NodeId]) -> Result<(), CompileError> {
+    let arena = self.arena.expect("a");
+    let (build, append, extend) = match kind {
+      NodeKind::Set { .. } => (OpCode::BuildSet, OpCode::SetAdd, OpCode::SetUpdate),
+      _ => (OpCode::BuildList, OpCode::ListAppend, OpCode::ListExtend),
+    };
+    let tuple = matches!(kind, NodeKind::Tuple { .. });
+    let Some(split) = elts.iter().position(|elt| matches!(arena.get(*elt).kind(), NodeKind::Starred { .. })) else {
+      for elt in elts {
+        self.compile_expr(*elt)?;
+      }
+      self.emit_op(if tuple { OpCode::BuildTuple } else { build });
+      self.emit_arg(elts.len() as u16);
+      return Ok(());
+    };
+    for elt in &elts[..split] {
+      self.compile_expr(*elt)?;
+    }
+    self.emit_op(build);
+    self.emit_arg(split as u16);
+    for elt in &elts[split..] {
+      match arena.get(*elt).kind() {
+        NodeKind::Starred { value } => {
+          self.compile_expr(*value)?;
+          self.emit_op(extend);
+        },
+        _ => {
+          self.compile_expr(*elt)?;
+          self.emit_op(append);
+        },
+      }
+      self.emit_arg(1);
+    }
+    if tuple {
+      self.emit_op(OpCode::ListToTuple);
+    }
+    Ok(())
+  }
+
+  /// 将栈顶的值存入赋值目标；Tuple / List 目标先解包再逐个存储
+  fn compile_store(&mut self, target: NodeId) -> Result<(), CompileError> {
+    let arena = self.arena.expect("a");
+    let node = arena.get(target);
+    let outer = self.enter_location(*node.span());
+    match node.kind() {
+      NodeKind::Name { id } => {
+        let name = arena.interner.resolve(*id).to_string();
+        self.store_name(name);
+      },
+      NodeKind::Attribute { value, attr } => {
+        self.compile_expr(*value)?;
+        let idx = self.code().add_name(arena.interner.resolve(*attr).to_string());
+        self.emit_op(OpCode::SetAttr);
+        self.emit_arg(idx);
+      },
+      NodeKind::Subscript { value, slice } => {
+        self.compile_expr(*value)?;
+        self.compile_expr(*slice)?;
+        self.emit_op(OpCode::StoreSubscr);
+      },
+      NodeKind::Tuple { elts } | NodeKind::List { elts } => {
+        let starred: Vec<usize> = elts.iter()
+          .enumerate()
+          .filter(|(_, elt)| matches!(arena.get(**elt).kind(), NodeKind::Starred { .. }))
+          .map(|(i, _)| i)
+          .collect();
+        match starred[..] {
+          [] => {
+            self.emit_op(OpCode::UnpackSequence);
+            self.emit_arg(elts.len() as u16);
+          },
+          [i] => {
+            let after = elts.len() - i - 1;
+            if i > 0xFF || after > 0xFF {
+              return Err(CompileError::new("too many expressions in star-unpacking assignment", *node.span()));
+            }
+            self.emit_op(OpCode::UnpackEx);
+            self.emit_arg((i | after << 8) as u16);
+          },
+          _ => {
+            let span = *arena.get(elts[starred[1]]).span();
+            return Err(CompileError::new("multiple starred expressions in assignment", span));
+          },
+        }
+        for elt in elts {
+          match arena.get(*elt).kind() {
+            NodeKind::Starred { value } => self.compile_store(*value)?,
+            _ => self.compile_store(*elt)?,
+          }
+        }
+      },
+      NodeKind::Starred { .. } => {
+        return Err(CompileError::new("starred assignment target must be in a list or tuple", *node.span()));
+      },
+      _ => return Err(CompileError::new("cannot assign to expression", *node.span())),
+    }
+    self.leave_location(outer);
+    Ok(())
+  }
+
+  /// 按符号表中的分类选择加载指令
+  fn load_name(&mut self, name: String) {
+    let (op, idx) = self.name_op(name, OpCode::LoadName, OpCode::LoadFast, OpCode::LoadGlobal, OpCode::LoadDeref);
+    self.emit_op(op);
+    self.emit_arg(idx);
+  }
+
+  /// 按符号表中的分类选择存储指令
+  fn store_name(&mut self, name: String) {
+    let (op, idx) = self.name_op(name, OpCode::StoreName, OpCode::StoreFast, OpCode::StoreGlobal, OpCode::StoreDeref);
+    self.emit_op(op);
+    self.emit_arg(idx);
+  }
+
+  /// 清除名字: 先赋值 None 再删除，即使子句体中已删除该名字也不会出错
+  fn unbind_name(&mut self, name: String) {
+    self.load_const(Constant::None);
+    self.store_name(name.clone());
+    let (op, idx) = self.name_op(name, OpCode::DeleteName, OpCode::DeleteFast, OpCode::DeleteGlobal, OpCode::DeleteDeref);
+    self.emit_op(op);
+    self.emit_arg(idx);
+  }
+
+  /// 模块级使用 name 指令；函数内局部变量使用 fast，全局变量使用 global，cell 与自由变量使用 deref；
+  /// 类体中的局部变量与未绑定的名字使用 name 指令，先在类的命名空间中查找
+  fn name_op(&mut self, name: String, name_op: OpCode, fast: OpCode, global: OpCode, deref: OpCode) -> (OpCode, u16) {
+    let scope = self.scope();
+    match (scope.kind, scope.lookup(&name)) {
+      (ScopeKind::Module, _) | (ScopeKind::Class, SymbolScope::Local | SymbolScope::GlobalImplicit) => {
+        (name_op, self.code().add_name(name))
+      },
+      (_, SymbolScope::Local) => (fast, self.code().add_varname(name)),
+      (_, SymbolScope::Cell | SymbolScope::Free) => (deref, self.code().deref_index(&name).expect("cell")),
+      (_, SymbolScope::GlobalExplicit | SymbolScope::GlobalImplicit) => (global, self.code().add_name(name)),
+    }
+  }
+
+  /// 当前代码对象的作用域
+  fn scope(&self) -> &Scope {
+    self.symtable.as_ref().expect("symtable").scope(*self.scopes.last().expect("scope"))
+  }
+
+  /// func 为方法中的 super 且方法至少有一个位置参数时，返回该参数的名字
+  fn super_receiver(&self, func: NodeId) -> Option<String> {
+    let arena = self.arena.expect("a");
+    let NodeKind::Name { id } = arena.get(func).kind() else { return None };
+    let code = self.code_stack.last().expect("code");
+    let method = self.scope().lookup("__class__") == SymbolScope::Free && code.arg_count > 0;
+    (arena.interner.resolve(*id) == "super" && method).then(|| code.varnames[0].clone())
+  }
+
+  /// 此后写入的指令定位到 span，返回之前的位置供 leave_location 恢复；
+  /// 子节点编译完后恢复为父节点的位置，使父节点自身的指令定位到父节点
+  fn enter_location(&mut self, span: Span) -> Option<Span> {
+    self.code().set_location(span);
+    self.location.replace(span)
+  }
+
+  fn leave_location(&mut self, outer: Option<Span>) {
+    if let Some(span) = outer {
+      self.code().set_location(span);
+    }
+    self.location = outer;
+  }
+
+  /// 写入跳转指令，目标地址留待 patch_jump 修补；返回指令的偏移
+  fn emit_jump(&mut self, op: OpCode) -> usize {
+    let offset = self.code().offset();
+    self.emit_op(op);
+    self.emit_arg(0xFFFF);
+    offset
+  }
+
+  fn load_const(&mut self, constant: Constant) {
+    let idx = self.code().add_const(constant);
+    self.emit_op(OpCode::LoadConst);
+    self.emit_arg(idx);
+  }
+
   fn emit_op(&mut self, op: OpCode) {
     self.code().emit_op(op);
   }
@@ -113,5 +1209,100 @@ impl<'a> Compiler<'a> {
 
 #[derive(Debug)]
 pub struct CompileError {
+  /// 对应的 Python 异常；None 表示编译器尚未支持的语法
+  pub kind: Option<ErrorKind>,
   pub message: String,
+  pub span: Span,
+}
+
+impl CompileError {
+  /// 编译期检查出的语法错误 (SyntaxError)
+  pub fn new<M: Into<String>>(message: M, span: Span) -> Self {
+    Self { kind: Some(ErrorKind::Syntax), message: message.into(), span }
+  }
+
+  /// 解析器接受但编译器尚未支持的语法
+  pub fn unsupported<M: Into<String>>(message: M, span: Span) -> Self {
+    Self { kind: None, message: message.into(), span }
+  }
+
+  /// 诊断与 JSON 输出中的错误代码
+  pub fn code(&self) -> &'static str {
+    self.kind.map_or("CompileError", |kind| kind.name())
+  }
+  
+  pub fn diagnostic(&self) -> Diagnostic {
+    Diagnostic::error(&self.message)
+      .with_code(self.code())
+      .with_primary(self.span, "")
+  }
+}
+
+impl fmt::Display for CompileError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.code(), self.message)
+  }
+}
+
+/// 比较运算符对应的比较指令
+fn compare_opcode(kind: &TokenKind) -> OpCode {
+  match kind {
+    TokenKind::EqEqual => OpCode::CompareEq,
+    TokenKind::NotEqual => OpCode::CompareNe,
+    TokenKind::Less => OpCode::CompareLt,
+    TokenKind::LessEqual => OpCode::CompareLe,
+    TokenKind::Greater => OpCode::CompareGt,
+    TokenKind::GreaterEqual => OpCode::CompareGe,
+    _ => unreachable!("not a comparison operator"),
+  }
+}
+
+/// 增量赋值运算符对应的二元运算指令
+fn aug_opcode(kind: &TokenKind) -> OpCode {
+  match kind {
+    TokenKind::PlusEqual => OpCode::BinaryAdd,
+    TokenKind::MinEqual => OpCode::BinarySub,
+    TokenKind::StarEqual => OpCode::BinaryMul,
+    TokenKind::AtEqual => OpCode::BinaryMatMul,
+    TokenKind::SlashEqual => OpCode::BinaryDiv,
+    TokenKind::PercentEqual => OpCode::BinaryMod,
+    TokenKind::AmperEqual => OpCode::BinaryAnd,
+    TokenKind::VBarEqual => OpCode::BinaryOr,
+    TokenKind::CircumflexEqual => OpCode::BinaryXor,
+    TokenKind::LeftShiftEqual => OpCode::BinaryLShift,
+    TokenKind::RightShiftEqual => OpCode::BinaryRShift,
+    TokenKind::DoubleStarEqual => OpCode::BinaryPow,
+    TokenKind::DoubleSlashEqual => OpCode::BinaryFloorDiv,
+    _ => unreachable!("not an augmented assignment operator"),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cathon_core::ast::{Lexer, Parser};
+
+  fn compile(code: &str) -> Result<CodeObject, CompileError> {
+    let mut lexer = Lexer::new(code);
+    let mut parser = Parser::new(&mut lexer);
+    let module = parser.parse().unwrap();
+    let arena = parser.arena;
+    Compiler::new().compile(&arena, module)
+  }
+
+  #[test]
+  fn compile_errors() {
+    // 编译期检查出的错误是 SyntaxError，尚未支持的语法仍报告为 CompileError
+    for (code, kind, message, span) in [
+      ("break", "SyntaxError", "'break' outside loop", Span::new(0, 5)),
+      ("if 1:\n  continue", "SyntaxError", "'continue' not properly in loop", Span::new(8, 16)),
+      ("class A:\n  return 1", "SyntaxError", "'return' outside function", Span::new(11, 19)),
+      ("*a", "SyntaxError", "can't use starred expression here", Span::new(0, 2)),
+      ("t'{x}'", "CompileError", "t-string literals are not supported yet", Span::new(0, 6)),
+    ] {
+      let err = compile(code).expect_err(code);
+      assert_eq!((err.code(), err.message.as_str(), err.span), (kind, message, span), "{}", code);
+    }
+    assert_eq!(compile("break").unwrap_err().to_string(), "SyntaxError: 'break' outside loop");
+  }
 }
@@ -1,10 +1,26 @@
-use crate::code::CodeObject;
+use crate::code::{CodeObject, Constant};
 use crate::opcode::OpCode;
 
 pub fn disassemble(code: &CodeObject) {
   println!("=== {} ===", code.name);
-  println!("Constants: {:?}", code.constants);
+  // 嵌套的代码对象只显示名字，其指令在后面单独列出
+  let constants: Vec<String> = code.constants.iter()
+    .map(|constant| match constant {
+      Constant::Code(inner) => format!("<code {}>", inner.name),
+      other => format!("{:?}", other),
+    })
+    .collect();
+  println!("Constants: [{}]", constants.join(", "));
   println!("Names: {:?}", code.names);
+  if !code.varnames.is_empty() {
+    println!("Varnames: {:?}", code.varnames);
+  }
+  if !code.cellvars.is_empty() {
+    println!("Cellvars: {:?}", code.cellvars);
+  }
+  if !code.freevars.is_empty() {
+    println!("Freevars: {:?}", code.freevars);
+  }
   println!();
   
   let mut offset = 0;
@@ -17,9 +33,17 @@ pub fn disassemble(code: &CodeObject) {
     // 读取参数 (大部分指令都有2字节参数)
     match op {
         OpCode::LoadConst | OpCode::LoadName | OpCode::StoreName |
-        OpCode::LoadFast | OpCode::StoreFast | OpCode::Jump |
-        OpCode::JumpIfFalse | OpCode::JumpIfTrue | OpCode::Loop |
-        OpCode::Call | OpCode::BuildList => {
+        OpCode::LoadFast | OpCode::StoreFast | OpCode::LoadGlobal | OpCode::StoreGlobal |
+        OpCode::LoadDeref | OpCode::StoreDeref | OpCode::LoadClosure | OpCode::DeleteName |
+        OpCode::DeleteFast | OpCode::DeleteGlobal | OpCode::DeleteDeref | OpCode::Jump |
+        OpCode::JumpIfFalse | OpCode::JumpIfTrue | OpCode::Loop | OpCode::ForIter |
+        OpCode::Call | OpCode::CallKw | OpCode::MakeFunction | OpCode::BuildClass |
+        OpCode::BuildList | OpCode::BuildTuple | OpCode::BuildDict |
+        OpCode::BuildString | OpCode::UnpackSequence | OpCode::BuildSlice |
+        OpCode::BuildSet | OpCode::UnpackEx | OpCode::ListAppend | OpCode::ListExtend |
+        OpCode::SetAdd | OpCode::SetUpdate | OpCode::MapAdd | OpCode::DictUpdate | OpCode::FormatValue |
+        OpCode::GetAttr | OpCode::SetAttr | OpCode::Raise | OpCode::InplaceOp |
+        OpCode::Send => {
             let arg = ((code.code[offset] as u16) << 8) | (code.code[offset + 1] as u16);
             print!(" {}", arg);
             offset += 2;
@@ -29,4 +53,18 @@ pub fn disassemble(code: &CodeObject) {
     
     println!();
   }
-}
\ No newline at end of file
+
+  if !code.exception_table.is_empty() {
+    println!("ExceptionTable:");
+    for entry in &code.exception_table {
+      println!("  {:04} to {:04} -> {:04} [{}]", entry.start, entry.end, entry.handler, entry.depth);
+    }
+  }
+
+  for constant in &code.constants {
+    if let Constant::Code(inner) = constant {
+      println!();
+      disassemble(inner);
+    }
+  }
+}
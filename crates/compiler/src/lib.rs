@@ -2,8 +2,13 @@ mod compiler;
 mod opcode;
 mod code;
 mod disassembler;
+mod symtable;
 pub use compiler::Compiler;
+pub use compiler::CompileError;
 pub use opcode::OpCode;
 pub use code::CodeObject;
 pub use code::Constant;
-pub use disassembler::disassemble;
\ No newline at end of file
+pub use code::ExceptionEntry;
+pub use disassembler::disassemble;
+pub use symtable::SymbolTable;
+pub use symtable::SymbolScope;
\ No newline at end of file
@@ -19,6 +19,22 @@ pub enum OpCode {
   LoadName = 14,
   /// 存储变量名: STORE_NAME index
   StoreName = 15,
+  /// 加载 cell 或自由变量的值: LOAD_DEREF index，index 先数 cellvars 再数 freevars
+  LoadDeref = 16,
+  /// 存储 cell 或自由变量的值: STORE_DEREF index
+  StoreDeref = 17,
+  /// 加载 cell 本身 (用于构建闭包): LOAD_CLOSURE index
+  LoadClosure = 18,
+
+  // ============ 删除变量 ============
+  /// 删除变量名: DELETE_NAME index
+  DeleteName = 120,
+  /// 删除局部变量: DELETE_FAST index
+  DeleteFast = 121,
+  /// 删除全局变量: DELETE_GLOBAL index
+  DeleteGlobal = 122,
+  /// 清空 cell 或自由变量: DELETE_DEREF index
+  DeleteDeref = 123,
 
   // ============ 栈操作 ============
   /// 弹出栈顶
@@ -27,6 +43,10 @@ pub enum OpCode {
   Dup = 21,
   /// 交换栈顶两个元素
   Swap = 22,
+  /// 复制栈顶两个元素: [a, b] -> [a, b, a, b]
+  DupTwo = 23,
+  /// 栈顶元素下移两位: [a, b, c] -> [c, a, b]
+  RotThree = 24,
 
   // ============ 二元运算 ============
   BinaryAdd = 30,
@@ -36,11 +56,24 @@ pub enum OpCode {
   BinaryFloorDiv = 34,
   BinaryMod = 35,
   BinaryPow = 36,
+  /// 矩阵乘法: a @ b
+  BinaryMatMul = 37,
+  /// 增量赋值的运算: INPLACE_OP op，op 为对应的二元运算的操作码
+  InplaceOp = 38,
 
   // ============ 一元运算 ============
   UnaryNeg = 40,
   UnaryNot = 41,
   UnaryPos = 42,
+  /// 按位取反: ~x
+  UnaryInvert = 43,
+
+  // ============ 位运算 ============
+  BinaryLShift = 44,
+  BinaryRShift = 45,
+  BinaryAnd = 46,
+  BinaryOr = 47,
+  BinaryXor = 48,
 
   // ============ 比较运算 ============
   CompareEq = 50,
@@ -65,8 +98,20 @@ pub enum OpCode {
   Call = 70,
   /// 返回
   Return = 71,
-  /// 创建函数: MAKE_FUNCTION
+  /// 创建函数: MAKE_FUNCTION flags，栈顶为代码对象；flags & 1 时其下有默认值元组，
+  /// flags & 2 时有仅关键字参数默认值字典 (位于默认值元组之上)，
+  /// flags & 4 时有 cell 组成的闭包元组 (紧挨代码对象之下)
   MakeFunction = 72,
+  /// 带关键字参数的调用: CALL_KW argc，栈顶为关键字参数名元组，argc 包含关键字参数的值
+  CallKw = 73,
+  /// 生成器产出栈顶的值并挂起，恢复时压入传入的值: YIELD_VALUE
+  YieldValue = 74,
+  /// 创建类: BUILD_CLASS count，栈上依次为类体函数、类名与 count 个基类；
+  /// 在新的命名空间中执行类体，再由命名空间中的属性创建类
+  BuildClass = 75,
+  /// yield from 的委托: SEND target，[iter, value] -> [iter, yielded]；
+  /// 子迭代器结束时弹出两者，压入其返回值并跳转到 target
+  Send = 76,
 
   // ============ 容器操作 ============
   /// 构建列表: BUILD_LIST count
@@ -79,16 +124,57 @@ pub enum OpCode {
   BinarySubscr = 83,
   /// 下标赋值: STORE_SUBSCR
   StoreSubscr = 84,
+  /// 拼接字符串: BUILD_STRING count
+  BuildString = 85,
+  /// 序列解包: UNPACK_SEQUENCE count，解包后第一个元素位于栈顶
+  UnpackSequence = 86,
+  /// 构建切片: BUILD_SLICE count，count 为 2 (start, stop) 或 3 (start, stop, step)
+  BuildSlice = 87,
+  /// 构建集合: BUILD_SET count
+  BuildSet = 88,
+  /// 带星号的序列解包: UNPACK_EX counts，低 8 位为星号之前的目标数，高 8 位为之后的目标数；
+  /// 星号目标得到列表，解包后第一个元素位于栈顶
+  UnpackEx = 89,
+
+  // ============ 容器追加 (用于带解包的字面量与推导式) ============
+  /// 弹出栈顶并追加到自栈顶起第 i 个元素 (列表): LIST_APPEND i
+  ListAppend = 100,
+  /// 弹出可迭代对象并展开追加到第 i 个元素 (列表): LIST_EXTEND i
+  ListExtend = 101,
+  /// 弹出栈顶并加入第 i 个元素 (集合): SET_ADD i
+  SetAdd = 102,
+  /// 弹出可迭代对象并加入第 i 个元素 (集合): SET_UPDATE i
+  SetUpdate = 103,
+  /// 弹出值与键并加入第 i 个元素 (字典): MAP_ADD i
+  MapAdd = 104,
+  /// 弹出字典并合并到第 i 个元素 (字典): DICT_UPDATE i
+  DictUpdate = 105,
+  /// 将栈顶的列表转换为元组
+  ListToTuple = 106,
+
+  // ============ 异常处理 ============
+  /// 抛出异常: RAISE argc，argc 为 1 时抛出栈顶的异常 (或异常类型的实例)，为 0 时重新抛出正在处理的异常
+  Raise = 110,
+  /// 弹出栈顶的异常并重新抛出，不追加回溯
+  Reraise = 111,
+  /// 将栈顶的异常记为当前帧正在处理的异常 (进入 except / finally 的异常路径)
+  PushExcInfo = 112,
+  /// 结束对最近一个异常的处理
+  PopExcept = 113,
+  /// [exc, type] -> [exc, bool]，type 可以是异常类型的元组
+  CheckExcMatch = 114,
 
   // ============ 其他 ============
   /// 获取属性: GET_ATTR index
   GetAttr = 90,
   /// 设置属性: SET_ATTR index
   SetAttr = 91,
-  /// 获取迭代器
+  /// 获取迭代器: GET_ITER，将栈顶替换为其迭代器
   GetIter = 92,
-  /// 迭代下一个
+  /// 迭代下一个: FOR_ITER target，压入下一个元素；耗尽时弹出迭代器并跳转到 target
   ForIter = 93,
+  /// 格式化值: FORMAT_VALUE flags，低 2 位为转换 (0 无, 1 !s, 2 !r, 3 !a)，4 表示栈顶有格式说明
+  FormatValue = 94,
 
   /// 空操作
   Nop = 255,
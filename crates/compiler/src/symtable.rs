@@ -0,0 +1,641 @@
+use cathon_core::ast::{Arena, Arguments, Comprehension, NodeId, NodeKind};
+use cathon_core::Span;
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+use crate::compiler::CompileError;
+
+/// 名字在作用域中的 "定义" 方式，按位组合
+const DEF_LOCAL: u8 = 1;
+const DEF_PARAM: u8 = 2;
+const DEF_GLOBAL: u8 = 4;
+const DEF_NONLOCAL: u8 = 8;
+const USE: u8 = 16;
+/// 类体中的局部变量同时被方法作为外层函数的自由变量引用，类体需要捕获该 cell 传给方法
+const DEF_FREE_CLASS: u8 = 32;
+
+/// 名字最终的分类，决定编译器使用的加载/存储指令
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolScope {
+  /// 函数的局部变量 (LoadFast / StoreFast)
+  Local,
+  /// global 声明的名字 (LoadGlobal / StoreGlobal)
+  GlobalExplicit,
+  /// 未在任何外层函数中绑定的名字，按全局变量或内置名处理
+  GlobalImplicit,
+  /// 引用外层函数的局部变量 (LoadDeref / StoreDeref)
+  Free,
+  /// 被内层函数引用的局部变量，存放在 cell 中 (LoadDeref / StoreDeref)
+  Cell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+  Module,
+  /// 类体：名字存放在类的命名空间中，对其中的函数不可见
+  Class,
+  Function,
+}
+
+#[derive(Debug)]
+struct Symbol {
+  flags: u8,
+  scope: SymbolScope,
+  /// global / nonlocal 声明语句的位置
+  declared: Option<Span>,
+}
+
+/// 一个模块或函数的作用域
+#[derive(Debug)]
+pub struct Scope {
+  pub kind: ScopeKind,
+  /// 名字按首次出现的顺序排列
+  symbols: IndexMap<String, Symbol>,
+  /// 直接嵌套的函数定义、lambda 与推导式节点
+  children: Vec<NodeId>,
+  /// 含有 yield 的函数与生成器表达式，调用时返回生成器
+  pub generator: bool,
+  /// 推导式的作用域记录其种类 (如 list comprehension)，其中不能使用 yield
+  comprehension: Option<&'static str>,
+}
+
+impl Scope {
+  fn new(kind: ScopeKind) -> Self {
+    Self { kind, symbols: IndexMap::new(), children: Vec::new(), generator: false, comprehension: None }
+  }
+
+  /// 名字的分类；未出现过的名字视为全局变量
+  pub fn lookup(&self, name: &str) -> SymbolScope {
+    self.symbols.get(name).map_or(SymbolScope::GlobalImplicit, |symbol| symbol.scope)
+  }
+
+  /// 需要为本作用域新建 cell 的变量
+  pub fn cellvars(&self) -> Vec<String> {
+    self.names(SymbolScope::Cell)
+  }
+
+  /// 从外层作用域捕获的变量，顺序与 MakeFunction 的闭包元组一致
+  pub fn freevars(&self) -> Vec<String> {
+    self.symbols.iter()
+      .filter(|(_, symbol)| symbol.scope == SymbolScope::Free || symbol.flags & DEF_FREE_CLASS != 0)
+      .map(|(name, _)| name.clone())
+      .collect()
+  }
+
+  fn names(&self, scope: SymbolScope) -> Vec<String> {
+    self.symbols.iter()
+      .filter(|(_, symbol)| symbol.scope == scope)
+      .map(|(name, _)| name.clone())
+      .collect()
+  }
+}
+
+/// 符号表：模块与每个函数定义、类定义、lambda、推导式节点各对应一个作用域
+#[derive(Debug)]
+pub struct SymbolTable {
+  scopes: HashMap<NodeId, Scope>,
+}
+
+impl SymbolTable {
+  /// 收集每个作用域中名字的使用与定义，然后自外向内确定每个名字的分类
+  pub fn build(arena: &Arena, module: NodeId) -> Result<Self, CompileError> {
+    let mut builder = Builder { arena, scopes: HashMap::new(), stack: Vec::new() };
+    builder.enter(ScopeKind::Module);
+    let NodeKind::Module { body } = arena.get(module).kind() else { panic!("need module") };
+    builder.visit_body(body)?;
+    builder.leave(module);
+
+    let mut table = Self { scopes: builder.scopes };
+    table.analyze(module, &HashSet::new())?;
+    Ok(table)
+  }
+
+  pub fn scope(&self, node: NodeId) -> &Scope {
+    &self.scopes[&node]
+  }
+
+  /// bound 为外层函数中绑定的名字；返回本作用域 (含其内层) 需要从外层捕获的名字
+  fn analyze(&mut self, node: NodeId, bound: &HashSet<String>) -> Result<HashSet<String>, CompileError> {
+    let scope = self.scopes.get_mut(&node).expect("scope");
+    let kind = scope.kind;
+    let mut inner_bound = bound.clone();
+    // 方法中的零参数 super() 通过隐式的 __class__ cell 取得定义它的类
+    if kind == ScopeKind::Class {
+      inner_bound.insert("__class__".to_string());
+    }
+    for (name, symbol) in scope.symbols.iter_mut() {
+      symbol.scope = if symbol.flags & DEF_GLOBAL != 0 {
+        inner_bound.remove(name);
+        SymbolScope::GlobalExplicit
+      } else if kind == ScopeKind::Module {
+        SymbolScope::GlobalImplicit
+      } else if symbol.flags & DEF_NONLOCAL != 0 {
+        if !bound.contains(name) {
+          let span = symbol.declared.expect("nonlocal declaration");
+          return Err(CompileError::new(format!("no binding for nonlocal '{}' found", name), span));
+        }
+        SymbolScope::Free
+      } else if symbol.flags & (DEF_LOCAL | DEF_PARAM) != 0 {
+        if kind == ScopeKind::Function {
+          inner_bound.insert(name.clone());
+        }
+        SymbolScope::Local
+      } else if bound.contains(name) {
+        SymbolScope::Free
+      } else {
+        SymbolScope::GlobalImplicit
+      };
+    }
+
+    let children = scope.children.clone();
+    let mut child_free = HashSet::new();
+    for child in children {
+      child_free.extend(self.analyze(child, &inner_bound)?);
+    }
+
+    // 内层引用的局部变量改为 cell；只经过本作用域的自由变量也要捕获，以便传给内层。
+    // 类的局部变量对内层不可见，内层引用的同名变量来自更外层的函数
+    let scope = self.scopes.get_mut(&node).expect("scope");
+    for name in child_free {
+      match scope.symbols.get_mut(&name) {
+        _ if kind == ScopeKind::Class && name == "__class__" => {
+          scope.symbols.insert(name, Symbol { flags: 0, scope: SymbolScope::Cell, declared: None });
+        },
+        Some(symbol) if symbol.scope == SymbolScope::Local && kind == ScopeKind::Class => symbol.flags |= DEF_FREE_CLASS,
+        Some(symbol) if symbol.scope == SymbolScope::Local => symbol.scope = SymbolScope::Cell,
+        Some(_) => {},
+        None => {
+          scope.symbols.insert(name, Symbol { flags: 0, scope: SymbolScope::Free, declared: None });
+        },
+      }
+    }
+    Ok(scope.freevars().into_iter().collect())
+  }
+}
+
+struct Builder<'a> {
+  arena: &'a Arena,
+  scopes: HashMap<NodeId, Scope>,
+  /// 正在收集的作用域，最内层在末尾
+  stack: Vec<Scope>,
+}
+
+impl Builder<'_> {
+  fn enter(&mut self, kind: ScopeKind) {
+    self.stack.push(Scope::new(kind));
+  }
+
+  fn leave(&mut self, node: NodeId) {
+    let scope = self.stack.pop().expect("scope");
+    if let Some(parent) = self.stack.last_mut() {
+      parent.children.push(node);
+    }
+    self.scopes.insert(node, scope);
+  }
+
+  fn scope(&mut self) -> &mut Scope {
+    self.stack.last_mut().expect("scope")
+  }
+
+  /// 记录名字的一次定义或使用
+  fn add(&mut self, name: &str, flag: u8) {
+    let symbols = &mut self.scope().symbols;
+    match symbols.get_mut(name) {
+      Some(symbol) => symbol.flags |= flag,
+      None => {
+        symbols.insert(name.to_string(), Symbol { flags: flag, scope: SymbolScope::GlobalImplicit, declared: None });
+      },
+    }
+  }
+
+  /// global / nonlocal 声明必须出现在该名字的任何使用与赋值之前
+  fn declare(&mut self, name: &str, flag: u8, span: Span) -> Result<(), CompileError> {
+    let (keyword, other) = if flag == DEF_GLOBAL { ("global", DEF_NONLOCAL) } else { ("nonlocal", DEF_GLOBAL) };
+    let scope = self.scope();
+    if flag == DEF_NONLOCAL && scope.kind == ScopeKind::Module {
+      return Err(CompileError::new("nonlocal declaration not allowed at module level", span));
+    }
+    if let Some(symbol) = scope.symbols.get(name) {
+      let message = if symbol.flags & DEF_PARAM != 0 {
+        format!("name '{}' is parameter and {}", name, keyword)
+      } else if symbol.flags & other != 0 {
+        format!("name '{}' is nonlocal and global", name)
+      } else if symbol.flags & DEF_LOCAL != 0 {
+        format!("name '{}' is assigned to before {} declaration", name, keyword)
+      } else if symbol.flags & USE != 0 {
+        format!("name '{}' is used prior to {} declaration", name, keyword)
+      } else {
+        String::new()
+      };
+      if !message.is_empty() {
+        return Err(CompileError::new(message, span));
+      }
+    }
+    self.add(name, flag);
+    if let Some(symbol) = self.scope().symbols.get_mut(name) {
+      symbol.declared = Some(span);
+    }
+    Ok(())
+  }
+
+  fn visit_body(&mut self, body: &[NodeId]) -> Result<(), CompileError> {
+    for stmt in body {
+      self.visit_stmt(*stmt)?;
+    }
+    Ok(())
+  }
+
+  fn visit_stmt(&mut self, node_id: NodeId) -> Result<(), CompileError> {
+    let arena = self.arena;
+    let node = arena.get(node_id);
+    match node.kind() {
+      NodeKind::Expr { value } => self.visit_expr(*value),
+      NodeKind::Assign { targets, value } => {
+        self.visit_expr(*value)?;
+        for target in targets {
+          self.visit_target(*target)?;
+        }
+        Ok(())
+      },
+      NodeKind::AugAssign { target, value, .. } => {
+        self.visit_expr(*value)?;
+        if let NodeKind::Name { id } = arena.get(*target).kind() {
+          let name = arena.interner.resolve(*id);
+          self.add(name, USE | DEF_LOCAL);
+          Ok(())
+        } else {
+          self.visit_expr(*target)
+        }
+      },
+      NodeKind::If { test, body, orelse } | NodeKind::While { test, body, orelse } => {
+        self.visit_expr(*test)?;
+        self.visit_body(body)?;
+        self.visit_body(orelse)
+      },
+      NodeKind::For { target, iter, body, orelse } => {
+        self.visit_expr(*iter)?;
+        self.visit_target(*target)?;
+        self.visit_body(body)?;
+        self.visit_body(orelse)
+      },
+      NodeKind::FunctionDef { name, args, body } => {
+        self.visit_defaults(args)?;
+        self.add(arena.interner.resolve(*name), DEF_LOCAL);
+        self.enter_function(args);
+        self.visit_body(body)?;
+        self.leave(node_id);
+        Ok(())
+      },
+      NodeKind::ClassDef { name, bases, body } => {
+        for base in bases {
+          self.visit_expr(*base)?;
+        }
+        self.add(arena.interner.resolve(*name), DEF_LOCAL);
+        self.enter(ScopeKind::Class);
+        self.visit_body(body)?;
+        self.leave(node_id);
+        Ok(())
+      },
+      NodeKind::Return { value: Some(value) } | NodeKind::Raise { exc: Some(value) } => self.visit_expr(*value),
+      NodeKind::Try { body, handlers, orelse, finalbody } => {
+        self.visit_body(body)?;
+        for handler in handlers {
+          let NodeKind::ExceptHandler { ty, name, body } = arena.get(*handler).kind() else {
+            unreachable!("except handler");
+          };
+          if let Some(ty) = ty {
+            self.visit_expr(*ty)?;
+          }
+          if let Some(name) = name {
+            self.add(arena.interner.resolve(*name), DEF_LOCAL);
+          }
+          self.visit_body(body)?;
+        }
+        self.visit_body(orelse)?;
+        self.visit_body(finalbody)
+      },
+      NodeKind::Global { names } | NodeKind::Nonlocal { names } => {
+        let flag = if matches!(node.kind(), NodeKind::Global { .. }) { DEF_GLOBAL } else { DEF_NONLOCAL };
+        for name in names {
+          self.declare(arena.interner.resolve(*name), flag, *node.span())?;
+        }
+        Ok(())
+      },
+      _ => Ok(()),
+    }
+  }
+
+  /// 进入函数作用域并定义形参
+  fn enter_function(&mut self, args: &Arguments) {
+    self.enter(ScopeKind::Function);
+    let params = args.args.iter()
+      .chain(&args.kwonlyargs)
+      .chain(&args.vararg)
+      .chain(&args.kwarg);
+    for param in params {
+      self.add(self.arena.interner.resolve(*param), DEF_PARAM);
+    }
+  }
+
+  /// 推导式是独立的函数作用域：最外层的可迭代对象在当前作用域求值，其迭代器作为隐式参数 .0 传入
+  fn visit_comprehension(&mut self, node_id: NodeId, generators: &[Comprehension], elts: &[NodeId]) -> Result<(), CompileError> {
+    self.visit_expr(generators[0].iter)?;
+    self.enter(ScopeKind::Function);
+    let (what, generator) = match self.arena.get(node_id).kind() {
+      NodeKind::ListComp { .. } => ("list comprehension", false),
+      NodeKind::SetComp { .. } => ("set comprehension", false),
+      NodeKind::DictComp { .. } => ("dict comprehension", false),
+      _ => ("generator expression", true),
+    };
+    let scope = self.scope();
+    scope.comprehension = Some(what);
+    scope.generator = generator;
+    self.add(".0", DEF_PARAM);
+    for (i, generator) in generators.iter().enumerate() {
+      if i > 0 {
+        self.visit_expr(generator.iter)?;
+      }
+      self.visit_target(generator.target)?;
+      for cond in &generator.ifs {
+        self.visit_expr(*cond)?;
+      }
+    }
+    for elt in elts {
+      self.visit_expr(*elt)?;
+    }
+    self.leave(node_id);
+    Ok(())
+  }
+
+  /// yield 使所在的函数成为生成器；不能出现在函数之外或推导式中
+  fn mark_generator(&mut self, node_id: NodeId, keyword: &str) -> Result<(), CompileError> {
+    let span = *self.arena.get(node_id).span();
+    let scope = self.scope();
+    if let Some(what) = scope.comprehension {
+      return Err(CompileError::new(format!("'yield' inside {}", what), span));
+    }
+    if scope.kind != ScopeKind::Function {
+      return Err(CompileError::new(format!("'{}' outside function", keyword), span));
+    }
+    scope.generator = true;
+    Ok(())
+  }
+
+  /// 默认值在定义函数的作用域中求值
+  fn visit_defaults(&mut self, args: &Arguments) -> Result<(), CompileError> {
+    for default in args.defaults.iter().chain(args.kw_defaults.iter().flatten()) {
+      self.visit_expr(*default)?;
+    }
+    Ok(())
+  }
+
+  /// 赋值目标中的名字是定义，其余部分 (如 a.b、a[i] 中的 a 与 i) 是使用
+  fn visit_target(&mut self, node_id: NodeId) -> Result<(), CompileError> {
+    let arena = self.arena;
+    match arena.get(node_id).kind() {
+      NodeKind::Name { id } => {
+        self.add(arena.interner.resolve(*id), DEF_LOCAL);
+        Ok(())
+      },
+      NodeKind::Tuple { elts } | NodeKind::List { elts } => {
+        for elt in elts {
+          self.visit_target(*elt)?;
+        }
+        Ok(())
+      },
+      NodeKind::Starred { value } => self.visit_target(*value),
+      _ => self.visit_expr(node_id),
+    }
+  }
+
+  fn visit_expr(&mut self, node_id: NodeId) -> Result<(), CompileError> {
+    let arena = self.arena;
+    match arena.get(node_id).kind() {
+      NodeKind::Name { id } => {
+        let name = arena.interner.resolve(*id);
+        self.add(name, USE);
+        if name == "super" && self.scope().kind == ScopeKind::Function {
+          self.add("__class__", USE);
+        }
+      },
+      NodeKind::BinOp { left, right, .. } => {
+        self.visit_expr(*left)?;
+        self.visit_expr(*right)?;
+      },
+      NodeKind::UnaryOp { operand, .. } | NodeKind::Not { operand } => self.visit_expr(*operand)?,
+      NodeKind::BoolOp { values, .. } => {
+        for value in values {
+          self.visit_expr(*value)?;
+        }
+      },
+      NodeKind::Compare { left, comparators, .. } => {
+        self.visit_expr(*left)?;
+        for comparator in comparators {
+          self.visit_expr(*comparator)?;
+        }
+      },
+      NodeKind::IfExp { test, body, orelse } => {
+        self.visit_expr(*test)?;
+        self.visit_expr(*body)?;
+        self.visit_expr(*orelse)?;
+      },
+      NodeKind::Lambda { args, body } => {
+        self.visit_defaults(args)?;
+        self.enter_function(args);
+        self.visit_expr(*body)?;
+        self.leave(node_id);
+      },
+      NodeKind::ListComp { elt, generators } | NodeKind::SetComp { elt, generators }
+        | NodeKind::GeneratorExp { elt, generators } => {
+        self.visit_comprehension(node_id, generators, &[*elt])?;
+      },
+      NodeKind::DictComp { key, value, generators } => {
+        self.visit_comprehension(node_id, generators, &[*key, *value])?;
+      },
+      NodeKind::Call { func, args, keywords } => {
+        self.visit_expr(*func)?;
+        for arg in args.iter().chain(keywords.iter().map(|(_, value)| value)) {
+          self.visit_expr(*arg)?;
+        }
+      },
+      NodeKind::Attribute { value, .. } | NodeKind::Starred { value } => self.visit_expr(*value)?,
+      NodeKind::Yield { value } => {
+        if let Some(value) = value {
+          self.visit_expr(*value)?;
+        }
+        self.mark_generator(node_id, "yield")?;
+      },
+      NodeKind::YieldFrom { value } => {
+        self.visit_expr(*value)?;
+        self.mark_generator(node_id, "yield from")?;
+      },
+      NodeKind::Dict { keys, values } => {
+        for node in keys.iter().flatten().chain(values) {
+          self.visit_expr(*node)?;
+        }
+      },
+      NodeKind::Subscript { value, slice } => {
+        self.visit_expr(*value)?;
+        self.visit_expr(*slice)?;
+      },
+      NodeKind::Slice { lower, upper, step } => {
+        for part in [lower, upper, step].into_iter().flatten() {
+          self.visit_expr(*part)?;
+        }
+      },
+      NodeKind::Tuple { elts } | NodeKind::List { elts } | NodeKind::Set { elts }
+        | NodeKind::JoinedStr { values: elts } | NodeKind::TemplateStr { values: elts } => {
+        for elt in elts {
+          self.visit_expr(*elt)?;
+        }
+      },
+      NodeKind::FormattedValue { value, format_spec, .. } | NodeKind::Interpolation { value, format_spec, .. } => {
+        self.visit_expr(*value)?;
+        if let Some(spec) = format_spec {
+          self.visit_expr(*spec)?;
+        }
+      },
+      _ => {},
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cathon_core::ast::{Lexer, Parser};
+
+  fn build(code: &str) -> (Arena, NodeId, Result<SymbolTable, CompileError>) {
+    let mut lexer = Lexer::new(code);
+    let mut parser = Parser::new(&mut lexer);
+    let module = parser.parse().unwrap();
+    let arena = parser.arena;
+    let table = SymbolTable::build(&arena, module);
+    (arena, module, table)
+  }
+
+  #[test]
+  fn classify_names() {
+    let code = "g = 1\ndef outer(a):\n  b = 2\n  def inner():\n    nonlocal b\n    b += a\n    return g + len(b)\n  return inner\n";
+    let (arena, module, table) = build(code);
+    let table = table.unwrap();
+    let NodeKind::Module { body } = arena.get(module).kind() else { panic!("module") };
+    let NodeKind::FunctionDef { body: outer_body, .. } = arena.get(body[1]).kind() else { panic!("def") };
+
+    let module = table.scope(module);
+    assert_eq!(module.lookup("g"), SymbolScope::GlobalImplicit);
+    assert_eq!(module.lookup("outer"), SymbolScope::GlobalImplicit);
+
+    let outer = table.scope(body[1]);
+    assert_eq!(outer.cellvars(), vec!["a", "b"]);
+    assert_eq!(outer.lookup("inner"), SymbolScope::Local);
+
+    let inner = table.scope(outer_body[1]);
+    assert_eq!(inner.freevars(), vec!["b", "a"]);
+    assert_eq!(inner.lookup("g"), SymbolScope::GlobalImplicit);
+    assert_eq!(inner.lookup("len"), SymbolScope::GlobalImplicit);
+  }
+
+  #[test]
+  fn pass_through_and_global() {
+    // mid 本身不使用 x，但需要捕获 x 传给 inner；global 声明遮蔽外层的绑定
+    let code = "def f():\n  x = 1\n  y = 2\n  def mid():\n    global y\n    def inner():\n      return x + y\n    return inner\n  return mid\n";
+    let (arena, module, table) = build(code);
+    let table = table.unwrap();
+    let NodeKind::Module { body } = arena.get(module).kind() else { panic!("module") };
+    let NodeKind::FunctionDef { body: f_body, .. } = arena.get(body[0]).kind() else { panic!("def") };
+    let NodeKind::FunctionDef { body: mid_body, .. } = arena.get(f_body[2]).kind() else { panic!("def") };
+
+    assert_eq!(table.scope(body[0]).cellvars(), vec!["x"]);
+    assert_eq!(table.scope(body[0]).lookup("y"), SymbolScope::Local);
+    assert_eq!(table.scope(f_body[2]).freevars(), vec!["x"]);
+    assert_eq!(table.scope(f_body[2]).lookup("y"), SymbolScope::GlobalExplicit);
+    let inner = table.scope(mid_body[1]);
+    assert_eq!((inner.lookup("x"), inner.lookup("y")), (SymbolScope::Free, SymbolScope::GlobalImplicit));
+  }
+
+  #[test]
+  fn comprehension_scopes() {
+    // 最外层的可迭代对象属于外层作用域，推导式中的目标是其自身的局部变量
+    let code = "def f(n):\n  x = 1\n  return [x + n for x in range(n) for y in x]\n";
+    let (arena, module, table) = build(code);
+    let table = table.unwrap();
+    let NodeKind::Module { body } = arena.get(module).kind() else { panic!("module") };
+    let NodeKind::FunctionDef { body: f_body, .. } = arena.get(body[0]).kind() else { panic!("def") };
+    let NodeKind::Return { value: Some(comp) } = arena.get(f_body[1]).kind() else { panic!("return") };
+
+    let f = table.scope(body[0]);
+    assert_eq!(f.cellvars(), vec!["n"]);
+    assert_eq!(f.lookup("x"), SymbolScope::Local);
+    assert_eq!(f.lookup("range"), SymbolScope::GlobalImplicit);
+    let comp = table.scope(*comp);
+    assert_eq!((comp.lookup(".0"), comp.lookup("x"), comp.lookup("y")), (SymbolScope::Local, SymbolScope::Local, SymbolScope::Local));
+    assert_eq!(comp.freevars(), vec!["n"]);
+  }
+
+  #[test]
+  fn class_scopes() {
+    // 类体的局部变量对方法不可见；方法中的 y 来自 outer，经类体捕获；super() 引用隐式的 __class__
+    let code = "def outer():\n  y = 1\n  class A:\n    x = y\n    y = 2\n    def f(self):\n      return super().f() + x + y\n  return A\n";
+    let (arena, module, table) = build(code);
+    let table = table.unwrap();
+    let NodeKind::Module { body } = arena.get(module).kind() else { panic!("module") };
+    let NodeKind::FunctionDef { body: outer_body, .. } = arena.get(body[0]).kind() else { panic!("def") };
+    let NodeKind::ClassDef { body: class_body, .. } = arena.get(outer_body[1]).kind() else { panic!("class") };
+
+    let outer = table.scope(body[0]);
+    assert_eq!((outer.cellvars(), outer.lookup("A")), (vec!["y".to_string()], SymbolScope::Local));
+    let class = table.scope(outer_body[1]);
+    assert_eq!(class.kind, ScopeKind::Class);
+    assert_eq!((class.lookup("x"), class.lookup("y"), class.lookup("f")), (SymbolScope::Local, SymbolScope::Local, SymbolScope::Local));
+    assert_eq!((class.cellvars(), class.freevars()), (vec!["__class__".to_string()], vec!["y".to_string()]));
+    let method = table.scope(class_body[2]);
+    assert_eq!(method.freevars(), vec!["__class__", "y"]);
+    assert_eq!((method.lookup("x"), method.lookup("super")), (SymbolScope::GlobalImplicit, SymbolScope::GlobalImplicit));
+  }
+
+  #[test]
+  fn generator_scopes() {
+    // 含有 yield 的函数与生成器表达式调用时返回生成器，列表推导式与普通函数不是
+    let code = "def f():\n  x = yield\n  return (i for i in x)\ndef g(x):\n  return [i for i in x]\n";
+    let (arena, module, table) = build(code);
+    let table = table.unwrap();
+    let NodeKind::Module { body } = arena.get(module).kind() else { panic!("module") };
+    let NodeKind::FunctionDef { body: f_body, .. } = arena.get(body[0]).kind() else { panic!("def") };
+    let NodeKind::FunctionDef { body: g_body, .. } = arena.get(body[1]).kind() else { panic!("def") };
+    let NodeKind::Return { value: Some(genexpr) } = arena.get(f_body[1]).kind() else { panic!("return") };
+    let NodeKind::Return { value: Some(listcomp) } = arena.get(g_body[0]).kind() else { panic!("return") };
+    assert!(table.scope(body[0]).generator && table.scope(*genexpr).generator);
+    assert!(!table.scope(body[1]).generator && !table.scope(*listcomp).generator);
+
+    for (code, message, span) in [
+      ("yield 1", "'yield' outside function", Span::new(0, 7)),
+      ("class A:\n  x = yield from y", "'yield from' outside function", Span::new(15, 27)),
+      ("def f(x):\n  return [(yield i) for i in x]", "'yield' inside list comprehension", Span::new(21, 28)),
+      ("def f(x):\n  return {i: (yield) for i in x}", "'yield' inside dict comprehension", Span::new(24, 29)),
+      ("def f(x):\n  return ((yield from i) for i in x)", "'yield' inside generator expression", Span::new(21, 33)),
+    ] {
+      let (_, _, table) = build(code);
+      let err = table.expect_err(code);
+      assert_eq!((err.message.as_str(), err.span), (message, span), "{}", code);
+    }
+  }
+
+  #[test]
+  fn declaration_errors() {
+    for (code, message, span) in [
+      ("nonlocal x", "nonlocal declaration not allowed at module level", Span::new(0, 10)),
+      ("def f():\n  nonlocal x", "no binding for nonlocal 'x' found", Span::new(11, 21)),
+      ("x = 1\ndef f():\n  nonlocal x", "no binding for nonlocal 'x' found", Span::new(17, 27)),
+      ("def f(a):\n  global a", "name 'a' is parameter and global", Span::new(12, 20)),
+      ("def f(a):\n  def g():\n    nonlocal a, a\n    global a", "name 'a' is nonlocal and global", Span::new(43, 51)),
+      ("def f():\n  x = 1\n  global x", "name 'x' is assigned to before global declaration", Span::new(19, 27)),
+      ("def f():\n  print(x)\n  global x", "name 'x' is used prior to global declaration", Span::new(22, 30)),
+    ] {
+      let (_, _, table) = build(code);
+      let err = table.expect_err(code);
+      assert_eq!((err.message.as_str(), err.span), (message, span), "{}", code);
+      assert_eq!(err.code(), "SyntaxError", "{}", code);
+    }
+  }
+}
@@ -1,9 +1,72 @@
 use std::collections::VecDeque;
+use std::rc::Rc;
 use crate::Span;
+use crate::SourceFile;
 use crate::{Error, SyntaxError, TabError, IndentationError};
 use super::super::TokenKind;
 use super::super::Token;
+use super::super::{Trivia, TriviaKind};
+use super::super::Keyword;
+use super::super::SOFT_KEYWORDS;
 use super::TokenStream;
+use unicode_ident::{is_xid_start, is_xid_continue};
+use unicode_normalization::UnicodeNormalization;
+
+/// 运算符与分隔符表，按长度从长到短排列以实现最长匹配
+const OPERATORS: &[(&str, TokenKind)] = &[
+  ("**=", TokenKind::DoubleStarEqual),
+  ("//=", TokenKind::DoubleSlashEqual),
+  ("<<=", TokenKind::LeftShiftEqual),
+  (">>=", TokenKind::RightShiftEqual),
+  ("...", TokenKind::Ellipsis),
+  ("==", TokenKind::EqEqual),
+  ("!=", TokenKind::NotEqual),
+  ("<>", TokenKind::NotEqual),
+  ("<=", TokenKind::LessEqual),
+  (">=", TokenKind::GreaterEqual),
+  ("<<", TokenKind::LeftShift),
+  (">>", TokenKind::RightShift),
+  ("**", TokenKind::DoubleStar),
+  ("//", TokenKind::DoubleSlash),
+  ("+=", TokenKind::PlusEqual),
+  ("-=", TokenKind::MinEqual),
+  ("*=", TokenKind::StarEqual),
+  ("/=", TokenKind::SlashEqual),
+  ("%=", TokenKind::PercentEqual),
+  ("&=", TokenKind::AmperEqual),
+  ("|=", TokenKind::VBarEqual),
+  ("^=", TokenKind::CircumflexEqual),
+  ("@=", TokenKind::AtEqual),
+  ("->", TokenKind::RArrow),
+  (":=", TokenKind::ColonEqual),
+  ("&&", TokenKind::DoubleAmper),
+  ("||", TokenKind::DoubleVBar),
+  ("(", TokenKind::LPar),
+  (")", TokenKind::RPar),
+  ("[", TokenKind::LSqb),
+  ("]", TokenKind::RSqb),
+  ("{", TokenKind::LBrace),
+  ("}", TokenKind::RBrace),
+  (":", TokenKind::Colon),
+  (",", TokenKind::Comma),
+  (";", TokenKind::Semi),
+  ("+", TokenKind::Plus),
+  ("-", TokenKind::Minus),
+  ("*", TokenKind::Star),
+  ("/", TokenKind::Slash),
+  ("|", TokenKind::VBar),
+  ("&", TokenKind::Amper),
+  ("<", TokenKind::Less),
+  (">", TokenKind::Greater),
+  ("=", TokenKind::Equal),
+  (".", TokenKind::Dot),
+  ("%", TokenKind::Percent),
+  ("~", TokenKind::Tilde),
+  ("^", TokenKind::Circumflex),
+  ("@", TokenKind::At),
+  ("!", TokenKind::Exclamation),
+  ("?", TokenKind::Question),
+];
 
 #[derive(Debug, PartialEq)]
 enum IndentType {
@@ -11,6 +74,40 @@ enum IndentType {
   Tab,
 }
 
+/// f-string / t-string 的引号与前缀信息
+#[derive(Debug, Clone, Copy)]
+struct FStringQuote {
+  /// 字面量起始位置（含前缀）
+  start: usize,
+  quote: char,
+  triple: bool,
+  raw: bool,
+  template: bool,
+}
+
+impl FStringQuote {
+  fn unterminated(&self, pos: usize) -> Error {
+    let message = match (self.triple, self.template) {
+      (false, false) => "unterminated f-string literal",
+      (true, false) => "unterminated triple-quoted f-string literal",
+      (false, true) => "unterminated t-string literal",
+      (true, true) => "unterminated triple-quoted t-string literal",
+    };
+    SyntaxError::new(message, Span::new(self.start, pos))
+  }
+}
+
+/// f-string 扫描状态，栈顶为当前状态
+#[derive(Debug, Clone, Copy)]
+enum FStringState {
+  /// 字面文本
+  Text(FStringQuote),
+  /// 替换字段中的表达式，usize 为进入字段时的括号深度
+  Expr(FStringQuote, usize),
+  /// 格式说明 (`:` 之后)
+  Spec(FStringQuote),
+}
+
 #[derive(Debug)]
 pub struct Lexer {
   chars: Vec<char>,
@@ -20,6 +117,22 @@ pub struct Lexer {
   indent_type: Option<IndentType>,
   indents: Vec<usize>,
   buffer: VecDeque<Token>,
+  /// 当前括号嵌套深度
+  paren_depth: usize,
+  /// 嵌套的 f-string 状态
+  fstrings: Vec<FStringState>,
+  /// 下一个 token 位于物理行首，需要先计算缩进
+  at_line_start: bool,
+  /// 当前逻辑行是否已有 token（决定换行产生 Newline 还是 Nl）
+  line_has_content: bool,
+  /// 是否产生 Comment token
+  emit_comments: bool,
+  /// 无损模式：空白、注释、Nl 与续行作为 trivia 附着在 token 上
+  lossless: bool,
+  /// 已归入 trivia 或 token 的源码末尾位置（无损模式）
+  trivia_end: usize,
+  /// 所属源文件；产出的 span 都加上文件在 SourceMap 中的起始偏移
+  file: Option<Rc<SourceFile>>,
 }
 
 impl Lexer {
@@ -32,9 +145,49 @@ impl Lexer {
       indent_type: None,
       indents: Vec::new(),
       buffer: VecDeque::new(),
+      paren_depth: 0,
+      fstrings: Vec::new(),
+      at_line_start: true,
+      line_has_content: false,
+      emit_comments: false,
+      lossless: false,
+      trivia_end: 0,
+      file: None,
+    }
+  }
+  
+  /// 为 SourceMap 中的文件创建 Lexer：span 使用全局偏移，错误附带文件名与行列
+  pub fn from_file(file: Rc<SourceFile>) -> Self {
+    let mut lexer = Self::new(file.text());
+    lexer.file = Some(file);
+    lexer
+  }
+  
+  /// 文件在全局偏移空间中的起点
+  fn base(&self) -> usize {
+    self.file.as_ref().map_or(0, |file| file.start())
+  }
+  
+  /// 将错误定位到所属文件的行列（没有文件时原样返回）
+  pub fn locate(&self, err: Error) -> Error {
+    match &self.file {
+      Some(file) => err.with_location(file),
+      None => err,
     }
   }
   
+  /// 设置是否将 `#` 注释作为 Comment token 产出（默认直接跳过）
+  pub fn with_comments(mut self, emit: bool) -> Self {
+    self.emit_comments = emit;
+    self
+  }
+  
+  /// 设置无损模式：依次拼接每个 token 的前导 trivia、源码文本与尾随 trivia 即可还原输入
+  pub fn with_trivia(mut self, lossless: bool) -> Self {
+    self.lossless = lossless;
+    self
+  }
+  
   pub fn peek_char(&self) -> Option<char> {
     self.chars.get(self.pos).copied()
   } 
@@ -70,12 +223,49 @@ impl Lexer {
     TokenStream::new(self)
   }
   
+  /// 取出 span（全局偏移）对应的源码文本
+  pub fn source(&self, span: Span) -> String {
+    let base = self.base();
+    let end = span.end.saturating_sub(base).min(self.chars.len());
+    self.chars[span.start.saturating_sub(base).min(end)..end].iter().collect()
+  }
+  
   fn next_token(&mut self) -> Option<Result<Token, Error>> {
+    if let Some(tok) = self.buffer.pop_front() {
+      return Some(Ok(tok));
+    }
     if self.pos >= self.chars.len() {
+      if let Some(state) = self.fstrings.last() {
+        let (FStringState::Text(quote) | FStringState::Expr(quote, _) | FStringState::Spec(quote)) = state;
+        let err = quote.unterminated(self.pos);
+        self.fstrings.clear();
+        return Some(Err(err));
+      }
+      // 文件结束时关闭所有未闭合的缩进块
+      if !self.indents.is_empty() {
+        let level = self.indents.len();
+        self.indents.pop();
+        return Some(Ok(Token::new(TokenKind::Dedent(level), Span::new(self.pos, self.pos))));
+      }
       return self.emit_eof();
     }
-    if self.buffer.len() > 0 {
-      return Some(Ok(self.buffer.pop_front().expect("has buffer")));
+    
+    // 物理行首：计算缩进，产生 Indent / Dedent
+    if self.at_line_start {
+      self.at_line_start = false;
+      match self.make_indent() {
+        Err(err) => return Some(Err(err)),
+        Ok(tokens) => self.buffer.extend(tokens),
+      }
+      if let Some(tok) = self.buffer.pop_front() {
+        return Some(Ok(tok));
+      }
+    }
+    
+    // f-string 的字面文本与格式说明部分不跳过空白
+    match self.fstrings.last() {
+      Some(FStringState::Text(_) | FStringState::Spec(_)) => return Some(self.make_fstring_middle()),
+      Some(FStringState::Expr(..)) | None => {},
     }
 
     // 跳过空白
@@ -84,115 +274,644 @@ impl Lexer {
     let c = match self.peek_char() {
       Some(ch) => ch,
       // 走到这里说明刚好到尾（处理上面同 EOF），返回 EOF
-      None => return self.emit_eof(),
+      None => return self.next_token(),
     };
+    
+    // 注释
+    if c == '#' {
+      let start = self.pos;
+      while self.peek_char().is_some_and(|ch| ch != '\n' && ch != '\r') {
+        self.advance();
+      }
+      if self.emit_comments {
+        return Some(Ok(Token::new(TokenKind::Comment, Span::new(start, self.pos))));
+      }
+      return self.next_token();
+    }
+    // 未接换行的续行符
+    if c == '\\' {
+      let start = self.pos;
+      self.advance();
+      let message = if self.peek_char().is_none() {
+        "unexpected EOF while parsing"
+      } else {
+        "unexpected character after line continuation character"
+      };
+      return Some(Err(SyntaxError::new(message, Span::new(start, self.pos))));
+    }
+    
+    // f-string 替换字段内的 `}`、`:` 与换行
+    if let Some(&FStringState::Expr(quote, depth)) = self.fstrings.last()
+      && let Some(tok) = self.fstring_expr_boundary(c, quote, depth) {
+      return Some(tok);
+    }
 
-    // 数字
-    if c.is_ascii_digit() {
-      return self.make_number();
+    // 数字（含 .5 形式的浮点数）
+    if c.is_ascii_digit() || (c == '.' && self.chars.get(self.pos + 1).is_some_and(|d| d.is_ascii_digit())) {
+      return Some(self.make_number());
+    }
+    // 字符串（含 r/b/u 前缀）
+    if let Some(prefix_len) = self.string_prefix() {
+      return self.make_string(prefix_len);
+    }
+    // 标识符与关键字
+    if c == '_' || is_xid_start(c) {
+      return Some(Ok(self.make_name()));
     }
-    // 换行与缩进
-    if c == '\n' {
+    // 换行：括号内与空行产生 Nl，否则结束逻辑行产生 Newline
+    if c == '\n' || c == '\r' {
+      let start = self.pos;
       self.advance();
-      return match self.make_indent() {
-        Err(err) => Some(Err(err)),
-        Ok(mut tokens) => {
-          if !self.first_token {
-            tokens.push_front(Token::new(
-              TokenKind::Newline,
-              Span::new(self.pos, self.pos),
-            ));
-          };
-          match tokens.pop_front() {
-            Some(_) if self.first_token => Some(Err(IndentationError::new("unexpected indent", Span::new(self.pos, self.pos)))),
-            Some(tok) => {
-              for i in tokens {
-                self.buffer.push_back(i);
-              }
-              Some(Ok(tok))
-            },
-            None => self.next_token(),
-          }
-        }
+      if c == '\r' && self.peek_char() == Some('\n') {
+        self.advance();
+      }
+      let span = Span::new(start, self.pos);
+      if self.paren_depth > 0 || !self.fstrings.is_empty() {
+        return Some(Ok(Token::new(TokenKind::Nl, span)));
       }
+      self.at_line_start = true;
+      let kind = if self.line_has_content { TokenKind::Newline } else { TokenKind::Nl };
+      return Some(Ok(Token::new(kind, span)));
     }
 
-    // 单字符 token
+    // 运算符与分隔符
+    self.make_operator()
+  }
+  
+  /// 当前位置是否以 s 开头
+  fn starts_with(&self, s: &str) -> bool {
+    s.chars().enumerate().all(|(i, ch)| self.chars.get(self.pos + i) == Some(&ch))
+  }
+  
+  /// 按最长匹配读取一个运算符或分隔符
+  fn make_operator(&mut self) -> Option<Result<Token, Error>> {
     let start = self.pos;
-    self.advance(); // consume c
-    let end = self.pos;
-    let kind = match c {
-      '+' => TokenKind::Plus,
-      '-' => TokenKind::Minus,
-      '*' => TokenKind::Star,
-      '/' => TokenKind::Slash,
-      _ => return Some(Err(SyntaxError::new("invalid syntax", Span::new(start, end)))),
+    for (text, kind) in OPERATORS {
+      if self.starts_with(text) {
+        self.pos += text.len();
+        match kind {
+          TokenKind::LPar | TokenKind::LSqb | TokenKind::LBrace => self.paren_depth += 1,
+          TokenKind::RPar | TokenKind::RSqb | TokenKind::RBrace => self.paren_depth = self.paren_depth.saturating_sub(1),
+          _ => {},
+        }
+        return Some(Ok(Token::new(
+          kind.clone(),
+          Span::new(start, self.pos),
+        )));
+      }
+    }
+    let c = self.advance().expect("not eof");
+    let message = if c.is_ascii() {
+      "invalid syntax".to_string()
+    } else {
+      format!("invalid character '{}' (U+{:04X})", c, c as u32)
     };
-    Some(Ok(Token::new(
-      kind,
-      Span::new(start, end)
-    )))
+    Some(Err(SyntaxError::new(message, Span::new(start, self.pos))))
+  }
+  
+  /// 读取标识符或关键字；标识符遵循 Unicode XID 规则，非 ASCII 标识符做 NFKC 规范化
+  fn make_name(&mut self) -> Token {
+    let start = self.pos;
+    self.advance();
+    while let Some(c) = self.peek_char() {
+      if is_xid_continue(c) {
+        self.advance();
+      } else {
+        break;
+      }
+    }
+    let span = Span::new(start, self.pos);
+    let text: String = self.chars[start..self.pos].iter().collect();
+    if let Some(keyword) = Keyword::lookup(&text) {
+      return Token::new(TokenKind::Keyword(keyword), span);
+    }
+    let name = if text.is_ascii() { text } else { text.nfkc().collect() };
+    if SOFT_KEYWORDS.contains(&name.as_str()) {
+      Token::new(TokenKind::SoftKeyword(name), span)
+    } else {
+      Token::new(TokenKind::Name(name), span)
+    }
   }
   
   /// 如果已经遍历结束，则先发一个 EOF（只发一次），之后返回 None
   fn emit_eof(&mut self) -> Option<Result<Token, Error>> {
     if self.eof_emitted {
-      return None;
+      None
     } else {
       self.eof_emitted = true;
-      return Some(Ok(Token::eof(self.pos)));
+      Some(Ok(Token::eof(self.pos)))
     }
   }
   
+  /// 跳过空格、制表符、换页符以及 `\` 续行
   fn skip_whitespace(&mut self) {
     while let Some(c) = self.peek_char() {
-      if c == ' ' {
+      match c {
+        ' ' | '\t' | '\x0c' => {
+          self.advance();
+        },
+        '\\' if self.starts_with("\\\r\n") => self.pos += 3,
+        '\\' if self.starts_with("\\\n") || self.starts_with("\\\r") => self.pos += 2,
+        _ => break,
+      }
+    }
+  }
+  
+  /// 读取一串数字（允许以单个下划线分隔），返回去掉下划线的文本
+  fn read_digits(&mut self, is_digit: fn(char) -> bool, start: usize, message: &str) -> Result<String, Error> {
+    let mut digits = String::new();
+    while let Some(c) = self.peek_char() {
+      if is_digit(c) {
+        digits.push(c);
+        self.advance();
+      } else if c == '_' {
         self.advance();
+        // 下划线只能出现在两个数字之间
+        if !self.peek_char().is_some_and(is_digit) {
+          return Err(SyntaxError::new(message, Span::new(start, self.pos)));
+        }
       } else {
         break;
       }
     }
+    Ok(digits)
   }
   
-  fn make_number(&mut self) -> Option<Result<Token, Error>> {
+  /// 读取数字字面量：整数（十/十六/八/二进制）与浮点数，均允许下划线分隔
+  fn make_number(&mut self) -> Result<Token, Error> {
     let start = self.pos;
-    while let Some(d) = self.peek_char() {
-      if d.is_ascii_digit() {
+    
+    // 0x / 0o / 0b 前缀
+    if self.peek_char() == Some('0') {
+      let radix = match self.chars.get(self.pos + 1) {
+        Some('x' | 'X') => Some((16, "invalid hexadecimal literal")),
+        Some('o' | 'O') => Some((8, "invalid octal literal")),
+        Some('b' | 'B') => Some((2, "invalid binary literal")),
+        _ => None,
+      };
+      if let Some((radix, message)) = radix {
+        self.pos += 2;
+        // 前缀后允许紧跟一个下划线: 0x_ff
+        if self.peek_char() == Some('_') {
+          self.advance();
+        }
+        let digits = match radix {
+          16 => self.read_digits(|c| c.is_ascii_hexdigit(), start, message)?,
+          8 => self.read_digits(|c| c.is_digit(8), start, message)?,
+          _ => self.read_digits(|c| c.is_digit(2), start, message)?,
+        };
+        if digits.is_empty() || self.peek_char().is_some_and(|c| c.is_ascii_alphanumeric()) {
+          return Err(SyntaxError::new(message, Span::new(start, self.pos + 1)));
+        }
+        let span = Span::new(start, self.pos);
+        return i64::from_str_radix(&digits, radix)
+          .map(|value| Token::new(TokenKind::Int(value), span))
+          .map_err(|_| SyntaxError::new("integer literal is too large", span));
+      }
+    }
+    
+    let message = "invalid decimal literal";
+    let int_part = self.read_digits(|c| c.is_ascii_digit(), start, message)?;
+    let mut is_float = false;
+    let mut text = int_part.clone();
+    
+    // 小数部分: 1.5 / 1. / .5
+    if self.peek_char() == Some('.') && !self.starts_with("...") {
+      self.advance();
+      is_float = true;
+      text.push('.');
+      if self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
+        text.push_str(&self.read_digits(|c| c.is_ascii_digit(), start, message)?);
+      }
+    }
+    
+    // 指数部分: 1e9 / 1E-9
+    if let Some('e' | 'E') = self.peek_char() {
+      let mark = self.pos;
+      self.advance();
+      let mut exponent = String::from("e");
+      if let Some(sign @ ('+' | '-')) = self.peek_char() {
+        exponent.push(sign);
         self.advance();
-      } else {
-        break;
       }
+      if !self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
+        self.pos = mark;
+        return Err(SyntaxError::new(message, Span::new(start, self.pos + 1)));
+      }
+      exponent.push_str(&self.read_digits(|c| c.is_ascii_digit(), start, message)?);
+      is_float = true;
+      text.push_str(&exponent);
     }
-    let end = self.pos;
-    let text: String = self.chars[start..end].iter().collect();
-    let value = text.parse::<i64>().unwrap_or(0);
-    Some(Ok(Token::new(
-      TokenKind::Int(value),
-      Span::new(start, end),
-    )))
+    
+    // 数字后紧跟字母或下划线 (如 1abc)
+    if self.peek_char().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+      return Err(SyntaxError::new(message, Span::new(start, self.pos + 1)));
+    }
+    
+    let span = Span::new(start, self.pos);
+    if is_float {
+      let value = text.parse::<f64>()
+        .map_err(|_| SyntaxError::new(message, span))?;
+      return Ok(Token::new(TokenKind::Float(value), span));
+    }
+    
+    if int_part.len() > 1 && int_part.starts_with('0') && int_part.chars().any(|c| c != '0') {
+      return Err(SyntaxError::new(
+        "leading zeros in decimal integer literals are not permitted; use an 0o prefix for octal integers",
+        span,
+      ));
+    }
+    int_part.parse::<i64>()
+      .map(|value| Token::new(TokenKind::Int(value), span))
+      .map_err(|_| SyntaxError::new("integer literal is too large", span))
   }
   
-  fn make_indent(&mut self) -> Result<VecDeque<Token>, Error> {
+  /// 若当前位置是字符串字面量的开头，返回前缀（r/b/u/f/t 及其组合，大小写不敏感）的长度
+  fn string_prefix(&self) -> Option<usize> {
+    let mut prefix = String::new();
+    while let Some(&c) = self.chars.get(self.pos + prefix.len()) {
+      if c == '"' || c == '\'' {
+        return match prefix.to_ascii_lowercase().as_str() {
+          "" | "r" | "u" | "b" | "br" | "rb" => Some(prefix.len()),
+          "f" | "fr" | "rf" | "t" | "tr" | "rt" => Some(prefix.len()),
+          _ => None,
+        };
+      }
+      if prefix.len() >= 2 || !c.is_ascii_alphabetic() {
+        return None;
+      }
+      prefix.push(c);
+    }
+    None
+  }
+  
+  /// 读取字符串或字节串字面量，prefix_len 为前缀长度
+  fn make_string(&mut self, prefix_len: usize) -> Option<Result<Token, Error>> {
     let start = self.pos;
-    while let Some(d) = self.peek_char() {
-      if d == ' ' || d == '\t' {
-        let indent_type = if d == ' ' { IndentType::Space } else { IndentType::Tab };
-        match &self.indent_type {
-          Some(x) if x != &indent_type => return Err(TabError::new("inconsistent use of tabs and spaces in indentation", Span::new(self.pos, self.pos))),
-          Some(_) => {},
-          None => {self.indent_type = Some(indent_type)},
-        } 
+    let prefix: String = self.chars[start..start + prefix_len].iter().collect::<String>().to_ascii_lowercase();
+    let raw = prefix.contains('r');
+    let bytes = prefix.contains('b');
+    self.pos += prefix_len;
+    
+    let quote = self.advance().expect("quote");
+    let triple_quote: String = [quote; 3].iter().collect();
+    let triple = self.starts_with(&triple_quote[1..]);
+    if triple {
+      self.pos += 2;
+    }
+    
+    if prefix.contains('f') || prefix.contains('t') {
+      let template = prefix.contains('t');
+      self.fstrings.push(FStringState::Text(FStringQuote { start, quote, triple, raw, template }));
+      let kind = if template { TokenKind::TStringStart } else { TokenKind::FStringStart };
+      return Some(Ok(Token::new(kind, Span::new(start, self.pos))));
+    }
+    
+    let mut value = String::new();
+    while let Some(c) = self.peek_char() {
+      if c == quote && (!triple || self.starts_with(&triple_quote)) {
+        self.pos += if triple { 3 } else { 1 };
+        let kind = if bytes {
+          TokenKind::Bytes(value.chars().map(|c| c as u8).collect())
+        } else {
+          TokenKind::String(value)
+        };
+        return Some(Ok(Token::new(kind, Span::new(start, self.pos))));
+      }
+      if c == '\n' && !triple {
+        break;
+      }
+      if c == '\\' {
+        let escape_start = self.pos;
         self.advance();
-      } else {
+        let e = match self.advance() {
+          Some(e) => e,
+          None => break,
+        };
+        if raw {
+          value.push('\\');
+          value.push(e);
+        } else if let Err(err) = self.read_escape(e, escape_start, bytes, &mut value) {
+          return Some(Err(err));
+        }
+        continue;
+      }
+      if bytes && !c.is_ascii() {
+        return Some(Err(SyntaxError::new(
+          "bytes can only contain ASCII literal characters",
+          Span::new(self.pos, self.pos + 1),
+        )));
+      }
+      value.push(c);
+      self.advance();
+    }
+    
+    let message = if triple {
+      "unterminated triple-quoted string literal"
+    } else {
+      "unterminated string literal"
+    };
+    Some(Err(SyntaxError::new(message, Span::new(start, self.pos))))
+  }
+  
+  /// 读取 f-string 的字面文本或格式说明，遇到 `{`、`}` 或结束引号时产生对应 token
+  fn make_fstring_middle(&mut self) -> Result<Token, Error> {
+    let (quote, in_spec) = match self.fstrings.last() {
+      Some(&FStringState::Text(quote)) => (quote, false),
+      Some(&FStringState::Spec(quote)) => (quote, true),
+      _ => unreachable!("not in f-string text"),
+    };
+    let triple_quote: String = [quote.quote; 3].iter().collect();
+    let start = self.pos;
+    let mut value = String::new();
+    let middle = |value: String, end: usize| {
+      let kind = if quote.template { TokenKind::TStringMiddle(value) } else { TokenKind::FStringMiddle(value) };
+      Ok(Token::new(kind, Span::new(start, end)))
+    };
+    
+    while let Some(c) = self.peek_char() {
+      // 结束引号
+      if c == quote.quote && (!quote.triple || self.starts_with(&triple_quote)) {
+        if in_spec {
+          return Err(SyntaxError::new("f-string: expecting '}'", Span::new(self.pos, self.pos + 1)));
+        }
+        if self.pos > start {
+          return middle(value, self.pos);
+        }
+        self.pos += if quote.triple { 3 } else { 1 };
+        self.fstrings.pop();
+        let kind = if quote.template { TokenKind::TStringEnd } else { TokenKind::FStringEnd };
+        return Ok(Token::new(kind, Span::new(start, self.pos)));
+      }
+      if c == '\n' && !quote.triple {
         break;
       }
+      if c == '{' {
+        // {{ 转义为字面量 {
+        if !in_spec && self.chars.get(self.pos + 1) == Some(&'{') {
+          value.push('{');
+          self.pos += 2;
+          continue;
+        }
+        if self.pos > start {
+          return middle(value, self.pos);
+        }
+        self.advance();
+        self.fstrings.push(FStringState::Expr(quote, self.paren_depth));
+        return Ok(Token::new(TokenKind::LBrace, Span::new(start, self.pos)));
+      }
+      if c == '}' {
+        if in_spec {
+          if self.pos > start {
+            return middle(value, self.pos);
+          }
+          self.advance();
+          self.fstrings.pop();
+          return Ok(Token::new(TokenKind::RBrace, Span::new(start, self.pos)));
+        }
+        // }} 转义为字面量 }
+        if self.chars.get(self.pos + 1) == Some(&'}') {
+          value.push('}');
+          self.pos += 2;
+          continue;
+        }
+        return Err(SyntaxError::new("f-string: single '}' is not allowed", Span::new(self.pos, self.pos + 1)));
+      }
+      if c == '\\' && !quote.raw {
+        let escape_start = self.pos;
+        self.advance();
+        match self.advance() {
+          Some(e) => self.read_escape(e, escape_start, false, &mut value)?,
+          None => break,
+        }
+        continue;
+      }
+      value.push(c);
+      self.advance();
+    }
+    
+    let err = quote.unterminated(self.pos);
+    self.fstrings.clear();
+    Err(err)
+  }
+  
+  /// 处理替换字段表达式中的边界：顶层的 `}` 结束字段，`:` 开始格式说明；
+  /// 不属于边界时返回 None，按普通 token 继续扫描
+  fn fstring_expr_boundary(&mut self, c: char, quote: FStringQuote, depth: usize) -> Option<Result<Token, Error>> {
+    let start = self.pos;
+    match c {
+      '}' if self.paren_depth == depth => {
+        self.advance();
+        self.fstrings.pop();
+        Some(Ok(Token::new(TokenKind::RBrace, Span::new(start, self.pos))))
+      },
+      ':' if self.paren_depth == depth => {
+        self.advance();
+        *self.fstrings.last_mut().expect("in f-string") = FStringState::Spec(quote);
+        Some(Ok(Token::new(TokenKind::Colon, Span::new(start, self.pos))))
+      },
+      '\n' if quote.triple => {
+        self.advance();
+        self.next_token()
+      },
+      '\n' => {
+        let err = quote.unterminated(self.pos);
+        self.fstrings.clear();
+        Some(Err(err))
+      },
+      _ => None,
+    }
+  }
+  
+  /// 处理反斜杠转义，e 为反斜杠后的字符（已消费），start 为反斜杠位置
+  fn read_escape(&mut self, e: char, start: usize, bytes: bool, out: &mut String) -> Result<(), Error> {
+    match e {
+      // 反斜杠续行
+      '\n' => {},
+      '\\' | '\'' | '"' => out.push(e),
+      'a' => out.push('\x07'),
+      'b' => out.push('\x08'),
+      'f' => out.push('\x0c'),
+      'n' => out.push('\n'),
+      'r' => out.push('\r'),
+      't' => out.push('\t'),
+      'v' => out.push('\x0b'),
+      '0'..='7' => {
+        let mut code = e.to_digit(8).expect("octal");
+        for _ in 0..2 {
+          match self.peek_char().and_then(|c| c.to_digit(8)) {
+            Some(d) => {
+              code = code * 8 + d;
+              self.advance();
+            },
+            None => break,
+          }
+        }
+        if bytes && code > 0xff {
+          return Err(SyntaxError::new("octal escape value out of range", Span::new(start, self.pos)));
+        }
+        out.push(char::from_u32(code).expect("octal escape is a valid char"));
+      },
+      'x' => out.push(self.read_hex_escape(2, start, "truncated \\xXX escape")?),
+      'u' if !bytes => out.push(self.read_hex_escape(4, start, "truncated \\uXXXX escape")?),
+      'U' if !bytes => out.push(self.read_hex_escape(8, start, "truncated \\UXXXXXXXX escape")?),
+      'N' if !bytes => {
+        let malformed = |lexer: &Self| SyntaxError::new("malformed \\N character escape", Span::new(start, lexer.pos));
+        if self.peek_char() != Some('{') {
+          return Err(malformed(self));
+        }
+        self.advance();
+        let mut name = String::new();
+        loop {
+          match self.peek_char() {
+            Some('}') => {
+              self.advance();
+              break;
+            },
+            Some(c) if c != '\n' && c != '"' && c != '\'' => {
+              name.push(c);
+              self.advance();
+            },
+            _ => return Err(malformed(self)),
+          }
+        }
+        if name.is_empty() {
+          return Err(malformed(self));
+        }
+        match unicode_names2::character(&name) {
+          Some(c) => out.push(c),
+          None => return Err(SyntaxError::new("unknown Unicode character name", Span::new(start, self.pos))),
+        }
+      },
+      // 无法识别的转义保持原样
+      _ => {
+        out.push('\\');
+        out.push(e);
+      },
+    }
+    Ok(())
+  }
+  
+  /// 读取 digits 位十六进制数组成的转义字符
+  fn read_hex_escape(&mut self, digits: usize, start: usize, message: &str) -> Result<char, Error> {
+    let mut code: u32 = 0;
+    for _ in 0..digits {
+      match self.peek_char().and_then(|c| c.to_digit(16)) {
+        Some(d) => {
+          code = code * 16 + d;
+          self.advance();
+        },
+        None => return Err(SyntaxError::new(message, Span::new(start, self.pos))),
+      }
+    }
+    char::from_u32(code)
+      .ok_or_else(|| SyntaxError::new("illegal Unicode character", Span::new(start, self.pos)))
+  }
+  
+  /// 无损模式下取下一个 token，并附上前导与尾随 trivia
+  fn next_lossless(&mut self) -> Option<Result<Token, Error>> {
+    loop {
+      match self.next_token() {
+        Some(Ok(tok)) if tok.kind().is_trivia() => continue,
+        Some(Ok(tok)) => {
+          let span = tok.span();
+          let leading = self.trivia(self.trivia_end, span.start);
+          let trailing_end = self.trailing_end(&tok);
+          let trailing = self.trivia(span.end, trailing_end);
+          self.trivia_end = trailing_end;
+          return Some(Ok(tok.with_trivia(leading, trailing)));
+        },
+        other => return other,
+      }
+    }
+  }
+  
+  /// 尾随 trivia 的结束位置：同一行内 token 之后的空白与注释，不跨越换行
+  fn trailing_end(&self, tok: &Token) -> usize {
+    let mut end = tok.span().end.max(self.trivia_end);
+    let in_fstring_text = matches!(self.fstrings.last(), Some(FStringState::Text(_) | FStringState::Spec(_)));
+    if in_fstring_text || matches!(tok.kind(), TokenKind::Newline | TokenKind::Indent(_) | TokenKind::Dedent(_)) {
+      return end;
+    }
+    while let Some(' ' | '\t' | '\x0c') = self.chars.get(end) {
+      end += 1;
+    }
+    if self.chars.get(end) == Some(&'#') {
+      while self.chars.get(end).is_some_and(|&c| c != '\n' && c != '\r') {
+        end += 1;
+      }
+    }
+    end
+  }
+  
+  /// 将 [start, end) 的源码切分为 trivia
+  fn trivia(&self, start: usize, end: usize) -> Vec<Trivia> {
+    let mut res = Vec::new();
+    let mut i = start;
+    while i < end {
+      let begin = i;
+      let c = self.chars[i];
+      i += 1;
+      let kind = match c {
+        '#' => {
+          while i < end && self.chars[i] != '\n' && self.chars[i] != '\r' {
+            i += 1;
+          }
+          TriviaKind::Comment
+        },
+        '\\' | '\r' | '\n' => {
+          if c == '\\' && i < end && self.chars[i] == '\r' {
+            i += 1;
+          }
+          if c != '\n' && i < end && self.chars[i] == '\n' {
+            i += 1;
+          }
+          if c == '\\' { TriviaKind::Continuation } else { TriviaKind::Nl }
+        },
+        _ => {
+          while i < end && matches!(self.chars[i], ' ' | '\t' | '\x0c') {
+            i += 1;
+          }
+          TriviaKind::Whitespace
+        },
+      };
+      let text = self.chars[begin..i].iter().collect();
+      res.push(Trivia::new(kind, Span::new(begin, i), text));
+    }
+    res
+  }
+  
+  /// 计算行首缩进；空行与纯注释行不影响缩进
+  fn make_indent(&mut self) -> Result<VecDeque<Token>, Error> {
+    let start = self.pos;
+    while let Some(' ' | '\t' | '\x0c') = self.peek_char() {
+      self.advance();
     }
     let end = self.pos;
+    let mut res = VecDeque::new();
+    if matches!(self.peek_char(), None | Some('\n' | '\r' | '#')) {
+      return Ok(res);
+    }
+    
+    for &d in &self.chars[start..end] {
+      let indent_type = match d {
+        ' ' => IndentType::Space,
+        '\t' => IndentType::Tab,
+        _ => continue,
+      };
+      match &self.indent_type {
+        Some(x) if x != &indent_type => return Err(TabError::new("inconsistent use of tabs and spaces in indentation", Span::new(start, end))),
+        Some(_) => {},
+        None => {self.indent_type = Some(indent_type)},
+      }
+    }
     let count: usize = end - start;
+    if count > 0 && self.first_token {
+      return Err(IndentationError::new("unexpected indent", Span::new(start, end)));
+    }
     
-    let mut res = VecDeque::new();
-    while self.indents.len() > 0 {
-      if &count >= self.indents.last().expect("len>0") {
+    while let Some(&last) = self.indents.last() {
+      if count >= last {
         break
       }
       res.push_back(Token::new(
@@ -202,20 +921,16 @@ impl Lexer {
       self.indents.pop();
     }
     
-    if count > 0 {
-      // 是否为新的缩进
-      /* let mut new_indent = self.indents.iter().filter(|x| x == count).count() == 0;
-      if self.peek_char() == '\n' {
-        new_indent = false;
-      }*/
-      let last = self.indents.last();
-      if last == None || &count > last.expect("Not None") {
-        self.indents.push(count);
-        res.push_back(Token::new(
-          TokenKind::Indent(self.indents.len()),
-          Span::new(end, end),
-        ));
+    let last = self.indents.last().copied().unwrap_or(0);
+    if count > last {
+      if !res.is_empty() {
+        return Err(IndentationError::new("unindent does not match any outer indentation level", Span::new(start, end)));
       }
+      self.indents.push(count);
+      res.push_back(Token::new(
+        TokenKind::Indent(self.indents.len()),
+        Span::new(end, end),
+      ));
     }
     
     Ok(res)
@@ -226,11 +941,23 @@ impl Iterator for &mut Lexer {
   type Item = Result<Token, Error>;
   
   fn next(&mut self) -> Option<Self::Item> {
-    let token = self.next_token();
-    if let Some(_) = token {
-      self.first_token = false;
+    let token = if self.lossless { self.next_lossless() } else { self.next_token() };
+    if let Some(Ok(tok)) = &token {
+      match tok.kind() {
+        TokenKind::Newline => self.line_has_content = false,
+        TokenKind::Comment | TokenKind::Nl | TokenKind::Indent(_) | TokenKind::Dedent(_) | TokenKind::Endmarker => {},
+        _ => {
+          self.line_has_content = true;
+          self.first_token = false;
+        },
+      }
+    }
+    let base = self.base();
+    match token {
+      Some(Ok(tok)) if base > 0 => Some(Ok(tok.offset(base))),
+      Some(Err(err)) => Some(Err(self.locate(err.offset(base)))),
+      token => token,
     }
-    token
   }
 }
 
@@ -239,10 +966,11 @@ impl Iterator for &mut Lexer {
 mod tests {
   use super::Lexer;
   use super::TokenKind;
-  use crate::errors::Error;
+  use super::Keyword;
+  use super::TriviaKind;
   use crate::errors::ErrorKind;
   
-  // 测试：数字与运算符（注意：不要使用浮点文字 like "3.5" ——当前词法器尚未支持小数点，会触发 todo!()）
+  // 测试：数字与运算符
   #[test]
   fn numbers_and_ops() {
     let s = "12 + 2 - 3";
@@ -263,6 +991,317 @@ mod tests {
     assert!(matches!(core[0].kind(), TokenKind::Int(x) if *x == 12));
   }
   
+  // 测试：运算符按最长匹配切分，且 span 正确
+  #[test]
+  fn operators_longest_match() {
+    let s = "**= //= >>= <<= ... == != <> <= >= -> := && || ( ) [ ] { } ~ ^ @ ! %";
+    let toks = Lexer::new(s).tokenize_all().expect("no except");
+    let expected = [
+      TokenKind::DoubleStarEqual,
+      TokenKind::DoubleSlashEqual,
+      TokenKind::RightShiftEqual,
+      TokenKind::LeftShiftEqual,
+      TokenKind::Ellipsis,
+      TokenKind::EqEqual,
+      TokenKind::NotEqual,
+      TokenKind::NotEqual,
+      TokenKind::LessEqual,
+      TokenKind::GreaterEqual,
+      TokenKind::RArrow,
+      TokenKind::ColonEqual,
+      TokenKind::DoubleAmper,
+      TokenKind::DoubleVBar,
+      TokenKind::LPar,
+      TokenKind::RPar,
+      TokenKind::LSqb,
+      TokenKind::RSqb,
+      TokenKind::LBrace,
+      TokenKind::RBrace,
+      TokenKind::Tilde,
+      TokenKind::Circumflex,
+      TokenKind::At,
+      TokenKind::Exclamation,
+      TokenKind::Percent,
+      TokenKind::Endmarker,
+    ];
+    let kinds: Vec<&TokenKind> = toks.iter().map(|t| t.kind()).collect();
+    assert_eq!(kinds, expected.iter().collect::<Vec<_>>());
+  
+    let texts: Vec<&str> = toks[..5].iter().map(|t| &s[t.span().start..t.span().end]).collect();
+    assert_eq!(texts, vec!["**=", "//=", ">>=", "<<=", "..."]);
+  }
+  
+  // 测试：相邻运算符不加空格时也能正确切分
+  #[test]
+  fn adjacent_operators() {
+    let toks = Lexer::new("1<<-2**-3").tokenize_all().expect("no except");
+    let kinds: Vec<&TokenKind> = toks.iter().map(|t| t.kind()).collect();
+    assert_eq!(kinds, vec![
+      &TokenKind::Int(1),
+      &TokenKind::LeftShift,
+      &TokenKind::Minus,
+      &TokenKind::Int(2),
+      &TokenKind::DoubleStar,
+      &TokenKind::Minus,
+      &TokenKind::Int(3),
+      &TokenKind::Endmarker,
+    ]);
+  }
+  
+  // 测试：无法识别的字符报告 SyntaxError，span 指向该字符
+  #[test]
+  fn invalid_character() {
+    let err = Lexer::new("1 $ 2").tokenize_all().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Syntax);
+    assert_eq!((err.span().start, err.span().end), (2, 3));
+  }
+
+  fn single(s: &str) -> TokenKind {
+    let toks = Lexer::new(s).tokenize_all().expect("no except");
+    assert_eq!(toks.len(), 2, "{:?}", toks);
+    assert_eq!((toks[0].span().start, toks[0].span().end), (0, s.chars().count()));
+    toks[0].kind().clone()
+  }
+
+  // 测试：单/双引号、三引号与转义
+  #[test]
+  fn strings() {
+    assert_eq!(single(r#"'abc'"#), TokenKind::String("abc".into()));
+    assert_eq!(single(r#""it's""#), TokenKind::String("it's".into()));
+    assert_eq!(single(r#"'a\tb\n\'\"\\'"#), TokenKind::String("a\tb\n'\"\\".into()));
+    assert_eq!(single(r#"'\x41\u00e9\U0001F600\101\0'"#), TokenKind::String("Aé😀A\0".into()));
+    assert_eq!(single(r#"'\N{GREEK SMALL LETTER ALPHA}'"#), TokenKind::String("α".into()));
+    assert_eq!(single(r#"'\q'"#), TokenKind::String("\\q".into()));
+    assert_eq!(single("'a\\\nb'"), TokenKind::String("ab".into()));
+    assert_eq!(single("\"\"\"line1\n'line2'\"\"\""), TokenKind::String("line1\n'line2'".into()));
+    assert_eq!(single(r#"'''a""b'''"#), TokenKind::String(r#"a""b"#.into()));
+    assert_eq!(single("'中文'"), TokenKind::String("中文".into()));
+  }
+  
+  // 测试：raw 字符串与字节串
+  #[test]
+  fn raw_and_bytes() {
+    assert_eq!(single(r#"r'\d+\n'"#), TokenKind::String(r"\d+\n".into()));
+    assert_eq!(single(r#"R"\"""#), TokenKind::String(r#"\""#.into()));
+    assert_eq!(single(r#"b'\x00\xffA'"#), TokenKind::Bytes(vec![0, 255, b'A']));
+    assert_eq!(single(r#"rb'\x00'"#), TokenKind::Bytes(br"\x00".to_vec()));
+    assert_eq!(single(r#"Br'\u'"#), TokenKind::Bytes(br"\u".to_vec()));
+    assert_eq!(single(r#"u'x'"#), TokenKind::String("x".into()));
+  }
+  
+  // 测试：未闭合或非法的字面量给出精确的 span
+  #[test]
+  fn string_errors() {
+    let cases = [
+      ("1 + 'abc", "unterminated string literal", (4, 8)),
+      ("'abc\n'", "unterminated string literal", (0, 4)),
+      ("'''abc\n", "unterminated triple-quoted string literal", (0, 7)),
+      (r"'\x4'", r"truncated \xXX escape", (1, 4)),
+      (r"'ab\u12'", r"truncated \uXXXX escape", (3, 7)),
+      (r"'\U00110000'", "illegal Unicode character", (1, 11)),
+      (r"'\N{NOT A REAL NAME}'", "unknown Unicode character name", (1, 20)),
+      (r"'\N'", r"malformed \N character escape", (1, 3)),
+      ("b'é'", "bytes can only contain ASCII literal characters", (2, 3)),
+    ];
+    for (source, message, span) in cases {
+      let err = Lexer::new(source).tokenize_all().unwrap_err();
+      assert_eq!(err.kind(), ErrorKind::Syntax, "{}", source);
+      assert_eq!(err.message(), message, "{}", source);
+      assert_eq!((err.span().start, err.span().end), span, "{}", source);
+    }
+  }
+
+  // 测试：浮点数、进制前缀与下划线分隔
+  #[test]
+  fn numeric_literals() {
+    assert_eq!(single("2.75"), TokenKind::Float(2.75));
+    assert_eq!(single(".5"), TokenKind::Float(0.5));
+    assert_eq!(single("1."), TokenKind::Float(1.0));
+    assert_eq!(single("1e-9"), TokenKind::Float(1e-9));
+    assert_eq!(single("2.5E+3"), TokenKind::Float(2500.0));
+    assert_eq!(single("1_000.000_1"), TokenKind::Float(1000.0001));
+    assert_eq!(single("1_000_000"), TokenKind::Int(1_000_000));
+    assert_eq!(single("0xFF"), TokenKind::Int(255));
+    assert_eq!(single("0x_ff_ff"), TokenKind::Int(0xffff));
+    assert_eq!(single("0o17"), TokenKind::Int(15));
+    assert_eq!(single("0B1010"), TokenKind::Int(10));
+    assert_eq!(single("000"), TokenKind::Int(0));
+    assert_eq!(single("9223372036854775807"), TokenKind::Int(i64::MAX));
+  
+    // 省略号不应被当作小数点
+    let toks = Lexer::new("1...").tokenize_all().unwrap();
+    assert_eq!(toks[0].kind(), &TokenKind::Int(1));
+    assert_eq!(toks[1].kind(), &TokenKind::Ellipsis);
+  }
+  
+  // 测试：非法数字字面量与溢出报告 SyntaxError，而不是静默得到 0
+  #[test]
+  fn numeric_errors() {
+    let cases = [
+      ("9223372036854775808", "integer literal is too large", (0, 19)),
+      ("0x1_0000_0000_0000_0000", "integer literal is too large", (0, 23)),
+      ("1__0", "invalid decimal literal", (0, 2)),
+      ("1_", "invalid decimal literal", (0, 2)),
+      ("1e", "invalid decimal literal", (0, 2)),
+      ("12abc", "invalid decimal literal", (0, 3)),
+      ("0x", "invalid hexadecimal literal", (0, 3)),
+      ("0o8", "invalid octal literal", (0, 3)),
+      ("0b102", "invalid binary literal", (0, 5)),
+      ("0123", "leading zeros in decimal integer literals are not permitted; use an 0o prefix for octal integers", (0, 4)),
+    ];
+    for (source, message, span) in cases {
+      let err = Lexer::new(source).tokenize_all().unwrap_err();
+      assert_eq!(err.kind(), ErrorKind::Syntax, "{}", source);
+      assert_eq!(err.message(), message, "{}", source);
+      assert_eq!((err.span().start, err.span().end), span, "{}", source);
+    }
+  }
+
+  // 测试：标识符、硬关键字与软关键字
+  #[test]
+  fn names_and_keywords() {
+    let toks = Lexer::new("if x_1 else _ match null Inf").tokenize_all().unwrap();
+    let kinds: Vec<&TokenKind> = toks.iter().map(|t| t.kind()).collect();
+    assert_eq!(kinds, vec![
+      &TokenKind::Keyword(Keyword::If),
+      &TokenKind::Name("x_1".into()),
+      &TokenKind::Keyword(Keyword::Else),
+      &TokenKind::SoftKeyword("_".into()),
+      &TokenKind::SoftKeyword("match".into()),
+      &TokenKind::Keyword(Keyword::Null),
+      &TokenKind::Keyword(Keyword::Inf),
+      &TokenKind::Endmarker,
+    ]);
+    // r/b 开头但后面不是引号时仍是标识符
+    assert_eq!(single("rb"), TokenKind::Name("rb".into()));
+    assert_eq!(single("bar"), TokenKind::Name("bar".into()));
+  }
+  
+  // 测试：非 ASCII 标识符与 NFKC 规范化
+  #[test]
+  fn unicode_names() {
+    let s = "变量 = 1";
+    let toks = Lexer::new(s).tokenize_all().unwrap();
+    assert_eq!(toks[0].kind(), &TokenKind::Name("变量".into()));
+    assert_eq!((toks[0].span().start, toks[0].span().end), (0, 2));
+    assert_eq!(toks[1].kind(), &TokenKind::Equal);
+    assert_eq!(toks[2].kind(), &TokenKind::Int(1));
+  
+    assert_eq!(single("café"), TokenKind::Name("café".into()));
+    assert_eq!(single("ﬁle"), TokenKind::Name("file".into()));
+    assert_eq!(single("ｘ"), TokenKind::Name("x".into()));
+  
+    let err = Lexer::new("a€").tokenize_all().unwrap_err();
+    assert_eq!(err.message(), "invalid character '€' (U+20AC)");
+    assert_eq!((err.span().start, err.span().end), (1, 2));
+  }
+
+  fn kinds(s: &str) -> Vec<TokenKind> {
+    Lexer::new(s).tokenize_all().unwrap().into_iter().map(|t| t.kind().clone()).collect()
+  }
+
+  // 测试：f-string 拆分为 start/middle/end 与替换字段中的表达式 token
+  #[test]
+  fn fstring_tokens() {
+    let s = r#"f"x={x!r:>10}""#;
+    let toks = Lexer::new(s).tokenize_all().unwrap();
+    let expected = [
+      (TokenKind::FStringStart, "f\""),
+      (TokenKind::FStringMiddle("x=".into()), "x="),
+      (TokenKind::LBrace, "{"),
+      (TokenKind::Name("x".into()), "x"),
+      (TokenKind::Exclamation, "!"),
+      (TokenKind::Name("r".into()), "r"),
+      (TokenKind::Colon, ":"),
+      (TokenKind::FStringMiddle(">10".into()), ">10"),
+      (TokenKind::RBrace, "}"),
+      (TokenKind::FStringEnd, "\""),
+      (TokenKind::Endmarker, ""),
+    ];
+    assert_eq!(toks.len(), expected.len());
+    for (tok, (kind, text)) in toks.iter().zip(expected.iter()) {
+      assert_eq!(tok.kind(), kind);
+      assert_eq!(&s[tok.span().start..tok.span().end], *text);
+    }
+  }
+  
+  // 测试：{{ }} 转义、嵌套引号、嵌套字段与嵌套 f-string
+  #[test]
+  fn fstring_nesting() {
+    assert_eq!(kinds(r#"f'{{a}}\n'"#), vec![
+      TokenKind::FStringStart,
+      TokenKind::FStringMiddle("{a}\n".into()),
+      TokenKind::FStringEnd,
+      TokenKind::Endmarker,
+    ]);
+    assert_eq!(kinds(r#"f"{d["k"]}""#), vec![
+      TokenKind::FStringStart,
+      TokenKind::LBrace,
+      TokenKind::Name("d".into()),
+      TokenKind::LSqb,
+      TokenKind::String("k".into()),
+      TokenKind::RSqb,
+      TokenKind::RBrace,
+      TokenKind::FStringEnd,
+      TokenKind::Endmarker,
+    ]);
+    assert_eq!(kinds(r#"f"{x:{w}.2f} {f'{y}'=}""#), vec![
+      TokenKind::FStringStart,
+      TokenKind::LBrace,
+      TokenKind::Name("x".into()),
+      TokenKind::Colon,
+      TokenKind::LBrace,
+      TokenKind::Name("w".into()),
+      TokenKind::RBrace,
+      TokenKind::FStringMiddle(".2f".into()),
+      TokenKind::RBrace,
+      TokenKind::FStringMiddle(" ".into()),
+      TokenKind::LBrace,
+      TokenKind::FStringStart,
+      TokenKind::LBrace,
+      TokenKind::Name("y".into()),
+      TokenKind::RBrace,
+      TokenKind::FStringEnd,
+      TokenKind::Equal,
+      TokenKind::RBrace,
+      TokenKind::FStringEnd,
+      TokenKind::Endmarker,
+    ]);
+    // 字段内的字典字面量不结束字段；t-string 使用独立的 token
+    assert_eq!(kinds(r#"t'{ {1: 2}[1] }'"#), vec![
+      TokenKind::TStringStart,
+      TokenKind::LBrace,
+      TokenKind::LBrace,
+      TokenKind::Int(1),
+      TokenKind::Colon,
+      TokenKind::Int(2),
+      TokenKind::RBrace,
+      TokenKind::LSqb,
+      TokenKind::Int(1),
+      TokenKind::RSqb,
+      TokenKind::RBrace,
+      TokenKind::TStringEnd,
+      TokenKind::Endmarker,
+    ]);
+  }
+  
+  // 测试：f-string 的错误
+  #[test]
+  fn fstring_errors() {
+    let cases = [
+      ("f'abc", "unterminated f-string literal", (0, 5)),
+      ("f'{x", "unterminated f-string literal", (0, 4)),
+      ("t'''{x}", "unterminated triple-quoted t-string literal", (0, 7)),
+      ("f'a}'", "f-string: single '}' is not allowed", (3, 4)),
+      ("f'{x:>3'", "f-string: expecting '}'", (7, 8)),
+    ];
+    for (source, message, span) in cases {
+      let err = Lexer::new(source).tokenize_all().unwrap_err();
+      assert_eq!(err.message(), message, "{}", source);
+      assert_eq!((err.span().start, err.span().end), span, "{}", source);
+    }
+  }
+
   // 测试：空输入只产生 Endmarker
   #[test]
   fn empty_input() {
@@ -302,7 +1341,9 @@ mod tests {
 1
     "#;
     
+    // 空行只产生 Nl，不影响缩进
     let expected = [
+      TokenKind::Nl,
       TokenKind::Int(1),
       TokenKind::Newline,
       TokenKind::Indent(1),
@@ -316,16 +1357,130 @@ mod tests {
       TokenKind::Dedent(2),
       TokenKind::Int(2),
       TokenKind::Newline,
+      TokenKind::Nl,
       TokenKind::Dedent(1),
-      TokenKind::Newline,
       TokenKind::Int(1),
       TokenKind::Newline,
       TokenKind::Endmarker,
     ];
     let tokens = Lexer::new(s).tokenize_all().unwrap();
     
+    assert_eq!(tokens.len(), expected.len());
     for (i, token) in tokens.iter().enumerate() {
       assert_eq!(token.kind(), &expected[i]);
     }
   }
+  
+  // 测试：注释，可选择产出 Comment token
+  #[test]
+  fn comments() {
+    let s = "# head\n1 # one\n  # indented comment\n2";
+    assert_eq!(kinds(s), vec![
+      TokenKind::Nl, TokenKind::Int(1), TokenKind::Newline, TokenKind::Nl, TokenKind::Int(2), TokenKind::Endmarker,
+    ]);
+    
+    let tokens = Lexer::new(s).with_comments(true).tokenize_all().unwrap();
+    let comments: Vec<&str> = tokens.iter()
+      .filter(|t| t.kind() == &TokenKind::Comment)
+      .map(|t| &s[t.span().start..t.span().end])
+      .collect();
+    assert_eq!(comments, vec!["# head", "# one", "# indented comment"]);
+  }
+  
+  // 测试：括号内换行产生 Nl，且不计算缩进
+  #[test]
+  fn implicit_line_joining() {
+    let s = "f(1,\n    2,  # two\n[3,\n4])\nx";
+    assert_eq!(kinds(s), vec![
+      TokenKind::Name("f".into()), TokenKind::LPar, TokenKind::Int(1), TokenKind::Comma, TokenKind::Nl,
+      TokenKind::Int(2), TokenKind::Comma, TokenKind::Nl,
+      TokenKind::LSqb, TokenKind::Int(3), TokenKind::Comma, TokenKind::Nl,
+      TokenKind::Int(4), TokenKind::RSqb, TokenKind::RPar, TokenKind::Newline,
+      TokenKind::Name("x".into()), TokenKind::Endmarker,
+    ]);
+  }
+  
+  // 测试：反斜杠续行与 \r\n 换行
+  #[test]
+  fn backslash_continuation() {
+    let s = "1 + \\\n    2\r\n3";
+    assert_eq!(kinds(s), vec![
+      TokenKind::Int(1), TokenKind::Plus, TokenKind::Int(2), TokenKind::Newline, TokenKind::Int(3), TokenKind::Endmarker,
+    ]);
+    
+    let err = Lexer::new("1 + \\ 2").tokenize_all().unwrap_err();
+    assert_eq!(err.message(), "unexpected character after line continuation character");
+    let err = Lexer::new("1 + \\").tokenize_all().unwrap_err();
+    assert_eq!(err.message(), "unexpected EOF while parsing");
+  }
+  
+  // 测试：无损模式下拼接 trivia 与 token 文本可还原输入
+  #[test]
+  fn lossless_round_trip() {
+    let inputs = [
+      "",
+      "12 + 2 - 3",
+      "**= //= >>= <<= ... == != <> <= >= -> := && || ( ) [ ] { } ~ ^ @ ! %",
+      "1<<-2**-3",
+      r#"'a\tb\n\'\"\\'  "it's""#,
+      "\"\"\"line1\n'line2'\"\"\"",
+      r#"r'\d+\n' b'\x00\xffA' Br'\u'"#,
+      "2.75 .5 1. 1e-9 0x_ff_ff 1...",
+      "if x_1 else _ match null Inf",
+      "变量 = 1 # 注释\nﬁle",
+      r##"f"a{x!r:>{w}} {{b}}" t'{y=}' f"""{
+  z # 注释
+}""""##,
+      "\n1 \n  2\n    3 \n    3 \n  2\n\n1\n    ",
+      "# head\n1 # one\n  # indented comment\n2",
+      "f(1,\n    2,  # two\n[3,\n4])\nx",
+      "1 + \\\n    2\r\n3\r\n",
+      "1\n  2\n    3",
+      r#"'abc' '\x41\u00e9\U0001F600\101\0' '\N{GREEK SMALL LETTER ALPHA}' '\q' '''a""b''' '中文'"#,
+      "'a\\\nb'",
+      r#"R"\"" rb'\x00' u'x'"#,
+      "2.5E+3 1_000.000_1 1_000_000 0xFF 0o17 0B1010 000 9223372036854775807",
+      "变量 = 1\ncafé ｘ",
+      r#"f"x={x!r:>10}" f'{{a}}\n' f"{d["k"]}" f"{x:{w}.2f} {f'{y}'=}" t'{ {1: 2}[1] }'"#,
+      "1 + \\\n    2\r\n3",
+    ];
+    for s in inputs {
+      let tokens = Lexer::new(s).with_trivia(true).tokenize_all().unwrap();
+      let chars: Vec<char> = s.chars().collect();
+      let mut out = String::new();
+      for tok in &tokens {
+        assert!(!tok.kind().is_trivia());
+        for trivia in tok.leading() {
+          out.push_str(trivia.text());
+        }
+        out.extend(&chars[tok.span().start..tok.span().end]);
+        for trivia in tok.trailing() {
+          out.push_str(trivia.text());
+        }
+      }
+      assert_eq!(out, s);
+    }
+    
+    let tokens = Lexer::new("x = 1  # c\n\\\n").with_trivia(true).tokenize_all().unwrap();
+    let trailing: Vec<TriviaKind> = tokens[2].trailing().iter().map(|t| t.kind()).collect();
+    assert_eq!(trailing, vec![TriviaKind::Whitespace, TriviaKind::Comment]);
+    let leading: Vec<TriviaKind> = tokens[4].leading().iter().map(|t| t.kind()).collect();
+    assert_eq!(tokens[4].kind(), &TokenKind::Endmarker);
+    assert_eq!(leading, vec![TriviaKind::Continuation]);
+  }
+  
+  // 测试：文件结束时补齐 Dedent，缩进不匹配时报错
+  #[test]
+  fn dedent_at_eof() {
+    assert_eq!(kinds("1\n  2\n    3"), vec![
+      TokenKind::Int(1), TokenKind::Newline, TokenKind::Indent(1), TokenKind::Int(2), TokenKind::Newline,
+      TokenKind::Indent(2), TokenKind::Int(3), TokenKind::Dedent(2), TokenKind::Dedent(1), TokenKind::Endmarker,
+    ]);
+    
+    let err = Lexer::new("1\n    2\n  3").tokenize_all().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Indentation);
+    assert_eq!(err.message(), "unindent does not match any outer indentation level");
+    let err = Lexer::new("  1").tokenize_all().unwrap_err();
+    assert_eq!(err.message(), "unexpected indent");
+  }
 }
\ No newline at end of file
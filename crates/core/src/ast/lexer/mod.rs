@@ -1,4 +1,5 @@
 mod stream;
+#[allow(clippy::module_inception)]
 mod lexer;
 pub use stream::TokenStream;
 pub use lexer::Lexer;
\ No newline at end of file
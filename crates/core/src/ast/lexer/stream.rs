@@ -1,4 +1,5 @@
 use crate::Error;
+use crate::Span;
 use super::super::TokenKind;
 use super::super::Token;
 use super::Lexer;
@@ -27,6 +28,8 @@ impl<'a> TokenStream<'a> {
           self.buf.push_back(Ok(tok));
           break;
         },
+        // 注释与 Nl 对语法分析透明
+        Ok(tok) if tok.kind().is_trivia() => {},
         Ok(_) => self.buf.push_back(x),
         Err(_) => {
           self.buf.push_back(x);
@@ -45,13 +48,22 @@ impl<'a> TokenStream<'a> {
     self.buf.get(n - 1)
   }
 
+  /// 取出 span 对应的源码文本
+  pub fn source(&self, span: Span) -> String {
+    self.iter.source(span)
+  }
+
+  /// 将错误定位到源文件的行列
+  pub fn locate(&self, err: Error) -> Error {
+    self.iter.locate(err)
+  }
+
   /// 取出并消费下一个 token（owned）
   pub fn next_token(&mut self) -> Option<Result<Token, Error>> {
-    if let Some(t) = self.buf.pop_front() {
-      Some(t)
-    } else {
-      self.iter.next()
+    if self.buf.is_empty() {
+      self.ensure_buffered(1);
     }
+    self.buf.pop_front()
   }
 }
 
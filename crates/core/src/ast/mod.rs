@@ -3,9 +3,17 @@ mod lexer;
 mod parser;
 pub use token::TokenKind;
 pub use token::Token;
+pub use token::Trivia;
+pub use token::TriviaKind;
+pub use token::Keyword;
+pub use token::SOFT_KEYWORDS;
 pub use lexer::TokenStream;
 pub use lexer::Lexer;
 pub use parser::Parser;
 pub use parser::NodeId;
 pub use parser::NodeKind;
-pub use parser::Arena;
\ No newline at end of file
+pub use parser::Arena;
+pub use parser::Arguments;
+pub use parser::Comprehension;
+pub use parser::Interner;
+pub use parser::Symbol;
\ No newline at end of file
@@ -1,6 +1,11 @@
 mod nodes;
+#[allow(clippy::module_inception)]
 mod parser;
 pub use parser::Parser;
 pub use nodes::NodeId;
 pub use nodes::NodeKind;
-pub use nodes::Arena;
\ No newline at end of file
+pub use nodes::Arena;
+pub use nodes::Arguments;
+pub use nodes::Comprehension;
+pub use nodes::Interner;
+pub use nodes::Symbol;
\ No newline at end of file
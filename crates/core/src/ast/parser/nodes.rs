@@ -4,6 +4,8 @@ use std::collections::HashMap;
 
 pub type NodeId = usize;
 pub type Symbol = usize;
+/// 调用中的关键字参数：(参数名, 值)
+pub type KeywordArg = (Symbol, NodeId);
 
 /// 字符串驻留
 #[derive(Default, Debug)]
@@ -20,7 +22,7 @@ impl Interner {
     }
   }
   
-  fn intern(&mut self, s: &str) -> Symbol {
+  pub fn intern(&mut self, s: &str) -> Symbol {
     if let Some(&id) = self.map.get(s) { return id; }
     let id = self.vec.len();
     self.vec.push(s.to_string());
@@ -28,7 +30,7 @@ impl Interner {
     id
   }
   
-  fn resolve(&self, sym: Symbol) -> &str { &self.vec[sym] }
+  pub fn resolve(&self, sym: Symbol) -> &str { &self.vec[sym] }
 }
 
 #[derive(Debug)]
@@ -36,10 +38,106 @@ pub enum NodeKind {
   Module { body: Vec<NodeId> },
   Expr { value: NodeId },
   Constant { value: Token },
+  Name { id: Symbol },
+  /// f-string: values 由 Constant 与 FormattedValue 组成
+  JoinedStr { values: Vec<NodeId> },
+  /// f-string 的替换字段; conversion 为 's' / 'r' / 'a'，format_spec 为 JoinedStr
+  FormattedValue { value: NodeId, conversion: Option<char>, format_spec: Option<NodeId> },
+  /// t-string: values 由 Constant 与 Interpolation 组成
+  TemplateStr { values: Vec<NodeId> },
+  /// t-string 的插值字段，expression 为字段中表达式的源码文本
+  Interpolation { value: NodeId, expression: String, conversion: Option<char>, format_spec: Option<NodeId> },
   BinOp { left: NodeId, op: Token, right: NodeId },
   UnaryOp { op: Token, operand: NodeId },
-  Call { func: NodeId, args: Vec<NodeId> },
-  Assign { target: Symbol, value: NodeId },
+  /// a && b && c 或 a or b；op 为第一个运算符，values 至少两个，求值短路
+  BoolOp { op: Token, values: Vec<NodeId> },
+  /// !operand 或 not operand
+  Not { operand: NodeId },
+  /// left op1 c1 op2 c2 ...，链式比较 a < b <= c 中的 b 只求值一次
+  Compare { left: NodeId, ops: Vec<Token>, comparators: Vec<NodeId> },
+  /// 条件表达式 `body if test else orelse` 或 `test ? body : orelse`
+  IfExp { test: NodeId, body: NodeId, orelse: NodeId },
+  /// lambda args: body
+  Lambda { args: Arguments, body: NodeId },
+  /// func(args, name=value)
+  Call { func: NodeId, args: Vec<NodeId>, keywords: Vec<KeywordArg> },
+  /// value.attr
+  Attribute { value: NodeId, attr: Symbol },
+  /// value[slice]；多维下标 `a[i, j:k]` 的 slice 为 Tuple
+  Subscript { value: NodeId, slice: NodeId },
+  /// 下标中的 lower:upper:step，省略的部分为 None
+  Slice { lower: Option<NodeId>, upper: Option<NodeId>, step: Option<NodeId> },
+  Tuple { elts: Vec<NodeId> },
+  List { elts: Vec<NodeId> },
+  /// {k: v, **m}；keys 中的 None 表示 values 中对应的是 ** 展开的字典
+  Dict { keys: Vec<Option<NodeId>>, values: Vec<NodeId> },
+  Set { elts: Vec<NodeId> },
+  /// 元组、列表、集合中的 *iterable，或赋值目标中的 *name
+  Starred { value: NodeId },
+  /// [elt for ...]
+  ListComp { elt: NodeId, generators: Vec<Comprehension> },
+  /// {elt for ...}
+  SetComp { elt: NodeId, generators: Vec<Comprehension> },
+  /// {key: value for ...}
+  DictComp { key: NodeId, value: NodeId, generators: Vec<Comprehension> },
+  /// (elt for ...)，作为调用的唯一参数时可省略括号
+  GeneratorExp { elt: NodeId, generators: Vec<Comprehension> },
+  /// yield [value]
+  Yield { value: Option<NodeId> },
+  /// yield from value
+  YieldFrom { value: NodeId },
+  /// 链式赋值 `a = b = value` 的 targets 为 [a, b]；target 可以是 Name / Attribute / Subscript / Tuple / List
+  Assign { targets: Vec<NodeId>, value: NodeId },
+  /// 增量赋值 `target op= value`，op 为 `+=` 等 token
+  AugAssign { target: NodeId, op: Token, value: NodeId },
+  /// if 语句；elif 表示为 orelse 中唯一的 If
+  If { test: NodeId, body: Vec<NodeId>, orelse: Vec<NodeId> },
+  /// while 循环；orelse 在循环条件为假退出（而非 break）时执行
+  While { test: NodeId, body: Vec<NodeId>, orelse: Vec<NodeId> },
+  /// for target in iter；orelse 在迭代耗尽（而非 break）时执行
+  For { target: NodeId, iter: NodeId, body: Vec<NodeId>, orelse: Vec<NodeId> },
+  Break,
+  Continue,
+  FunctionDef { name: Symbol, args: Arguments, body: Vec<NodeId> },
+  /// class name(bases): body
+  ClassDef { name: Symbol, bases: Vec<NodeId>, body: Vec<NodeId> },
+  Return { value: Option<NodeId> },
+  /// raise [exc]；没有 exc 时重新抛出正在处理的异常
+  Raise { exc: Option<NodeId> },
+  /// try 语句；handlers 为 ExceptHandler，orelse 在未出错时执行，finalbody 总会执行
+  Try { body: Vec<NodeId>, handlers: Vec<NodeId>, orelse: Vec<NodeId>, finalbody: Vec<NodeId> },
+  /// except [ty [as name]]: body；ty 为 None 时捕获所有异常
+  ExceptHandler { ty: Option<NodeId>, name: Option<Symbol>, body: Vec<NodeId> },
+  Pass,
+  /// global a, b
+  Global { names: Vec<Symbol> },
+  /// nonlocal a, b
+  Nonlocal { names: Vec<Symbol> },
+}
+
+/// 函数的形参列表
+#[derive(Debug, Default)]
+pub struct Arguments {
+  /// 位置参数
+  pub args: Vec<Symbol>,
+  /// 最后 defaults.len() 个位置参数的默认值
+  pub defaults: Vec<NodeId>,
+  /// *args
+  pub vararg: Option<Symbol>,
+  /// '*' 或 *args 之后的仅关键字参数
+  pub kwonlyargs: Vec<Symbol>,
+  /// 与 kwonlyargs 一一对应，None 表示没有默认值
+  pub kw_defaults: Vec<Option<NodeId>>,
+  /// **kwargs
+  pub kwarg: Option<Symbol>,
+}
+
+/// 推导式中的一个 `for target in iter if cond ...` 子句
+#[derive(Debug)]
+pub struct Comprehension {
+  pub target: NodeId,
+  pub iter: NodeId,
+  pub ifs: Vec<NodeId>,
 }
 
 #[derive(Debug)]
@@ -58,14 +156,16 @@ impl Node {
   }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct Arena {
   pub nodes: Vec<Node>,
+  /// 标识符驻留表，Name 等节点中的 Symbol 由此解析
+  pub interner: Interner,
 }
 
 impl Arena {
   pub fn new() -> Self { 
-    Arena { nodes: Vec::new() } 
+    Arena { nodes: Vec::new(), interner: Interner::new() } 
   }
 
   pub fn alloc(&mut self, kind: NodeKind, span: Span) -> NodeId {
@@ -1,6 +1,7 @@
 use crate::Span;
-use crate::{Error, SyntaxError};
+use crate::{Error, SyntaxError, IndentationError};
 use super::super::TokenKind;
+use super::super::Keyword;
 use super::super::Token;
 use super::super::TokenStream;
 use super::super::Lexer;
@@ -9,8 +10,8 @@ use super::nodes::*;
 #[derive(Debug)]
 pub struct Parser<'a> {
   tokens: TokenStream<'a>,
-  pos: usize,
-  interner: Interner,
+  /// 上一个已消费 token 的结束位置，用于没有 token 可指向时的错误位置
+  prev_end: usize,
   pub arena: Arena,
 }
 
@@ -18,8 +19,7 @@ impl<'a> Parser<'a> {
   pub fn new(lexer: &'a mut Lexer) -> Self {
     Self {
       tokens: lexer.stream(),
-      pos: 0,
-      interner: Interner::new(),
+      prev_end: 0,
       arena: Arena::new(),
     }
   }
@@ -29,8 +29,39 @@ impl<'a> Parser<'a> {
   }
 
   fn next(&mut self) -> Option<Result<Token, Error>> {
-    self.pos += 1;
-    self.tokens.next_token()
+    let tok = self.tokens.next_token();
+    if let Some(Ok(tok)) = &tok {
+      self.prev_end = tok.span().end;
+    }
+    tok
+  }
+  
+  /// 上一个 token 之后的空 span
+  fn here(&self) -> Span {
+    Span::new(self.prev_end, self.prev_end)
+  }
+
+  /// 下一个 token 是否为 kind
+  fn check(&mut self, kind: &TokenKind) -> bool {
+    matches!(self.peek(), Some(Ok(tok)) if tok.kind() == kind)
+  }
+
+  /// 下一个 token 为 kind 时消费并返回它
+  fn eat(&mut self, kind: &TokenKind) -> Option<Token> {
+    if self.check(kind) {
+      self.next().and_then(|tok| tok.ok())
+    } else {
+      None
+    }
+  }
+
+  /// 下一个 token 属于 kinds 时消费并返回它；词法错误直接返回
+  fn eat_any(&mut self, kinds: &[TokenKind]) -> Result<Option<Token>, Error> {
+    match self.peek() {
+      Some(Ok(tok)) if kinds.contains(tok.kind()) => Ok(self.next().map(|tok| tok.expect("Ok"))),
+      Some(Err(_)) => Err(self.next().expect("Some").expect_err("Err")),
+      _ => Ok(None),
+    }
   }
 
   fn blanks(&mut self) -> usize {
@@ -40,7 +71,6 @@ impl<'a> Parser<'a> {
         Some(Ok(tok)) if tok.kind() == &TokenKind::Newline  => {
           self.next();
           count += 1;
-          break;
         },
         Some(_) | None => break,
       }
@@ -48,94 +78,635 @@ impl<'a> Parser<'a> {
     count
   }
 
+  /// 解析整个文件；Lexer 来自 SourceFile 时，错误附带文件名与行列
   pub fn parse(&mut self) -> Result<NodeId, Error> {
+    self.parse_file().map_err(|err| self.tokens.locate(err))
+  }
+  
+  fn parse_file(&mut self) -> Result<NodeId, Error> {
     let node = self.file()?;
     // ensure Endmarker
     match self.peek() {
       Some(Ok(tok)) if tok.kind() == &TokenKind::Endmarker => Ok(node),
-      Some(_) | None => Err(SyntaxError::new("invalid syntax", Span::new(self.pos, self.pos))),
+      Some(Ok(tok)) => Err(SyntaxError::new("invalid syntax", tok.span())),
+      Some(Err(_)) => Err(self.next().expect("Some").expect_err("Err")),
+      None => Err(SyntaxError::new("invalid syntax", self.here())),
     }
   }
   
+  /// program: [statements] ENDMARKER
   fn file(&mut self) -> Result<NodeId, Error> {
     let body = self.statements()?;
-    let node_first = self.arena.get(body[0]);
-    let node_end = self.arena.get(body[0]);
+    let span = match (body.first(), body.last()) {
+      (Some(first), Some(last)) => Span::new(self.arena.get(*first).span().start, self.arena.get(*last).span().end),
+      _ => self.here(),
+    };
     Ok(self.arena.alloc(
       NodeKind::Module { body }, 
-      Span::new(node_first.span().start, node_end.span().end),
+      span,
     ))
   }
   
+  /// statements: statement+（语句之间可以有空行，遇到 DEDENT 或 ENDMARKER 结束）
   fn statements(&mut self) -> Result<Vec<NodeId>, Error> {
-    self.blanks();
-    match self.peek() {
-      Some(Err(_)) => {
-        return Err(self.next().expect("Some").expect_err("Err"));
-      },
-      Some(Ok(tok)) if tok.kind() == &TokenKind::Endmarker => return Ok(Vec::new()),
-      Some(_) => {},
-      None => return Err(SyntaxError::new("no eof", Span::new(self.pos, self.pos))),
-    }
-    let res = self.statement()?;
-    /*loop {
-      if self.blanks() == 0 {
-        break;
+    let mut body = Vec::new();
+    loop {
+      self.blanks();
+      match self.peek() {
+        Some(Err(_)) => {
+          return Err(self.next().expect("Some").expect_err("Err"));
+        },
+        Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Endmarker | TokenKind::Dedent(..)) => break,
+        Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Indent(..)) => {
+          return Err(IndentationError::new("unexpected indent", tok.span()));
+        },
+        Some(_) => {},
+        None => return Err(SyntaxError::new("no eof", self.here())),
       }
-    }*/
-    Ok(res)
+      body.extend(self.statement()?);
+    }
+    Ok(body)
   }
   
+  /// statement: compound_stmt | stmts
   fn statement(&mut self) -> Result<Vec<NodeId>, Error> {
-    Ok(self.stmts()?)
+    match self.peek() {
+      Some(Ok(tok)) if matches!(
+        tok.kind(),
+        TokenKind::Keyword(Keyword::If | Keyword::While | Keyword::For | Keyword::Def | Keyword::Class | Keyword::Try)
+      ) => Ok(vec![self.compound_stmt()?]),
+      _ => self.stmts(),
+    }
   }
   
+  /// stmts: ';'.simple_stmt+ [';'] NEWLINE（文件或块末尾的最后一行可以没有 NEWLINE）
   fn stmts(&mut self) -> Result<Vec<NodeId>, Error> {
-    let mut items = Vec::new();
-    let item = self.stmt()?;
-    items.push(item);
+    let mut items = vec![self.simple_stmt()?];
+    while self.eat(&TokenKind::Semi).is_some() {
+      if let Some(Ok(tok)) = self.peek() && matches!(tok.kind(), TokenKind::Newline | TokenKind::Endmarker | TokenKind::Dedent(..)) {
+        break;
+      }
+      items.push(self.simple_stmt()?);
+    }
+    match self.peek() {
+      Some(Ok(tok)) if tok.kind() == &TokenKind::Newline => {
+        self.next();
+      },
+      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Endmarker | TokenKind::Dedent(..)) => {},
+      Some(Ok(tok)) => return Err(SyntaxError::new("invalid syntax", tok.span())),
+      Some(Err(_)) => return Err(self.next().expect("Some").expect_err("Err")),
+      None => return Err(SyntaxError::new("no eof", self.here())),
+    }
     Ok(items)
   }
   
-  fn stmt(&mut self) -> Result<NodeId, Error> {
-    if let Ok(item) = self.compound_stmt() {
-      Ok(item)
+  /// compound_stmt: function_def | class_def | if_stmt | while_stmt | for_stmt | try_stmt
+  fn compound_stmt(&mut self) -> Result<NodeId, Error> {
+    match self.peek() {
+      Some(Ok(tok)) if tok.kind() == &TokenKind::Keyword(Keyword::Try) => self.try_stmt(),
+      Some(Ok(tok)) if tok.kind() == &TokenKind::Keyword(Keyword::Def) => self.function_def(),
+      Some(Ok(tok)) if tok.kind() == &TokenKind::Keyword(Keyword::Class) => self.class_def(),
+      Some(Ok(tok)) if tok.kind() == &TokenKind::Keyword(Keyword::If) => self.if_stmt(),
+      Some(Ok(tok)) if tok.kind() == &TokenKind::Keyword(Keyword::While) => self.while_stmt(),
+      Some(Ok(tok)) if tok.kind() == &TokenKind::Keyword(Keyword::For) => self.for_stmt(),
+      _ => Err(SyntaxError::new("invalid syntax", self.here())),
+    }
+  }
+  
+  /// if_stmt: 'if' expression ':' block (elif_stmt | [else_block])
+  /// elif_stmt 与 if_stmt 结构相同，作为外层 If 的 orelse
+  fn if_stmt(&mut self) -> Result<NodeId, Error> {
+    let keyword = self.next().expect("Some").expect("Ok");
+    let what = if keyword.kind() == &TokenKind::Keyword(Keyword::If) { "'if' statement" } else { "'elif' statement" };
+    let test = self.expression()?;
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.block(what, &keyword)?;
+    let orelse = if self.check(&TokenKind::Keyword(Keyword::Elif)) {
+      vec![self.if_stmt()?]
     } else {
-      Ok(self.simple_stmt()?)
+      self.else_block()?
+    };
+    let span = self.block_span(&keyword, &body, &orelse);
+    Ok(self.arena.alloc(NodeKind::If { test, body, orelse }, span))
+  }
+  
+  /// while_stmt: 'while' expression ':' block [else_block]
+  fn while_stmt(&mut self) -> Result<NodeId, Error> {
+    let keyword = self.next().expect("Some").expect("Ok");
+    let test = self.expression()?;
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.block("'while' statement", &keyword)?;
+    let orelse = self.else_block()?;
+    let span = self.block_span(&keyword, &body, &orelse);
+    Ok(self.arena.alloc(NodeKind::While { test, body, orelse }, span))
+  }
+  
+  /// for_stmt: 'for' star_targets 'in' star_expressions ':' block [else_block]
+  fn for_stmt(&mut self) -> Result<NodeId, Error> {
+    let keyword = self.next().expect("Some").expect("Ok");
+    let target = self.star_targets()?;
+    self.expect(&TokenKind::Keyword(Keyword::In), "expected 'in'")?;
+    let iter = self.star_expressions()?;
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.block("'for' statement", &keyword)?;
+    let orelse = self.else_block()?;
+    let span = self.block_span(&keyword, &body, &orelse);
+    Ok(self.arena.alloc(NodeKind::For { target, iter, body, orelse }, span))
+  }
+  
+  /// try_stmt: 'try' ':' block finally_block
+  ///   | 'try' ':' block except_block+ [else_block] [finally_block]
+  fn try_stmt(&mut self) -> Result<NodeId, Error> {
+    let keyword = self.next().expect("Some").expect("Ok");
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.block("'try' statement", &keyword)?;
+    let mut handlers = Vec::new();
+    while let Some(except) = self.eat(&TokenKind::Keyword(Keyword::Except)) {
+      if let Some(last) = handlers.last() && matches!(self.arena.get(*last).kind(), NodeKind::ExceptHandler { ty: None, .. }) {
+        return Err(SyntaxError::new("default 'except:' must be last", *self.arena.get(*last).span()));
+      }
+      handlers.push(self.except_block(except)?);
+    }
+    let orelse = if handlers.is_empty() { Vec::new() } else { self.else_block()? };
+    let finalbody = match self.eat(&TokenKind::Keyword(Keyword::Finally)) {
+      Some(finally) => {
+        self.expect(&TokenKind::Colon, "expected ':'")?;
+        self.block("'finally' statement", &finally)?
+      },
+      None => Vec::new(),
+    };
+    if handlers.is_empty() && finalbody.is_empty() {
+      let span = match self.peek() {
+        Some(Ok(tok)) => tok.span(),
+        _ => self.here(),
+      };
+      return Err(SyntaxError::new("expected 'except' or 'finally' block", span));
     }
+    let last = [&finalbody, &orelse, &handlers, &body].into_iter()
+      .find_map(|nodes| nodes.last())
+      .expect("non-empty block");
+    let span = Span::new(keyword.span().start, self.arena.get(*last).span().end);
+    Ok(self.arena.alloc(NodeKind::Try { body, handlers, orelse, finalbody }, span))
   }
   
-  fn compound_stmt(&mut self) -> Result<NodeId, Error> {
-    Err(SyntaxError::new("not implement", Span::new(self.pos, self.pos)))
+  /// except_block: 'except' expression ['as' NAME] ':' block | 'except' ':' block
+  fn except_block(&mut self, keyword: Token) -> Result<NodeId, Error> {
+    let (ty, name) = if self.check(&TokenKind::Colon) {
+      (None, None)
+    } else {
+      let ty = self.expression()?;
+      let name = match self.eat(&TokenKind::Keyword(Keyword::As)) {
+        Some(_) => Some(self.name()?.0),
+        None => None,
+      };
+      (Some(ty), name)
+    };
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.block("'except' statement", &keyword)?;
+    let span = self.block_span(&keyword, &body, &[]);
+    Ok(self.arena.alloc(NodeKind::ExceptHandler { ty, name, body }, span))
   }
   
-  fn simple_stmt(&mut self) -> Result<NodeId, Error> {
-    let item = self.star_expressions()?;
-    Ok(item)
+  /// function_def: 'def' NAME '(' [params] ')' ':' block
+  fn function_def(&mut self) -> Result<NodeId, Error> {
+    let keyword = self.next().expect("Some").expect("Ok");
+    let (name, _) = self.name()?;
+    let lpar = self.expect(&TokenKind::LPar, "expected '('")?;
+    let args = self.params(&TokenKind::RPar)?;
+    self.closing(&lpar, TokenKind::RPar)?;
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.block("function definition", &keyword)?;
+    let span = self.block_span(&keyword, &body, &[]);
+    Ok(self.arena.alloc(NodeKind::FunctionDef { name, args, body }, span))
   }
   
-  fn star_expressions(&mut self) -> Result<NodeId, Error> {
-    let start = self.pos;
-    let value = self.star_expression()?;
+  /// class_def: 'class' NAME ['(' [arguments] ')'] ':' block
+  /// 基类只能是位置参数
+  fn class_def(&mut self) -> Result<NodeId, Error> {
+    let keyword = self.next().expect("Some").expect("Ok");
+    let (name, _) = self.name()?;
+    let bases = match self.eat(&TokenKind::LPar) {
+      Some(lpar) => {
+        let (bases, keywords) = self.arguments()?;
+        self.closing(&lpar, TokenKind::RPar)?;
+        if let Some((_, value)) = keywords.first() {
+          return Err(SyntaxError::new("keyword arguments are not supported in class definition", *self.arena.get(*value).span()));
+        }
+        bases
+      },
+      None => Vec::new(),
+    };
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.block("class definition", &keyword)?;
+    let span = self.block_span(&keyword, &body, &[]);
+    Ok(self.arena.alloc(NodeKind::ClassDef { name, bases, body }, span))
+  }
+  
+  /// params: 位置参数 (可带默认值)，'*' [NAME] 之后为仅关键字参数，最后可以是 '**' NAME
+  /// end 为结束形参列表的 token：函数定义为 ')'，lambda 为 ':'
+  fn params(&mut self, end: &TokenKind) -> Result<Arguments, Error> {
+    let mut args = Arguments::default();
+    let mut names: Vec<Symbol> = Vec::new();
+    // 是否已出现 '*' 或 *args
+    let mut star = None;
+    while !self.check(end) {
+      let (name, span) = if self.eat(&TokenKind::DoubleStar).is_some() {
+        let (name, span) = self.name()?;
+        args.kwarg = Some(name);
+        self.eat(&TokenKind::Comma);
+        if let Some(Ok(tok)) = self.peek() && tok.kind() != end {
+          return Err(SyntaxError::new("arguments cannot follow var-keyword argument", tok.span()));
+        }
+        (name, span)
+      } else if let Some(tok) = self.eat(&TokenKind::Star) {
+        if star.is_some() {
+          return Err(SyntaxError::new("* argument may appear only once", tok.span()));
+        }
+        star = Some(tok.span());
+        if self.eat(&TokenKind::Comma).is_some() {
+          continue;
+        }
+        if self.check(end) {
+          break;
+        }
+        let (name, span) = self.name()?;
+        args.vararg = Some(name);
+        (name, span)
+      } else {
+        let (name, span) = self.name()?;
+        let default = match self.eat(&TokenKind::Equal) {
+          Some(_) => Some(self.expression()?),
+          None => None,
+        };
+        if star.is_some() {
+          args.kwonlyargs.push(name);
+          args.kw_defaults.push(default);
+        } else {
+          args.args.push(name);
+          match default {
+            Some(default) => args.defaults.push(default),
+            None if !args.defaults.is_empty() => {
+              return Err(SyntaxError::new("parameter without a default follows parameter with a default", span));
+            },
+            None => {},
+          }
+        }
+        (name, span)
+      };
+      if names.contains(&name) {
+        return Err(SyntaxError::new(
+          format!("duplicate argument '{}' in function definition", self.arena.interner.resolve(name)),
+          span,
+        ));
+      }
+      names.push(name);
+      if self.eat(&TokenKind::Comma).is_none() {
+        break;
+      }
+    }
+    if let Some(span) = star && args.vararg.is_none() && args.kwonlyargs.is_empty() {
+      return Err(SyntaxError::new("named arguments must follow bare *", span));
+    }
+    Ok(args)
+  }
+  
+  /// NAME（软关键字也可以作为标识符）
+  fn name(&mut self) -> Result<(Symbol, Span), Error> {
+    match self.next() {
+      Some(Ok(tok)) => match tok.kind() {
+        TokenKind::Name(name) | TokenKind::SoftKeyword(name) => Ok((self.arena.interner.intern(name), tok.span())),
+        _ => Err(SyntaxError::new("invalid syntax", tok.span())),
+      },
+      Some(Err(err)) => Err(err),
+      None => Err(SyntaxError::new("invalid syntax", self.here())),
+    }
+  }
+  
+  /// ','.NAME+，返回名字列表与最后一个名字的结束位置
+  fn names(&mut self) -> Result<(Vec<Symbol>, usize), Error> {
+    let mut names = Vec::new();
+    loop {
+      let (name, span) = self.name()?;
+      names.push(name);
+      if self.eat(&TokenKind::Comma).is_none() {
+        return Ok((names, span.end));
+      }
+    }
+  }
+  
+  /// else_block: 'else' ':' block；没有 else 时为空
+  fn else_block(&mut self) -> Result<Vec<NodeId>, Error> {
+    match self.eat(&TokenKind::Keyword(Keyword::Else)) {
+      Some(keyword) => {
+        self.expect(&TokenKind::Colon, "expected ':'")?;
+        self.block("'else' statement", &keyword)
+      },
+      None => Ok(Vec::new()),
+    }
+  }
+  
+  /// 复合语句的 span：从关键字到最后一个子语句
+  fn block_span(&self, keyword: &Token, body: &[NodeId], orelse: &[NodeId]) -> Span {
+    let last = *orelse.last().or(body.last()).expect("non-empty block");
+    Span::new(keyword.span().start, self.arena.get(last).span().end)
+  }
+  
+  /// block: NEWLINE INDENT statements DEDENT | stmts
+  /// what 描述块所属的语句，用于 "expected an indented block after ..." 错误
+  fn block(&mut self, what: &str, keyword: &Token) -> Result<Vec<NodeId>, Error> {
+    if self.eat(&TokenKind::Newline).is_none() && !self.check(&TokenKind::Endmarker) {
+      return self.stmts();
+    }
     match self.peek() {
+      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Indent(..)) => {
+        self.next();
+      },
       Some(Ok(tok)) => {
-        let end = tok.span().end;
-        Ok(self.arena.alloc(
-          NodeKind::Expr { value }, 
-          Span::new(start, end),
-        ))
+        let span = if tok.kind() == &TokenKind::Endmarker { keyword.span() } else { tok.span() };
+        return Err(IndentationError::new(
+          format!("expected an indented block after {}", what),
+          span,
+        ));
       },
-      Some(Err(_)) => Err(self.next().expect("Some").expect_err("Err")),
-      None => Err(SyntaxError::new("no except", Span::new(self.pos, self.pos))),
+      Some(Err(_)) => return Err(self.next().expect("Some").expect_err("Err")),
+      None => return Err(SyntaxError::new("no eof", self.here())),
+    }
+    let body = self.statements()?;
+    // statements 只会停在 DEDENT 或 ENDMARKER；词法分析器在文件末尾补齐 DEDENT
+    if let Some(Ok(tok)) = self.peek() && matches!(tok.kind(), TokenKind::Dedent(..)) {
+      self.next();
+    }
+    Ok(body)
+  }
+  
+  /// 消费 kind，否则在下一个 token 处报告 message
+  fn expect(&mut self, kind: &TokenKind, message: &str) -> Result<Token, Error> {
+    match self.next() {
+      Some(Ok(tok)) if tok.kind() == kind => Ok(tok),
+      Some(Ok(tok)) => Err(SyntaxError::new(message, tok.span())),
+      Some(Err(err)) => Err(err),
+      None => Err(SyntaxError::new(message, self.here())),
+    }
+  }
+  
+  /// simple_stmt: assignment | star_expressions | yield_expr | 'pass' | 'break' | 'continue' | 'return' | 'raise' | 'global' | 'nonlocal'
+  fn simple_stmt(&mut self) -> Result<NodeId, Error> {
+    if let Some(tok) = self.eat(&TokenKind::Keyword(Keyword::Pass)) {
+      return Ok(self.arena.alloc(NodeKind::Pass, tok.span()));
+    }
+    if let Some(tok) = self.eat(&TokenKind::Keyword(Keyword::Break)) {
+      return Ok(self.arena.alloc(NodeKind::Break, tok.span()));
+    }
+    if let Some(tok) = self.eat(&TokenKind::Keyword(Keyword::Continue)) {
+      return Ok(self.arena.alloc(NodeKind::Continue, tok.span()));
+    }
+    if let Some(tok) = self.eat(&TokenKind::Keyword(Keyword::Return)) {
+      let value = match self.peek() {
+        Some(Ok(next)) if matches!(
+          next.kind(),
+          TokenKind::Newline | TokenKind::Semi | TokenKind::Endmarker | TokenKind::Dedent(..)
+        ) => None,
+        _ => Some(self.star_expressions()?),
+      };
+      let end = value.map_or(tok.span().end, |value| self.arena.get(value).span().end);
+      return Ok(self.arena.alloc(NodeKind::Return { value }, Span::new(tok.span().start, end)));
+    }
+    if let Some(tok) = self.eat(&TokenKind::Keyword(Keyword::Raise)) {
+      let exc = match self.peek() {
+        Some(Ok(next)) if matches!(
+          next.kind(),
+          TokenKind::Newline | TokenKind::Semi | TokenKind::Endmarker | TokenKind::Dedent(..)
+        ) => None,
+        _ => Some(self.expression()?),
+      };
+      let end = exc.map_or(tok.span().end, |exc| self.arena.get(exc).span().end);
+      return Ok(self.arena.alloc(NodeKind::Raise { exc }, Span::new(tok.span().start, end)));
+    }
+    if let Some(tok) = self.eat(&TokenKind::Keyword(Keyword::Global)) {
+      let (names, end) = self.names()?;
+      return Ok(self.arena.alloc(NodeKind::Global { names }, Span::new(tok.span().start, end)));
+    }
+    if let Some(tok) = self.eat(&TokenKind::Keyword(Keyword::Nonlocal)) {
+      let (names, end) = self.names()?;
+      return Ok(self.arena.alloc(NodeKind::Nonlocal { names }, Span::new(tok.span().start, end)));
+    }
+    let first = self.yield_or_star_expressions()?;
+    if self.check(&TokenKind::Equal) {
+      return self.assignment(first);
+    }
+    if let Some(Ok(tok)) = self.peek() && is_augassign(tok.kind()) {
+      return self.aug_assignment(first);
+    }
+    let span = *self.arena.get(first).span();
+    Ok(self.arena.alloc(
+      NodeKind::Expr { value: first }, 
+      span,
+    ))
+  }
+  
+  /// assignment: (star_targets '=')+ (yield_expr | star_expressions)
+  /// 等号左侧先按表达式解析，再检查能否作为赋值目标
+  fn assignment(&mut self, first: NodeId) -> Result<NodeId, Error> {
+    let mut targets = vec![first];
+    while self.eat(&TokenKind::Equal).is_some() {
+      targets.push(self.yield_or_star_expressions()?);
+    }
+    let value = targets.pop().expect("value");
+    for target in &targets {
+      self.check_target(*target)?;
     }
+    let span = Span::new(self.arena.get(first).span().start, self.arena.get(value).span().end);
+    Ok(self.arena.alloc(NodeKind::Assign { targets, value }, span))
   }
   
+  /// assignment: single_target augassign (yield_expr | star_expressions)
+  fn aug_assignment(&mut self, target: NodeId) -> Result<NodeId, Error> {
+    let node = self.arena.get(target);
+    let illegal = match node.kind() {
+      NodeKind::Name { .. } | NodeKind::Attribute { .. } | NodeKind::Subscript { .. } => None,
+      NodeKind::Tuple { .. } => Some("tuple"),
+      NodeKind::List { .. } => Some("list"),
+      NodeKind::Dict { .. } => Some("dict literal"),
+      NodeKind::Set { .. } => Some("set display"),
+      NodeKind::ListComp { .. } => Some("list comprehension"),
+      NodeKind::DictComp { .. } => Some("dict comprehension"),
+      NodeKind::SetComp { .. } => Some("set comprehension"),
+      NodeKind::GeneratorExp { .. } => Some("generator expression"),
+      NodeKind::Yield { .. } | NodeKind::YieldFrom { .. } => Some("yield expression"),
+      NodeKind::Starred { .. } => Some("starred"),
+      NodeKind::Constant { .. } => Some("literal"),
+      NodeKind::JoinedStr { .. } | NodeKind::TemplateStr { .. } => Some("f-string expression"),
+      NodeKind::Call { .. } => Some("function call"),
+      NodeKind::IfExp { .. } => Some("conditional expression"),
+      NodeKind::Compare { .. } => Some("comparison"),
+      NodeKind::Lambda { .. } => Some("lambda"),
+      _ => Some("expression"),
+    };
+    if let Some(illegal) = illegal {
+      return Err(SyntaxError::new(
+        format!("'{}' is an illegal expression for augmented assignment", illegal),
+        *node.span(),
+      ));
+    }
+    let op = self.next().expect("Some").expect("Ok");
+    let value = self.yield_or_star_expressions()?;
+    let span = Span::new(self.arena.get(target).span().start, self.arena.get(value).span().end);
+    Ok(self.arena.alloc(NodeKind::AugAssign { target, op, value }, span))
+  }
+  
+  /// 检查赋值目标：Name / Attribute / Subscript，或由它们组成的 Tuple / List
+  fn check_target(&self, target: NodeId) -> Result<(), Error> {
+    let node = self.arena.get(target);
+    let what = match node.kind() {
+      NodeKind::Name { .. } | NodeKind::Attribute { .. } | NodeKind::Subscript { .. } => return Ok(()),
+      NodeKind::Tuple { elts } | NodeKind::List { elts } => {
+        for elt in elts {
+          self.check_target(*elt)?;
+        }
+        return Ok(());
+      },
+      NodeKind::Starred { value } => return self.check_target(*value),
+      NodeKind::Dict { .. } => "dict literal".to_string(),
+      NodeKind::Set { .. } => "set display".to_string(),
+      NodeKind::ListComp { .. } => "list comprehension".to_string(),
+      NodeKind::DictComp { .. } => "dict comprehension".to_string(),
+      NodeKind::SetComp { .. } => "set comprehension".to_string(),
+      NodeKind::GeneratorExp { .. } => "generator expression".to_string(),
+      NodeKind::Yield { .. } | NodeKind::YieldFrom { .. } => "yield expression".to_string(),
+      NodeKind::Constant { value } if matches!(value.kind(), TokenKind::Keyword(..)) => self.tokens.source(*node.span()),
+      NodeKind::Constant { .. } => "literal".to_string(),
+      NodeKind::JoinedStr { .. } | NodeKind::TemplateStr { .. } => "f-string expression".to_string(),
+      NodeKind::Call { .. } => "function call".to_string(),
+      NodeKind::IfExp { .. } => "conditional expression".to_string(),
+      NodeKind::Compare { .. } => "comparison".to_string(),
+      NodeKind::Lambda { .. } => "lambda".to_string(),
+      _ => "expression".to_string(),
+    };
+    Err(SyntaxError::new(format!("cannot assign to {}", what), *node.span()))
+  }
+  
+  /// star_targets: star_target (',' star_target )* [',']
+  /// 目标按 primary 解析，因此不会吞掉 for 语句中的 'in'
+  fn star_targets(&mut self) -> Result<NodeId, Error> {
+    let first = self.star_target()?;
+    let target = if self.check(&TokenKind::Comma) {
+      let start = self.arena.get(first).span().start;
+      let mut elts = vec![first];
+      while self.eat(&TokenKind::Comma).is_some() && !self.at_sequence_end() {
+        elts.push(self.star_target()?);
+      }
+      self.arena.alloc(NodeKind::Tuple { elts }, Span::new(start, self.prev_end))
+    } else {
+      first
+    };
+    self.check_target(target)?;
+    Ok(target)
+  }
+  
+  /// star_target: '*' primary | primary
+  fn star_target(&mut self) -> Result<NodeId, Error> {
+    match self.eat(&TokenKind::Star) {
+      Some(star) => {
+        let value = self.primary()?;
+        let span = Span::new(star.span().start, self.arena.get(value).span().end);
+        Ok(self.arena.alloc(NodeKind::Starred { value }, span))
+      },
+      None => self.primary(),
+    }
+  }
+  
+  /// star_expressions: star_expression (',' star_expression )* [',']
+  /// 含逗号时为不带括号的元组
+  fn star_expressions(&mut self) -> Result<NodeId, Error> {
+    let first = self.star_expression()?;
+    if !self.check(&TokenKind::Comma) {
+      return Ok(first);
+    }
+    let start = self.arena.get(first).span().start;
+    let mut elts = vec![first];
+    while self.eat(&TokenKind::Comma).is_some() && !self.at_sequence_end() {
+      elts.push(self.star_expression()?);
+    }
+    Ok(self.arena.alloc(NodeKind::Tuple { elts }, Span::new(start, self.prev_end)))
+  }
+  
+  /// yield_expr | star_expressions
+  fn yield_or_star_expressions(&mut self) -> Result<NodeId, Error> {
+    match self.eat(&TokenKind::Keyword(Keyword::Yield)) {
+      Some(keyword) => self.yield_expr(keyword),
+      None => self.star_expressions(),
+    }
+  }
+  
+  /// yield_expr: 'yield' 'from' expression | 'yield' [star_expressions]
+  fn yield_expr(&mut self, keyword: Token) -> Result<NodeId, Error> {
+    let start = keyword.span().start;
+    if self.eat(&TokenKind::Keyword(Keyword::From)).is_some() {
+      let value = self.expression()?;
+      let span = Span::new(start, self.arena.get(value).span().end);
+      return Ok(self.arena.alloc(NodeKind::YieldFrom { value }, span));
+    }
+    let ends = self.at_sequence_end() || matches!(self.peek(), Some(Ok(next)) if matches!(next.kind(), TokenKind::Dedent(..)));
+    let value = if ends { None } else { Some(self.star_expressions()?) };
+    let end = value.map_or(keyword.span().end, |value| self.arena.get(value).span().end);
+    Ok(self.arena.alloc(NodeKind::Yield { value }, Span::new(start, end)))
+  }
+  
+  /// 下一个 token 是否结束一个以逗号分隔的序列（用于判断尾随逗号）
+  fn at_sequence_end(&mut self) -> bool {
+    match self.peek() {
+      Some(Ok(tok)) => matches!(
+        tok.kind(),
+        TokenKind::Newline | TokenKind::Endmarker | TokenKind::Semi | TokenKind::Equal
+          | TokenKind::RPar | TokenKind::RSqb | TokenKind::RBrace | TokenKind::Colon
+          | TokenKind::Keyword(Keyword::In)
+      ) || is_augassign(tok.kind()),
+      Some(Err(_)) => false,
+      None => true,
+    }
+  }
+  
+  /// star_expression: '*' bitwise_or | expression
   fn star_expression(&mut self) -> Result<NodeId, Error> {
-    Ok(self.expression()?)
+    match self.eat(&TokenKind::Star) {
+      Some(star) => {
+        let value = self.bitwise_or()?;
+        let span = Span::new(star.span().start, self.arena.get(value).span().end);
+        Ok(self.arena.alloc(NodeKind::Starred { value }, span))
+      },
+      None => self.expression(),
+    }
   }
   
   fn expression(&mut self) -> Result<NodeId, Error> {
-    Ok(self.disjunction()?)
+    if let Some(keyword) = self.eat(&TokenKind::Keyword(Keyword::Lambda)) {
+      return self.lambdef(keyword);
+    }
+    let first = self.disjunction()?;
+    let start = self.arena.get(first).span().start;
+    let (test, body) = if self.eat(&TokenKind::Keyword(Keyword::If)).is_some() {
+      let test = self.disjunction()?;
+      self.expect(&TokenKind::Keyword(Keyword::Else), "expected 'else' after 'if' expression")?;
+      (test, first)
+    } else if self.eat(&TokenKind::Question).is_some() {
+      let body = self.disjunction()?;
+      self.expect(&TokenKind::Colon, "expected ':' after '?' expression")?;
+      (first, body)
+    } else {
+      return Ok(first);
+    };
+    let orelse = self.expression()?;
+    let span = Span::new(start, self.arena.get(orelse).span().end);
+    Ok(self.arena.alloc(NodeKind::IfExp { test, body, orelse }, span))
+  }
+  
+  /// lambdef: 'lambda' [lambda_params] ':' expression
+  fn lambdef(&mut self, keyword: Token) -> Result<NodeId, Error> {
+    let args = self.params(&TokenKind::Colon)?;
+    self.expect(&TokenKind::Colon, "expected ':'")?;
+    let body = self.expression()?;
+    let span = Span::new(keyword.span().start, self.arena.get(body).span().end);
+    Ok(self.arena.alloc(NodeKind::Lambda { args, body }, span))
   }
   
   fn bin_op<F>(
@@ -165,68 +736,102 @@ impl<'a> Parser<'a> {
     Ok(left)
   }
   
+  /// 以 operators 中任一运算符连接的操作数合并为一个 BoolOp
+  fn bool_op<F>(&mut self, operators: &[TokenKind], func: F) -> Result<NodeId, Error>
+  where
+    F: Fn(&mut Self) -> Result<NodeId, Error>
+  {
+    let first = func(self)?;
+    let mut op = None;
+    let mut values = vec![first];
+    while let Some(tok) = self.eat_any(operators)? {
+      op.get_or_insert(tok);
+      values.push(func(self)?);
+    }
+    let Some(op) = op else {
+      return Ok(first);
+    };
+    let span = Span::new(self.arena.get(first).span().start, self.prev_end);
+    Ok(self.arena.alloc(NodeKind::BoolOp { op, values }, span))
+  }
+  
+  /// disjunction: conjunction (('||' | 'or') conjunction)*
   fn disjunction(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::DoubleVBar], Parser::conjunction)?)
+    self.bool_op(&[TokenKind::DoubleVBar, TokenKind::Keyword(Keyword::Or)], Parser::conjunction)
   }
   
+  /// conjunction: inversion (('&&' | 'and') inversion)*
   fn conjunction(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::DoubleAmper], Parser::inversion)?)
+    self.bool_op(&[TokenKind::DoubleAmper, TokenKind::Keyword(Keyword::And)], Parser::inversion)
   }
   
+  /// inversion: ('!' | 'not') inversion | comparison
   fn inversion(&mut self) -> Result<NodeId, Error> {
-    match self.peek() {
-      Some(Ok(tok)) if tok.kind() == &TokenKind::Exclamation => Ok(self.bin_op(&[TokenKind::Exclamation], Parser::comparison)?),
-      Some(Err(_)) => {
-        let err = self.next().expect("Some").expect_err("Err"); 
-        Err(err)
+    match self.eat_any(&[TokenKind::Exclamation, TokenKind::Keyword(Keyword::Not)])? {
+      Some(op) => {
+        let operand = self.inversion()?;
+        let span = Span::new(op.span().start, self.arena.get(operand).span().end);
+        Ok(self.arena.alloc(NodeKind::Not { operand }, span))
       },
-      Some(_) | None => Ok(self.comparison()?),
+      None => self.comparison(),
     }
   }
   
+  /// comparison: bitwise_or (compare_op bitwise_or)*
   fn comparison(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(
-      &[TokenKind::EqEqual, TokenKind::NotEqual, TokenKind::Less, TokenKind::Greater, TokenKind::LessEqual, TokenKind::GreaterEqual], 
-      Parser::bitwise_or
-    )?)
+    const OPERATORS: &[TokenKind] = &[
+      TokenKind::EqEqual, TokenKind::NotEqual, TokenKind::Less, TokenKind::Greater, TokenKind::LessEqual, TokenKind::GreaterEqual,
+    ];
+    let left = self.bitwise_or()?;
+    let (mut ops, mut comparators) = (Vec::new(), Vec::new());
+    while let Some(op) = self.eat_any(OPERATORS)? {
+      ops.push(op);
+      comparators.push(self.bitwise_or()?);
+    }
+    if ops.is_empty() {
+      return Ok(left);
+    }
+    let span = Span::new(self.arena.get(left).span().start, self.prev_end);
+    Ok(self.arena.alloc(NodeKind::Compare { left, ops, comparators }, span))
   }
   
   fn bitwise_or(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::VBar], Parser::bitwise_xor)?)
+    self.bin_op(&[TokenKind::VBar], Parser::bitwise_xor)
   }
   
   fn bitwise_xor(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::Circumflex], Parser::bitwise_add)?)
+    self.bin_op(&[TokenKind::Circumflex], Parser::bitwise_add)
   }
   
   fn bitwise_add(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::Amper], Parser::shift_expr)?)
+    self.bin_op(&[TokenKind::Amper], Parser::shift_expr)
   }
   
   fn shift_expr(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::LeftShift, TokenKind::RightShift], Parser::sum)?)
+    self.bin_op(&[TokenKind::LeftShift, TokenKind::RightShift], Parser::sum)
   }
   
   fn sum(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::Plus, TokenKind::Minus], Parser::term)?)
+    self.bin_op(&[TokenKind::Plus, TokenKind::Minus], Parser::term)
   }
   
   fn term(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::Star, TokenKind::Slash, TokenKind::DoubleSlash, TokenKind::Percent, TokenKind::At], Parser::factor)?)
+    self.bin_op(&[TokenKind::Star, TokenKind::Slash, TokenKind::DoubleSlash, TokenKind::Percent, TokenKind::At], Parser::factor)
   }
   
   fn factor(&mut self) -> Result<NodeId, Error> {
-    let pos_start = self.pos;
     match self.peek() {
-      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Plus | TokenKind::Minus) => {
+      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Plus | TokenKind::Minus | TokenKind::Tilde) => {
         let op = self.next().expect("Some").expect("Ok");
+        let start = op.span().start;
         let operand = self.factor()?;
+        let end = self.arena.get(operand).span().end;
         Ok(self.arena.alloc(
           NodeKind::UnaryOp { 
             op, 
             operand,
           },
-          Span::new(pos_start, self.pos),
+          Span::new(start, end),
         ))
       },
       Some(Err(_)) => {
@@ -237,18 +842,288 @@ impl<'a> Parser<'a> {
     }
   }
   
+  /// power: primary ['**' factor]（右结合，且右侧允许一元运算，如 2 ** -1）
   fn power(&mut self) -> Result<NodeId, Error> {
-    Ok(self.bin_op(&[TokenKind::DoubleStar], Parser::primary)?)
+    let left = self.primary()?;
+    match self.peek() {
+      Some(Ok(tok)) if tok.kind() == &TokenKind::DoubleStar => {
+        let op = self.next().expect("Some").expect("Ok");
+        let right = self.factor()?;
+        Ok(self.arena.alloc_BinOp(left, op, right))
+      },
+      Some(Err(_)) => Err(self.next().expect("Some").expect_err("Err")),
+      Some(_) | None => Ok(left),
+    }
   }
   
+  /// primary: primary '.' NAME | primary '(' [arguments] ')' | primary '[' slices ']' | atom
   fn primary(&mut self) -> Result<NodeId, Error> {
-    Ok(self.atom()?)
+    let mut value = self.atom()?;
+    let start = self.arena.get(value).span().start;
+    loop {
+      if self.eat(&TokenKind::Dot).is_some() {
+        let (attr, span) = self.name()?;
+        value = self.arena.alloc(NodeKind::Attribute { value, attr }, Span::new(start, span.end));
+      } else if let Some(lpar) = self.eat(&TokenKind::LPar) {
+        let (args, keywords) = self.arguments()?;
+        let rpar = self.closing(&lpar, TokenKind::RPar)?;
+        value = self.arena.alloc(NodeKind::Call { func: value, args, keywords }, Span::new(start, rpar.span().end));
+      } else if let Some(lsqb) = self.eat(&TokenKind::LSqb) {
+        let slice = self.slices()?;
+        let rsqb = self.closing(&lsqb, TokenKind::RSqb)?;
+        value = self.arena.alloc(NodeKind::Subscript { value, slice }, Span::new(start, rsqb.span().end));
+      } else {
+        break;
+      }
+    }
+    Ok(value)
+  }
+  
+  /// slices: slice !',' | ','.slice+ [',']，含逗号时为元组
+  fn slices(&mut self) -> Result<NodeId, Error> {
+    let first = self.slice()?;
+    if !self.check(&TokenKind::Comma) {
+      return Ok(first);
+    }
+    let start = self.arena.get(first).span().start;
+    let mut elts = vec![first];
+    while self.eat(&TokenKind::Comma).is_some() && !self.check(&TokenKind::RSqb) {
+      elts.push(self.slice()?);
+    }
+    Ok(self.arena.alloc(NodeKind::Tuple { elts }, Span::new(start, self.prev_end)))
+  }
+  
+  /// slice: [expression] ':' [expression] [':' [expression]] | expression
+  fn slice(&mut self) -> Result<NodeId, Error> {
+    let start = match self.peek() {
+      Some(Ok(tok)) => tok.span().start,
+      _ => self.prev_end,
+    };
+    let lower = if self.check(&TokenKind::Colon) { None } else { Some(self.expression()?) };
+    if self.eat(&TokenKind::Colon).is_none() {
+      return Ok(lower.expect("expression"));
+    }
+    let upper = self.slice_part()?;
+    let step = match self.eat(&TokenKind::Colon) {
+      Some(_) => self.slice_part()?,
+      None => None,
+    };
+    Ok(self.arena.alloc(NodeKind::Slice { lower, upper, step }, Span::new(start, self.prev_end)))
+  }
+  
+  /// slice 中 ':' 之后可省略的表达式
+  fn slice_part(&mut self) -> Result<Option<NodeId>, Error> {
+    match self.peek() {
+      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Colon | TokenKind::Comma | TokenKind::RSqb) => Ok(None),
+      _ => Ok(Some(self.expression()?)),
+    }
+  }
+  
+  /// arguments: 位置参数在前，NAME '=' expression 形式的关键字参数在后，允许尾随逗号
+  fn arguments(&mut self) -> Result<(Vec<NodeId>, Vec<KeywordArg>), Error> {
+    let mut args = Vec::new();
+    let mut keywords: Vec<KeywordArg> = Vec::new();
+    while !self.check(&TokenKind::RPar) {
+      let keyword = matches!(self.tokens.peek(2), Some(Ok(tok)) if tok.kind() == &TokenKind::Equal)
+        && matches!(self.peek(), Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Name(..) | TokenKind::SoftKeyword(..)));
+      if keyword {
+        let (name, span) = self.name()?;
+        self.next();
+        if keywords.iter().any(|(other, _)| *other == name) {
+          return Err(SyntaxError::new(
+            format!("keyword argument repeated: {}", self.arena.interner.resolve(name)),
+            span,
+          ));
+        }
+        keywords.push((name, self.expression()?));
+      } else {
+        let mut arg = self.expression()?;
+        if self.check(&TokenKind::Keyword(Keyword::For)) {
+          self.check_comprehension_elt(arg)?;
+          let generators = self.for_if_clauses()?;
+          let span = Span::new(self.arena.get(arg).span().start, self.prev_end);
+          arg = self.arena.alloc(NodeKind::GeneratorExp { elt: arg, generators }, span);
+          if !args.is_empty() || !keywords.is_empty() || self.check(&TokenKind::Comma) {
+            return Err(SyntaxError::new("Generator expression must be parenthesized", *self.arena.get(arg).span()));
+          }
+        }
+        if !keywords.is_empty() {
+          return Err(SyntaxError::new("positional argument follows keyword argument", *self.arena.get(arg).span()));
+        }
+        args.push(arg);
+      }
+      if self.eat(&TokenKind::Comma).is_none() {
+        break;
+      }
+    }
+    Ok((args, keywords))
+  }
+  
+  /// 消费与 open 配对的右括号
+  fn closing(&mut self, open: &Token, kind: TokenKind) -> Result<Token, Error> {
+    match self.next() {
+      Some(Ok(tok)) if tok.kind() == &kind => Ok(tok),
+      Some(Ok(tok)) if tok.kind() != &TokenKind::Endmarker => Err(SyntaxError::new("invalid syntax", tok.span())),
+      Some(Err(err)) => Err(err),
+      Some(Ok(_)) | None => Err(SyntaxError::new(
+        format!("'{}' was never closed", self.tokens.source(open.span())),
+        open.span(),
+      )),
+    }
+  }
+  
+  /// '(' ')' 为空元组；'(' star_expressions ')' 中含逗号时为元组，否则为带括号的表达式；
+  /// '(' expression for_if_clauses ')' 为生成器表达式，'(' yield_expr ')' 为 yield 表达式
+  fn paren(&mut self, lpar: Token) -> Result<NodeId, Error> {
+    if let Some(rpar) = self.eat(&TokenKind::RPar) {
+      return Ok(self.arena.alloc(NodeKind::Tuple { elts: Vec::new() }, Span::new(lpar.span().start, rpar.span().end)));
+    }
+    if let Some(keyword) = self.eat(&TokenKind::Keyword(Keyword::Yield)) {
+      let value = self.yield_expr(keyword)?;
+      self.closing(&lpar, TokenKind::RPar)?;
+      return Ok(value);
+    }
+    let first = self.star_expression()?;
+    if self.check(&TokenKind::Keyword(Keyword::For)) {
+      self.check_comprehension_elt(first)?;
+      let generators = self.for_if_clauses()?;
+      let rpar = self.closing(&lpar, TokenKind::RPar)?;
+      let span = Span::new(lpar.span().start, rpar.span().end);
+      return Ok(self.arena.alloc(NodeKind::GeneratorExp { elt: first, generators }, span));
+    }
+    let (mut elts, comma) = self.expression_list(first)?;
+    let rpar = self.closing(&lpar, TokenKind::RPar)?;
+    if !comma {
+      let value = elts.pop().expect("expression");
+      if let NodeKind::Starred { .. } = self.arena.get(value).kind() {
+        return Err(SyntaxError::new("cannot use starred expression here", *self.arena.get(value).span()));
+      }
+      return Ok(value);
+    }
+    Ok(self.arena.alloc(NodeKind::Tuple { elts }, Span::new(lpar.span().start, rpar.span().end)))
+  }
+  
+  /// list: '[' [star_expressions] ']'
+  /// listcomp: '[' expression for_if_clauses ']'
+  fn list(&mut self, lsqb: Token) -> Result<NodeId, Error> {
+    let kind = if self.check(&TokenKind::RSqb) {
+      NodeKind::List { elts: Vec::new() }
+    } else {
+      let first = self.star_expression()?;
+      if self.check(&TokenKind::Keyword(Keyword::For)) {
+        self.check_comprehension_elt(first)?;
+        NodeKind::ListComp { elt: first, generators: self.for_if_clauses()? }
+      } else {
+        NodeKind::List { elts: self.expression_list(first)?.0 }
+      }
+    };
+    let rsqb = self.closing(&lsqb, TokenKind::RSqb)?;
+    Ok(self.arena.alloc(kind, Span::new(lsqb.span().start, rsqb.span().end)))
+  }
+  
+  /// dict: '{' [','.(kvpair | '**' bitwise_or)+ [',']] '}'
+  /// set: '{' ','.star_expression+ [','] '}'
+  /// dictcomp: '{' kvpair for_if_clauses '}'
+  /// setcomp: '{' expression for_if_clauses '}'
+  /// 由第一个元素决定是字典还是集合，空的 {} 为字典
+  fn dict_or_set(&mut self, lbrace: Token) -> Result<NodeId, Error> {
+    let (mut keys, mut values, mut elts) = (Vec::new(), Vec::new(), Vec::new());
+    let mut is_dict = true;
+    let mut first = true;
+    while !self.check(&TokenKind::RBrace) {
+      if is_dict && let Some(star) = self.eat(&TokenKind::DoubleStar) {
+        let value = self.bitwise_or()?;
+        if first && self.check(&TokenKind::Keyword(Keyword::For)) {
+          return Err(SyntaxError::new(
+            "dict unpacking cannot be used in dict comprehension",
+            Span::new(star.span().start, self.arena.get(value).span().end),
+          ));
+        }
+        keys.push(None);
+        values.push(value);
+      } else {
+        let item = if is_dict && !first { self.expression()? } else { self.star_expression()? };
+        if first && (!self.check(&TokenKind::Colon) || matches!(self.arena.get(item).kind(), NodeKind::Starred { .. })) {
+          is_dict = false;
+        }
+        if is_dict {
+          self.expect(&TokenKind::Colon, "':' expected after dictionary key")?;
+          keys.push(Some(item));
+          values.push(self.expression()?);
+        } else {
+          elts.push(item);
+        }
+        if first && self.check(&TokenKind::Keyword(Keyword::For)) {
+          let kind = match (keys.pop(), values.pop(), elts.pop()) {
+            (Some(Some(key)), Some(value), _) => NodeKind::DictComp { key, value, generators: self.for_if_clauses()? },
+            (_, _, Some(elt)) => {
+              self.check_comprehension_elt(elt)?;
+              NodeKind::SetComp { elt, generators: self.for_if_clauses()? }
+            },
+            _ => unreachable!("first item"),
+          };
+          let rbrace = self.closing(&lbrace, TokenKind::RBrace)?;
+          return Ok(self.arena.alloc(kind, Span::new(lbrace.span().start, rbrace.span().end)));
+        }
+      }
+      first = false;
+      if self.eat(&TokenKind::Comma).is_none() {
+        break;
+      }
+    }
+    let rbrace = self.closing(&lbrace, TokenKind::RBrace)?;
+    let span = Span::new(lbrace.span().start, rbrace.span().end);
+    if is_dict {
+      Ok(self.arena.alloc(NodeKind::Dict { keys, values }, span))
+    } else {
+      Ok(self.arena.alloc(NodeKind::Set { elts }, span))
+    }
+  }
+  
+  /// 括号内以逗号分隔的表达式 (first 为已解析的第一个)，允许尾随逗号；返回元素与是否出现过逗号
+  fn expression_list(&mut self, first: NodeId) -> Result<(Vec<NodeId>, bool), Error> {
+    let mut elts = vec![first];
+    let mut comma = false;
+    while self.eat(&TokenKind::Comma).is_some() {
+      comma = true;
+      if self.at_sequence_end() {
+        break;
+      }
+      elts.push(self.star_expression()?);
+    }
+    Ok((elts, comma))
+  }
+  
+  /// for_if_clauses: ('for' star_targets 'in' disjunction ('if' disjunction)*)+
+  fn for_if_clauses(&mut self) -> Result<Vec<Comprehension>, Error> {
+    let mut generators = Vec::new();
+    while self.eat(&TokenKind::Keyword(Keyword::For)).is_some() {
+      let target = self.star_targets()?;
+      self.expect(&TokenKind::Keyword(Keyword::In), "expected 'in'")?;
+      let iter = self.disjunction()?;
+      let mut ifs = Vec::new();
+      while self.eat(&TokenKind::Keyword(Keyword::If)).is_some() {
+        ifs.push(self.disjunction()?);
+      }
+      generators.push(Comprehension { target, iter, ifs });
+    }
+    Ok(generators)
+  }
+  
+  /// 推导式的元素不能是 *iterable
+  fn check_comprehension_elt(&self, elt: NodeId) -> Result<(), Error> {
+    match self.arena.get(elt).kind() {
+      NodeKind::Starred { .. } => Err(SyntaxError::new(
+        "iterable unpacking cannot be used in comprehension",
+        *self.arena.get(elt).span(),
+      )),
+      _ => Ok(()),
+    }
   }
   
   fn atom(&mut self) -> Result<NodeId, Error> {
-    let start = self.pos;
     match self.next() {
-      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Int(..)) => {
+      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Int(..) | TokenKind::Float(..)) => {
         let span = Span::new(tok.span().start, tok.span().end);
         let node = self.arena.alloc(
           NodeKind::Constant { value: tok },
@@ -256,13 +1131,281 @@ impl<'a> Parser<'a> {
         );
         Ok(node)
       },
-      Some(Err(_)) => {
-        let err = self.next().expect("Some").expect_err("Err");
-        Err(err)
+      Some(Ok(tok)) if matches!(
+        tok.kind(), 
+        TokenKind::Keyword(Keyword::True | Keyword::False | Keyword::Null | Keyword::Inf | Keyword::NaN)
+      ) => {
+        let span = tok.span();
+        Ok(self.arena.alloc(NodeKind::Constant { value: tok }, span))
       },
-      Some(_) | None => Err(SyntaxError::new("invalid atom", Span::new(start, self.pos))),
+      Some(Ok(tok)) if matches!(tok.kind(), TokenKind::Name(..) | TokenKind::SoftKeyword(..)) => {
+        let span = tok.span();
+        let id = match tok.kind() {
+          TokenKind::Name(name) | TokenKind::SoftKeyword(name) => self.arena.interner.intern(name),
+          _ => unreachable!(),
+        };
+        Ok(self.arena.alloc(NodeKind::Name { id }, span))
+      },
+      Some(Ok(tok)) if matches!(
+        tok.kind(),
+        TokenKind::String(..) | TokenKind::Bytes(..) | TokenKind::FStringStart | TokenKind::TStringStart
+      ) => {
+        self.strings(tok)
+      },
+      Some(Ok(tok)) if tok.kind() == &TokenKind::LPar => self.paren(tok),
+      Some(Ok(tok)) if tok.kind() == &TokenKind::LSqb => self.list(tok),
+      Some(Ok(tok)) if tok.kind() == &TokenKind::LBrace => self.dict_or_set(tok),
+      Some(Err(err)) => Err(err),
+      Some(Ok(tok)) => Err(SyntaxError::new("invalid atom", tok.span())),
+      None => Err(SyntaxError::new("invalid atom", self.here())),
     }
   }
+  
+  /// 相邻的字符串字面量（含 f-string / t-string）在编译期拼接: "a" f"{b}" => f"a{b}"
+  fn strings(&mut self, first: Token) -> Result<NodeId, Error> {
+    let start = first.span().start;
+    let mut end = first.span().end;
+    let mut pieces = StringPieces::default();
+    let mut tok = first;
+    loop {
+      match tok.kind() {
+        TokenKind::String(s) => {
+          pieces.plain = true;
+          pieces.push_text(s, tok.span());
+        },
+        TokenKind::Bytes(b) => {
+          pieces.bytes.get_or_insert_with(Vec::new).extend_from_slice(b);
+        },
+        TokenKind::FStringStart | TokenKind::TStringStart => {
+          let template = tok.kind() == &TokenKind::TStringStart;
+          if template {
+            pieces.template = true;
+          } else {
+            pieces.formatted = true;
+          }
+          end = self.fstring_body(&mut pieces)?;
+        },
+        _ => unreachable!("not a string token"),
+      }
+      
+      let span = Span::new(start, end.max(tok.span().end));
+      if pieces.bytes.is_some() && (pieces.plain || pieces.formatted || pieces.template) {
+        return Err(SyntaxError::new("cannot mix bytes and nonbytes literals", span));
+      }
+      if pieces.template && (pieces.plain || pieces.formatted) {
+        return Err(SyntaxError::new("cannot mix t-string literals with string or bytes literals", span));
+      }
+      end = span.end;
+      
+      match self.peek() {
+        Some(Ok(next)) if matches!(
+          next.kind(),
+          TokenKind::String(..) | TokenKind::Bytes(..) | TokenKind::FStringStart | TokenKind::TStringStart
+        ) => {
+          tok = self.next().expect("Some").expect("Ok");
+        },
+        _ => break,
+      }
+    }
+    
+    let span = Span::new(start, end);
+    if let Some(bytes) = pieces.bytes {
+      return Ok(self.arena.alloc(
+        NodeKind::Constant { value: Token::new(TokenKind::Bytes(bytes), span) },
+        span,
+      ));
+    }
+    if !pieces.formatted && !pieces.template {
+      let text = pieces.text.map(|(text, _)| text).unwrap_or_default();
+      return Ok(self.arena.alloc(
+        NodeKind::Constant { value: Token::new(TokenKind::String(text), span) },
+        span,
+      ));
+    }
+    let values = pieces.finish(&mut self.arena);
+    let kind = if pieces.template {
+      NodeKind::TemplateStr { values }
+    } else {
+      NodeKind::JoinedStr { values }
+    };
+    Ok(self.arena.alloc(kind, span))
+  }
+  
+  /// f-string / t-string 的内容（FStringStart 之后），返回结束引号的位置
+  fn fstring_body(&mut self, pieces: &mut StringPieces) -> Result<usize, Error> {
+    loop {
+      let tok = match self.next() {
+        Some(Ok(tok)) => tok,
+        Some(Err(err)) => return Err(err),
+        None => return Err(SyntaxError::new("unterminated f-string literal", self.here())),
+      };
+      match tok.kind() {
+        TokenKind::FStringMiddle(s) | TokenKind::TStringMiddle(s) => pieces.push_text(s, tok.span()),
+        TokenKind::LBrace => {
+          let field = self.fstring_field(tok, pieces)?;
+          pieces.push_node(field, &mut self.arena);
+        },
+        TokenKind::FStringEnd | TokenKind::TStringEnd => return Ok(tok.span().end),
+        _ => return Err(SyntaxError::new("f-string: expecting '}'", tok.span())),
+      }
+    }
+  }
+  
+  /// 替换字段: '{' expression ['='] ['!' NAME] [':' format_spec] '}'
+  /// 自说明表达式 `{x=}` 的源码文本作为字面量写入 pieces
+  fn fstring_field(&mut self, lbrace: Token, pieces: &mut StringPieces) -> Result<NodeId, Error> {
+    let is_boundary = |kind: &TokenKind| matches!(
+      kind,
+      TokenKind::RBrace | TokenKind::Colon | TokenKind::Exclamation | TokenKind::Equal
+    );
+    if let Some(Ok(tok)) = self.peek() && is_boundary(tok.kind()) {
+      let span = tok.span();
+      let text = self.tokens.source(span);
+      return Err(SyntaxError::new(
+        format!("f-string: valid expression required before '{}'", text),
+        span,
+      ));
+    }
+    let value = self.expression()?;
+    
+    let terminator = match self.peek() {
+      Some(Ok(tok)) if is_boundary(tok.kind()) => tok.span(),
+      Some(Ok(tok)) => return Err(SyntaxError::new("f-string: expecting '}'", tok.span())),
+      Some(Err(_)) => return Err(self.next().expect("Some").expect_err("Err")),
+      None => return Err(SyntaxError::new("f-string: expecting '}'", self.here())),
+    };
+    let expression = self.tokens.source(Span::new(lbrace.span().end, terminator.start));
+    
+    // 自说明表达式: f"{x = }" => "x = " + repr(x)
+    let mut debug = false;
+    if self.eat(&TokenKind::Equal).is_some() {
+      debug = true;
+      let next = match self.peek() {
+        Some(Ok(tok)) => tok.span().start,
+        _ => self.prev_end,
+      };
+      let text = self.tokens.source(Span::new(lbrace.span().end, next));
+      pieces.push_text(&text, Span::new(lbrace.span().end, next));
+    }
+    
+    let mut conversion = None;
+    if let Some(bang) = self.eat(&TokenKind::Exclamation) {
+      conversion = match self.next() {
+        Some(Ok(tok)) => match tok.kind() {
+          TokenKind::Name(name) if matches!(name.as_str(), "s" | "r" | "a") => name.chars().next(),
+          TokenKind::Name(name) => return Err(SyntaxError::new(
+            format!("f-string: invalid conversion character '{}': expected 's', 'r', or 'a'", name),
+            tok.span(),
+          )),
+          _ => return Err(SyntaxError::new("f-string: missing conversion character", bang.span())),
+        },
+        Some(Err(err)) => return Err(err),
+        None => return Err(SyntaxError::new("f-string: missing conversion character", bang.span())),
+      };
+    }
+    
+    let mut format_spec = None;
+    if let Some(colon) = self.eat(&TokenKind::Colon) {
+      let mut spec = StringPieces::default();
+      let spec_start = colon.span().end;
+      let mut spec_end = spec_start;
+      loop {
+        match self.peek() {
+          Some(Ok(tok)) if matches!(tok.kind(), TokenKind::FStringMiddle(..) | TokenKind::TStringMiddle(..)) => {
+            let tok = self.next().expect("Some").expect("Ok");
+            if let TokenKind::FStringMiddle(s) | TokenKind::TStringMiddle(s) = tok.kind() {
+              spec.push_text(s, tok.span());
+            }
+            spec_end = tok.span().end;
+          },
+          Some(Ok(tok)) if tok.kind() == &TokenKind::LBrace => {
+            let tok = self.next().expect("Some").expect("Ok");
+            let field = self.fstring_field(tok, &mut spec)?;
+            spec_end = self.arena.get(field).span().end;
+            spec.push_node(field, &mut self.arena);
+          },
+          _ => break,
+        }
+      }
+      let values = spec.finish(&mut self.arena);
+      format_spec = Some(self.arena.alloc(NodeKind::JoinedStr { values }, Span::new(spec_start, spec_end)));
+    } else if debug && conversion.is_none() {
+      conversion = Some('r');
+    }
+    
+    let rbrace = match self.next() {
+      Some(Ok(tok)) if tok.kind() == &TokenKind::RBrace => tok,
+      Some(Ok(tok)) => return Err(SyntaxError::new("f-string: expecting '}'", tok.span())),
+      Some(Err(err)) => return Err(err),
+      None => return Err(SyntaxError::new("f-string: expecting '}'", self.here())),
+    };
+    let span = Span::new(lbrace.span().start, rbrace.span().end);
+    let kind = if pieces.template {
+      NodeKind::Interpolation { value, expression, conversion, format_spec }
+    } else {
+      NodeKind::FormattedValue { value, conversion, format_spec }
+    };
+    Ok(self.arena.alloc(kind, span))
+  }
+}
+
+/// augassign: '+=' | '-=' | '*=' | '@=' | '/=' | '%=' | '&=' | '|=' | '^=' | '<<=' | '>>=' | '**=' | '//='
+fn is_augassign(kind: &TokenKind) -> bool {
+  matches!(
+    kind,
+    TokenKind::PlusEqual | TokenKind::MinEqual | TokenKind::StarEqual | TokenKind::AtEqual
+      | TokenKind::SlashEqual | TokenKind::PercentEqual | TokenKind::AmperEqual | TokenKind::VBarEqual
+      | TokenKind::CircumflexEqual | TokenKind::LeftShiftEqual | TokenKind::RightShiftEqual
+      | TokenKind::DoubleStarEqual | TokenKind::DoubleSlashEqual
+  )
+}
+
+/// 拼接字符串字面量时的中间状态
+#[derive(Default)]
+struct StringPieces {
+  /// 待写入的字面文本及其 span
+  text: Option<(String, Span)>,
+  bytes: Option<Vec<u8>>,
+  values: Vec<NodeId>,
+  /// 是否包含普通字符串 / f-string / t-string
+  plain: bool,
+  formatted: bool,
+  template: bool,
+}
+
+impl StringPieces {
+  fn push_text(&mut self, s: &str, span: Span) {
+    match &mut self.text {
+      Some((text, text_span)) => {
+        text.push_str(s);
+        text_span.end = span.end;
+      },
+      None => self.text = Some((s.to_string(), span)),
+    }
+  }
+  
+  fn flush(&mut self, arena: &mut Arena) {
+    if let Some((text, span)) = self.text.take() {
+      if text.is_empty() {
+        return;
+      }
+      let node = arena.alloc(
+        NodeKind::Constant { value: Token::new(TokenKind::String(text), span) },
+        span,
+      );
+      self.values.push(node);
+    }
+  }
+  
+  fn push_node(&mut self, node: NodeId, arena: &mut Arena) {
+    self.flush(arena);
+    self.values.push(node);
+  }
+  
+  fn finish(&mut self, arena: &mut Arena) -> Vec<NodeId> {
+    self.flush(arena);
+    std::mem::take(&mut self.values)
+  }
 }
 
 
@@ -270,6 +1413,46 @@ impl<'a> Parser<'a> {
 mod tests {
   use super::*;
 
+  /// 解析成功的模块：源码、arena 与顶层语句
+  struct Module<'a> {
+    code: &'a str,
+    id: NodeId,
+    arena: Arena,
+    body: Vec<NodeId>,
+  }
+
+  impl<'a> Module<'a> {
+    /// 节点对应的源码
+    fn text(&self, id: NodeId) -> &'a str {
+      let span = self.arena.get(id).span();
+      &self.code[span.start..span.end]
+    }
+
+    /// 表达式语句的值
+    fn value(&self, stmt: NodeId) -> NodeId {
+      match self.arena.get(stmt).kind() {
+        NodeKind::Expr { value } => *value,
+        other => panic!("need expr, got {:?}", other),
+      }
+    }
+  }
+
+  fn parse_module(code: &str) -> Module<'_> {
+    let mut lexer = Lexer::new(code);
+    let mut parser = Parser::new(&mut lexer);
+    let id = parser.parse().unwrap();
+    let NodeKind::Module { body } = parser.arena.get(id).kind() else { panic!("need module") };
+    let body = body.clone();
+    Module { code, id, arena: parser.arena, body }
+  }
+
+  /// 解析失败，错误的消息与位置符合预期
+  fn assert_parse_error(code: &str, message: &str, span: Span) {
+    let mut lexer = Lexer::new(code);
+    let err = Parser::new(&mut lexer).parse().expect_err(code);
+    assert_eq!((err.message(), err.span()), (message, &span), "{}", code);
+  }
+
   #[test]
   fn parse_simple() {
     let code = "12 + 2 - 3";
@@ -309,15 +1492,591 @@ mod tests {
     ));
   }
   
+  #[test]
+  fn parse_operators() {
+    // ** 右结合且右侧允许一元运算；比较与位运算可以从源码到达
+    let code = "2 ** -1 ** 2 << 1 | 3 == ~4";
+    let module = parse_module(code);
+    let arena = &module.arena;
+    // 顶层是 ==
+    match arena.get(module.value(module.body[0])).kind() {
+      NodeKind::Compare { ops, comparators, .. } => {
+        assert_eq!(ops.iter().map(Token::kind).collect::<Vec<_>>(), vec![&TokenKind::EqEqual]);
+        assert_eq!(module.text(comparators[0]), "~4");
+      },
+      _ => panic!("need Compare"),
+    }
+    // 2 ** (-(1 ** 2))
+    let pow = arena.nodes.iter().find(|n| matches!(
+      n.kind(),
+      NodeKind::BinOp { op, .. } if op.kind() == &TokenKind::DoubleStar
+    ) && n.span().start == 0).unwrap();
+    assert_eq!(&code[pow.span().start..pow.span().end], "2 ** -1 ** 2");
+  }
+  
+  #[test]
+  fn parse_string_concat() {
+    let code = r#""ab" 'c' """d""""#;
+    let module = parse_module(code);
+    let node = module.arena.get(0);
+    assert!(matches!(
+      node.kind(),
+      NodeKind::Constant { value } if value.kind() == &TokenKind::String("abcd".into())
+    ));
+    assert_eq!((node.span().start, node.span().end), (0, code.len()));
+
+    assert_parse_error("'a' b'b'", "cannot mix bytes and nonbytes literals", Span::new(0, 8));
+  }
+  
+  #[test]
+  fn parse_names_and_constants() {
+    let module = parse_module("变量 + true - null * NaN");
+    let arena = &module.arena;
+    match arena.get(0).kind() {
+      NodeKind::Name { id } => assert_eq!(arena.interner.resolve(*id), "变量"),
+      other => panic!("need Name, got {:?}", other),
+    }
+    assert!(matches!(
+      arena.get(1).kind(),
+      NodeKind::Constant { value } if value.kind() == &TokenKind::Keyword(Keyword::True)
+    ));
+  }
+  
+  #[test]
+  fn parse_fstring() {
+    let code = r#"f"x={x!r:>{w}}" 'y'"#;
+    let module = parse_module(code);
+    let arena = &module.arena;
+    let expr = module.value(module.body[0]);
+    let values = match arena.get(expr).kind() {
+      NodeKind::JoinedStr { values } => values,
+      other => panic!("need JoinedStr, got {:?}", other),
+    };
+    assert_eq!(module.text(expr), code);
+    assert_eq!(values.len(), 3);
+    assert_eq!(module.text(values[0]), "x=");
+    assert_eq!(module.text(values[2]), "'y'");
+    match arena.get(values[1]).kind() {
+      NodeKind::FormattedValue { value, conversion, format_spec } => {
+        assert_eq!(module.text(*value), "x");
+        assert_eq!(conversion, &Some('r'));
+        let spec = format_spec.expect("has spec");
+        assert_eq!(module.text(spec), ">{w}");
+        assert!(matches!(arena.get(spec).kind(), NodeKind::JoinedStr { values } if values.len() == 2));
+      },
+      other => panic!("need FormattedValue, got {:?}", other),
+    }
+  }
+  
+  #[test]
+  fn parse_fstring_debug_and_template() {
+    // {x = } 展开为字面量 "x = " 加上默认使用 repr 的字段
+    let module = parse_module("f'{x = }'");
+    let arena = &module.arena;
+    let joined = arena.nodes.iter().find_map(|n| match n.kind() {
+      NodeKind::JoinedStr { values } => Some(values.clone()),
+      _ => None,
+    }).unwrap();
+    assert!(matches!(
+      arena.get(joined[0]).kind(),
+      NodeKind::Constant { value } if value.kind() == &TokenKind::String("x = ".into())
+    ));
+    assert!(matches!(
+      arena.get(joined[1]).kind(),
+      NodeKind::FormattedValue { conversion: Some('r'), format_spec: None, .. }
+    ));
+  
+    let module = parse_module("t'hi {name!s}'");
+    assert!(module.arena.nodes.iter().any(|n| matches!(
+      n.kind(),
+      NodeKind::Interpolation { expression, conversion: Some('s'), .. } if expression == "name"
+    )));
+    assert!(module.arena.nodes.iter().any(|n| matches!(n.kind(), NodeKind::TemplateStr { values } if values.len() == 2)));
+  }
+  
+  #[test]
+  fn parse_fstring_errors() {
+    for (code, message, span) in [
+      ("f'{}'", "f-string: valid expression required before '}'", Span::new(3, 4)),
+      ("f'{x!z}'", "f-string: invalid conversion character 'z': expected 's', 'r', or 'a'", Span::new(5, 6)),
+      ("f'{x y}'", "f-string: expecting '}'", Span::new(5, 6)),
+      ("t'a' 'b'", "cannot mix t-string literals with string or bytes literals", Span::new(0, 8)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
   #[test]
   fn parse_error() {
     let code = "1 + ";
     let mut lexer = Lexer::new(code);
     let mut parser = Parser::new(&mut lexer);
     let err = parser.parse().expect_err("no expect");
-    let expected = SyntaxError::new("invalid atom", Span::new(2, 3));
+    // span 为字符偏移：指向 "1 + " 末尾的 Endmarker
+    let expected = SyntaxError::new("invalid atom", Span::new(4, 4));
     assert_eq!(err.kind(), expected.kind());
     assert_eq!(err.message(), "invalid atom");
     assert_eq!(err.span(), expected.span());
   }
+  
+  // 测试：模块由换行与分号分隔的多条语句组成，span 覆盖首尾语句
+  #[test]
+  fn parse_statements() {
+    let module = parse_module("\n# comment\n1 + 2\n\n3; 4;\n  \n5");
+    let texts: Vec<&str> = module.body.iter().map(|id| module.text(*id)).collect();
+    assert_eq!(texts, vec!["1 + 2", "3", "4", "5"]);
+    assert_eq!(module.text(module.id), "1 + 2\n\n3; 4;\n  \n5");
+    
+    for code in ["", "\n\n", "# only a comment\n"] {
+      assert!(parse_module(code).body.is_empty());
+    }
+  }
+  
+  // 测试：同一行内缺少分隔符、或以分号开头时报错
+  #[test]
+  fn parse_statements_error() {
+    for (code, message, span) in [
+      ("1 2", "invalid syntax", Span::new(2, 3)),
+      ("1\n;2", "invalid atom", Span::new(2, 3)),
+      ("1;;2", "invalid atom", Span::new(2, 3)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：链式赋值、解包目标与增量赋值
+  #[test]
+  fn parse_assignment() {
+    let code = "a = b = 1, 2,\nx.y[0] **= 3\n[c, (d, e)] = f";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    
+    let NodeKind::Assign { targets, value } = arena.get(body[0]).kind() else { panic!("assign") };
+    assert_eq!(targets.iter().map(|t| module.text(*t)).collect::<Vec<_>>(), vec!["a", "b"]);
+    assert!(matches!(arena.get(*value).kind(), NodeKind::Tuple { elts } if elts.len() == 2));
+    assert_eq!(module.text(*value), "1, 2,");
+    
+    let NodeKind::AugAssign { target, op, value } = arena.get(body[1]).kind() else { panic!("augassign") };
+    assert_eq!(op.kind(), &TokenKind::DoubleStarEqual);
+    assert_eq!((module.text(*target), module.text(*value)), ("x.y[0]", "3"));
+    let NodeKind::Subscript { value: obj, .. } = arena.get(*target).kind() else { panic!("subscript") };
+    assert!(matches!(arena.get(*obj).kind(), NodeKind::Attribute { attr, .. } if arena.interner.resolve(*attr) == "y"));
+    
+    let NodeKind::Assign { targets, .. } = arena.get(body[2]).kind() else { panic!("assign") };
+    let NodeKind::List { elts } = arena.get(targets[0]).kind() else { panic!("list") };
+    assert!(matches!(arena.get(elts[1]).kind(), NodeKind::Tuple { .. }));
+    assert_eq!(module.text(elts[1]), "(d, e)");
+  }
+  
+  // 测试：非法的赋值目标
+  #[test]
+  fn parse_assignment_error() {
+    for (code, message, span) in [
+      ("1 = a", "cannot assign to literal", Span::new(0, 1)),
+      ("a, b + 1 = c", "cannot assign to expression", Span::new(3, 8)),
+      ("a = null = 1", "cannot assign to null", Span::new(4, 8)),
+      ("a, b += 1", "'tuple' is an illegal expression for augmented assignment", Span::new(0, 4)),
+      ("[a] -= 1", "'list' is an illegal expression for augmented assignment", Span::new(0, 3)),
+      ("a += b = 1", "invalid syntax", Span::new(7, 8)),
+      ("(a = 1", "invalid syntax", Span::new(3, 4)),
+      ("[a, b", "'[' was never closed", Span::new(0, 1)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：if / elif / else，缩进块与单行块
+  #[test]
+  fn parse_if() {
+    let code = "if a:\n  b = 1\n\n  if c: pass\nelif d: e; f\nelse:\n  g\nh";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    assert_eq!(body.len(), 2);
+    assert_eq!(module.text(body[1]), "h");
+    
+    let NodeKind::If { test, body: then, orelse } = arena.get(body[0]).kind() else { panic!("if") };
+    assert_eq!(module.text(*test), "a");
+    assert_eq!(then.len(), 2);
+    assert!(matches!(arena.get(then[1]).kind(), NodeKind::If { body, orelse, .. } if body.len() == 1 && orelse.is_empty()));
+    assert_eq!(module.text(body[0]), "if a:\n  b = 1\n\n  if c: pass\nelif d: e; f\nelse:\n  g");
+    
+    // elif 是 orelse 中唯一的 If
+    assert_eq!(orelse.len(), 1);
+    let NodeKind::If { body: elif, orelse, .. } = arena.get(orelse[0]).kind() else { panic!("elif") };
+    assert_eq!(elif.iter().map(|id| module.text(*id)).collect::<Vec<_>>(), vec!["e", "f"]);
+    assert_eq!(orelse.iter().map(|id| module.text(*id)).collect::<Vec<_>>(), vec!["g"]);
+  }
+  
+  // 测试：缺少冒号、缩进块与多余缩进
+  #[test]
+  fn parse_if_error() {
+    for (code, message, span) in [
+      ("if a\n  b", "expected ':'", Span::new(4, 5)),
+      ("if a:\nb", "expected an indented block after 'if' statement", Span::new(6, 7)),
+      ("if a:\n  b\nelse:", "expected an indented block after 'else' statement", Span::new(10, 14)),
+      ("if a:\n  b\n    c", "unexpected indent", Span::new(14, 14)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：while / for 循环、else 子句与 break / continue
+  #[test]
+  fn parse_loops() {
+    let code = "while a:\n  if b: break\n  continue\nelse: c\nfor x, y.z in d, e:\n  pass";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    
+    let NodeKind::While { test, body: loop_body, orelse } = arena.get(body[0]).kind() else { panic!("while") };
+    assert_eq!(module.text(*test), "a");
+    assert!(matches!(arena.get(loop_body[1]).kind(), NodeKind::Continue));
+    assert_eq!(orelse.iter().map(|id| module.text(*id)).collect::<Vec<_>>(), vec!["c"]);
+    
+    let NodeKind::For { target, iter, body: loop_body, orelse } = arena.get(body[1]).kind() else { panic!("for") };
+    assert!(matches!(arena.get(*target).kind(), NodeKind::Tuple { elts } if elts.len() == 2));
+    assert_eq!((module.text(*target), module.text(*iter)), ("x, y.z", "d, e"));
+    assert!(matches!(arena.get(loop_body[0]).kind(), NodeKind::Pass));
+    assert!(orelse.is_empty());
+    assert_eq!(module.text(body[1]), "for x, y.z in d, e:\n  pass");
+  }
+  
+  #[test]
+  fn parse_loops_error() {
+    for (code, message, span) in [
+      ("for x + 1 in y: pass", "expected 'in'", Span::new(6, 7)),
+      ("for 1 in y: pass", "cannot assign to literal", Span::new(4, 5)),
+      ("while a\n  b", "expected ':'", Span::new(7, 8)),
+      ("for x in y:\nz", "expected an indented block after 'for' statement", Span::new(12, 13)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：函数定义的各类形参、return 与带关键字参数的调用
+  #[test]
+  fn parse_function_def() {
+    let code = "def f(a, b=1, *args, c, d=2, **kw):\n  return a\nf(1, x, c=3,)";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    let names = |symbols: &[Symbol]| symbols.iter().map(|s| arena.interner.resolve(*s)).collect::<Vec<_>>();
+    
+    let NodeKind::FunctionDef { name, args, body: func_body } = arena.get(body[0]).kind() else { panic!("def") };
+    assert_eq!(arena.interner.resolve(*name), "f");
+    assert_eq!(names(&args.args), vec!["a", "b"]);
+    assert_eq!(args.defaults.iter().map(|id| module.text(*id)).collect::<Vec<_>>(), vec!["1"]);
+    assert_eq!(args.vararg.map(|s| arena.interner.resolve(s)), Some("args"));
+    assert_eq!(names(&args.kwonlyargs), vec!["c", "d"]);
+    assert_eq!(args.kw_defaults.iter().map(|d| d.map(|id| module.text(id))).collect::<Vec<_>>(), vec![None, Some("2")]);
+    assert_eq!(args.kwarg.map(|s| arena.interner.resolve(s)), Some("kw"));
+    assert!(matches!(arena.get(func_body[0]).kind(), NodeKind::Return { value: Some(_) }));
+    
+    let NodeKind::Expr { value } = arena.get(body[1]).kind() else { panic!("expr") };
+    let NodeKind::Call { func, args, keywords } = arena.get(*value).kind() else { panic!("call") };
+    assert_eq!((module.text(*func), module.text(*value)), ("f", "f(1, x, c=3,)"));
+    assert_eq!(args.len(), 2);
+    assert_eq!(keywords.iter().map(|(k, v)| (arena.interner.resolve(*k), module.text(*v))).collect::<Vec<_>>(), vec![("c", "3")]);
+  }
+  
+  #[test]
+  fn parse_function_def_error() {
+    for (code, message, span) in [
+      ("def f(a=1, b): pass", "parameter without a default follows parameter with a default", Span::new(11, 12)),
+      ("def f(a, *, a): pass", "duplicate argument 'a' in function definition", Span::new(12, 13)),
+      ("def f(a, *): pass", "named arguments must follow bare *", Span::new(9, 10)),
+      ("def f(**kw, a): pass", "arguments cannot follow var-keyword argument", Span::new(12, 13)),
+      ("def f():\nreturn", "expected an indented block after function definition", Span::new(9, 15)),
+      ("f(a=1, 2)", "positional argument follows keyword argument", Span::new(7, 8)),
+      ("f(a=1, a=2)", "keyword argument repeated: a", Span::new(7, 8)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：两种条件表达式与 lambda；条件表达式右结合，lambda 体可以是条件表达式
+  #[test]
+  fn parse_conditional_and_lambda() {
+    let code = "a if b else c if d else e\nc ? a : d ? b : e\nlambda x, *, y=1: x if y else 0";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    
+    for (stmt, parts) in body[..2].iter().zip([("b", "a", "c if d else e"), ("c", "a", "d ? b : e")]) {
+      let NodeKind::IfExp { test, body, orelse } = arena.get(module.value(*stmt)).kind() else { panic!("ifexp") };
+      assert_eq!((module.text(*test), module.text(*body), module.text(*orelse)), parts);
+      assert!(matches!(arena.get(*orelse).kind(), NodeKind::IfExp { .. }));
+    }
+    
+    let NodeKind::Lambda { args, body: lambda_body } = arena.get(module.value(body[2])).kind() else { panic!("lambda") };
+    assert_eq!((args.args.len(), args.kwonlyargs.len()), (1, 1));
+    assert_eq!(module.text(*lambda_body), "x if y else 0");
+    assert_eq!(module.text(module.value(body[2])), "lambda x, *, y=1: x if y else 0");
+  }
+  
+  #[test]
+  fn parse_conditional_and_lambda_error() {
+    for (code, message, span) in [
+      ("x = a if b", "expected 'else' after 'if' expression", Span::new(10, 10)),
+      ("x = a ? b", "expected ':' after '?' expression", Span::new(9, 9)),
+      ("a if b else c = 1", "cannot assign to conditional expression", Span::new(0, 13)),
+      ("lambda: 1 = 2", "cannot assign to lambda", Span::new(0, 9)),
+      ("f = lambda a, a: a", "duplicate argument 'a' in function definition", Span::new(14, 15)),
+      ("f = lambda a", "expected ':'", Span::new(12, 12)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：下标中的切片，省略的部分为 None，含逗号时为元组
+  #[test]
+  fn parse_slices() {
+    let code = "a[1:2]\na[::-1]\na[:, i:]\na.b(c)[d]";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    let part = |id: Option<NodeId>| id.map(|id| module.text(id));
+    let subscript = |stmt: NodeId| {
+      let NodeKind::Expr { value } = arena.get(stmt).kind() else { panic!("expr") };
+      let NodeKind::Subscript { value, slice } = arena.get(*value).kind() else { panic!("subscript") };
+      (*value, *slice)
+    };
+    let slice = |id: NodeId| match arena.get(id).kind() {
+      NodeKind::Slice { lower, upper, step } => (module.text(id), part(*lower), part(*upper), part(*step)),
+      _ => panic!("slice"),
+    };
+    
+    assert_eq!(slice(subscript(body[0]).1), ("1:2", Some("1"), Some("2"), None));
+    assert_eq!(slice(subscript(body[1]).1), ("::-1", None, None, Some("-1")));
+    let NodeKind::Tuple { elts } = arena.get(subscript(body[2]).1).kind() else { panic!("tuple") };
+    assert_eq!(slice(elts[0]), (":", None, None, None));
+    assert_eq!(slice(elts[1]), ("i:", Some("i"), None, None));
+    
+    let (value, index) = subscript(body[3]);
+    assert!(matches!(arena.get(value).kind(), NodeKind::Call { .. }));
+    assert_eq!((module.text(value), module.text(index)), ("a.b(c)", "d"));
+  }
+  
+  // 测试：字典与集合字面量由第一个元素区分，** 展开的键为 None；星号表达式可出现在元组、列表、集合与赋值目标中
+  #[test]
+  fn parse_displays() {
+    let code = "{}\n{a: 1, **b,}\n{*a, 1}\n(*a, b)\nfirst, *rest = x";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    
+    assert!(matches!(arena.get(module.value(body[0])).kind(), NodeKind::Dict { keys, .. } if keys.is_empty()));
+    let NodeKind::Dict { keys, values } = arena.get(module.value(body[1])).kind() else { panic!("dict") };
+    assert_eq!(keys.iter().map(|key| key.map(|id| module.text(id))).collect::<Vec<_>>(), vec![Some("a"), None]);
+    assert_eq!(values.iter().map(|v| module.text(*v)).collect::<Vec<_>>(), vec!["1", "b"]);
+    let NodeKind::Set { elts } = arena.get(module.value(body[2])).kind() else { panic!("set") };
+    assert!(matches!(arena.get(elts[0]).kind(), NodeKind::Starred { .. }));
+    let NodeKind::Tuple { elts } = arena.get(module.value(body[3])).kind() else { panic!("tuple") };
+    assert_eq!(module.text(elts[0]), "*a");
+    
+    let NodeKind::Assign { targets, .. } = arena.get(body[4]).kind() else { panic!("assign") };
+    let NodeKind::Tuple { elts } = arena.get(targets[0]).kind() else { panic!("tuple") };
+    assert!(matches!(arena.get(elts[1]).kind(), NodeKind::Starred { .. }));
+  }
+  
+  #[test]
+  fn parse_displays_error() {
+    for (code, message, span) in [
+      ("{1: 2, 3}", "':' expected after dictionary key", Span::new(8, 9)),
+      ("x = (*a)", "cannot use starred expression here", Span::new(5, 7)),
+      ("{a: 1} = 2", "cannot assign to dict literal", Span::new(0, 6)),
+      ("{a} = 2", "cannot assign to set display", Span::new(0, 3)),
+      ("x = {1, 2", "'{' was never closed", Span::new(4, 5)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：推导式由元素之后的 for 子句识别，每个子句可带多个 if；生成器表达式可作为调用的唯一参数
+  #[test]
+  fn parse_comprehensions() {
+    let code = "[x for x in a if x if y for y in x]\n{k: v for k, v in d}\n{x for x in a}\n(x for x in a)\nf(x for x in a)";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    
+    let NodeKind::ListComp { elt, generators } = arena.get(module.value(body[0])).kind() else { panic!("listcomp") };
+    assert_eq!(module.text(*elt), "x");
+    assert_eq!(generators.len(), 2);
+    assert_eq!(generators[0].ifs.iter().map(|cond| module.text(*cond)).collect::<Vec<_>>(), vec!["x", "y"]);
+    assert_eq!((module.text(generators[1].target), module.text(generators[1].iter)), ("y", "x"));
+    let NodeKind::DictComp { key, value: v, generators } = arena.get(module.value(body[1])).kind() else { panic!("dictcomp") };
+    assert_eq!((module.text(*key), module.text(*v), module.text(generators[0].target)), ("k", "v", "k, v"));
+    assert!(matches!(arena.get(module.value(body[2])).kind(), NodeKind::SetComp { .. }));
+    assert_eq!(module.text(module.value(body[3])), "(x for x in a)");
+    let NodeKind::Call { args, .. } = arena.get(module.value(body[4])).kind() else { panic!("call") };
+    assert!(matches!(arena.get(args[0]).kind(), NodeKind::GeneratorExp { .. }));
+    assert_eq!(module.text(args[0]), "x for x in a");
+  }
+  
+  #[test]
+  fn parse_comprehensions_error() {
+    for (code, message, span) in [
+      ("f(a, x for x in b)", "Generator expression must be parenthesized", Span::new(5, 17)),
+      ("f(x for x in b, a)", "Generator expression must be parenthesized", Span::new(2, 14)),
+      ("[*a for a in b]", "iterable unpacking cannot be used in comprehension", Span::new(1, 3)),
+      ("{**a for a in b}", "dict unpacking cannot be used in dict comprehension", Span::new(1, 4)),
+      ("[x for x of b]", "expected 'in'", Span::new(9, 11)),
+      ("[x for x in b] = 1", "cannot assign to list comprehension", Span::new(0, 14)),
+      ("{x for x in b} = 1", "cannot assign to set comprehension", Span::new(0, 14)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：&& / and 优先于 || / or，同级的运算符合并为一个 BoolOp；! / not 可嵌套；比较运算可以链式书写
+  #[test]
+  fn parse_bool_ops_and_comparisons() {
+    let code = "a || b or c && not !d\n0 <= x < 10 != y";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    
+    let NodeKind::BoolOp { op, values } = arena.get(module.value(body[0])).kind() else { panic!("or") };
+    assert_eq!(op.kind(), &TokenKind::DoubleVBar);
+    assert_eq!(values.iter().map(|v| module.text(*v)).collect::<Vec<_>>(), vec!["a", "b", "c && not !d"]);
+    let NodeKind::BoolOp { op, values } = arena.get(values[2]).kind() else { panic!("and") };
+    assert_eq!(op.kind(), &TokenKind::DoubleAmper);
+    let NodeKind::Not { operand } = arena.get(values[1]).kind() else { panic!("not") };
+    assert!(matches!(arena.get(*operand).kind(), NodeKind::Not { .. }));
+    assert_eq!(module.text(*operand), "!d");
+    
+    let NodeKind::Compare { left, ops, comparators } = arena.get(module.value(body[1])).kind() else { panic!("compare") };
+    assert_eq!(module.text(*left), "0");
+    assert_eq!(
+      ops.iter().map(Token::kind).collect::<Vec<_>>(),
+      vec![&TokenKind::LessEqual, &TokenKind::Less, &TokenKind::NotEqual],
+    );
+    assert_eq!(comparators.iter().map(|c| module.text(*c)).collect::<Vec<_>>(), vec!["x", "10", "y"]);
+  }
+  
+  #[test]
+  fn parse_bool_ops_and_comparisons_error() {
+    for (code, message, span) in [
+      ("a < b = 1", "cannot assign to comparison", Span::new(0, 5)),
+      ("not a = 1", "cannot assign to expression", Span::new(0, 5)),
+      ("a or b += 1", "'expression' is an illegal expression for augmented assignment", Span::new(0, 6)),
+      ("a < b += 1", "'comparison' is an illegal expression for augmented assignment", Span::new(0, 5)),
+      ("a and", "invalid atom", Span::new(5, 5)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+  
+  // 测试：try 的 except / else / finally 子句与 raise
+  #[test]
+  fn parse_try_and_raise() {
+    let code = "try:\n  raise E(1)\nexcept (A, B) as e:\n  raise\nexcept:\n  pass\nelse: x\nfinally: y\ntry: a\nfinally:\n  b";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+
+    let NodeKind::Try { body: try_body, handlers, orelse, finalbody } = arena.get(body[0]).kind() else { panic!("try") };
+    let NodeKind::Raise { exc: Some(exc) } = arena.get(try_body[0]).kind() else { panic!("raise") };
+    assert_eq!(module.text(*exc), "E(1)");
+    assert_eq!(handlers.len(), 2);
+    let NodeKind::ExceptHandler { ty: Some(ty), name: Some(name), body: handler_body } = arena.get(handlers[0]).kind() else { panic!("except") };
+    assert_eq!((module.text(*ty), arena.interner.resolve(*name)), ("(A, B)", "e"));
+    assert!(matches!(arena.get(handler_body[0]).kind(), NodeKind::Raise { exc: None }));
+    assert_eq!(module.text(handlers[0]), "except (A, B) as e:\n  raise");
+    assert!(matches!(arena.get(handlers[1]).kind(), NodeKind::ExceptHandler { ty: None, name: None, .. }));
+    assert_eq!((module.text(orelse[0]), module.text(finalbody[0])), ("x", "y"));
+    assert_eq!(module.text(body[0]), &code[..code.find("\ntry").unwrap()]);
+
+    let NodeKind::Try { handlers, orelse, finalbody, .. } = arena.get(body[1]).kind() else { panic!("try") };
+    assert!(handlers.is_empty() && orelse.is_empty());
+    assert_eq!(module.text(finalbody[0]), "b");
+  }
+
+  #[test]
+  fn parse_try_and_raise_error() {
+    for (code, message, span) in [
+      ("try: a\nx = 1", "expected 'except' or 'finally' block", Span::new(7, 8)),
+      ("try: a\nelse: b", "expected 'except' or 'finally' block", Span::new(7, 11)),
+      ("try: a\nexcept: b\nexcept E: c", "default 'except:' must be last", Span::new(7, 16)),
+      ("try: a\nexcept E as e.x: b", "expected ':'", Span::new(20, 21)),
+      ("try: a\nexcept E\n  b", "expected ':'", Span::new(15, 16)),
+      ("try:\nx", "expected an indented block after 'try' statement", Span::new(5, 6)),
+      ("raise E from F", "invalid syntax", Span::new(8, 12)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+
+  #[test]
+  fn parse_class_def() {
+    let code = "class A:\n  x = 1\n  def f(self): pass\nclass B(A, mixins[0],): pass\nclass C(): pass";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+
+    let NodeKind::ClassDef { name, bases, body: class_body } = arena.get(body[0]).kind() else { panic!("class") };
+    assert_eq!(arena.interner.resolve(*name), "A");
+    assert!(bases.is_empty());
+    assert!(matches!(arena.get(class_body[1]).kind(), NodeKind::FunctionDef { .. }));
+    assert_eq!(module.text(body[0]), "class A:\n  x = 1\n  def f(self): pass");
+
+    let NodeKind::ClassDef { bases, .. } = arena.get(body[1]).kind() else { panic!("class") };
+    assert_eq!(bases.iter().map(|base| module.text(*base)).collect::<Vec<_>>(), vec!["A", "mixins[0]"]);
+    let NodeKind::ClassDef { bases, .. } = arena.get(body[2]).kind() else { panic!("class") };
+    assert!(bases.is_empty());
+
+    for (code, message, span) in [
+      ("class A(metaclass=M): pass", "keyword arguments are not supported in class definition", Span::new(18, 19)),
+      ("class A(B: pass", "invalid syntax", Span::new(9, 10)),
+      ("class (A): pass", "invalid syntax", Span::new(6, 7)),
+      ("class A:\nx", "expected an indented block after class definition", Span::new(9, 10)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+
+  #[test]
+  fn parse_yield() {
+    let code = "def g():\n  yield\n  x = yield 1, 2\n  y = (yield from f())\n  z += yield";
+    let module = parse_module(code);
+    let (arena, body) = (&module.arena, &module.body);
+    let NodeKind::FunctionDef { body, .. } = arena.get(body[0]).kind() else { panic!("def") };
+
+    let NodeKind::Expr { value } = arena.get(body[0]).kind() else { panic!("expr") };
+    assert!(matches!(arena.get(*value).kind(), NodeKind::Yield { value: None }));
+    let NodeKind::Assign { value, .. } = arena.get(body[1]).kind() else { panic!("assign") };
+    let NodeKind::Yield { value: Some(tuple) } = arena.get(*value).kind() else { panic!("yield") };
+    assert_eq!((module.text(*value), module.text(*tuple)), ("yield 1, 2", "1, 2"));
+    let NodeKind::Assign { value, .. } = arena.get(body[2]).kind() else { panic!("assign") };
+    let NodeKind::YieldFrom { value: call } = arena.get(*value).kind() else { panic!("yield from") };
+    assert_eq!((module.text(*value), module.text(*call)), ("yield from f()", "f()"));
+    let NodeKind::AugAssign { value, .. } = arena.get(body[3]).kind() else { panic!("augassign") };
+    assert!(matches!(arena.get(*value).kind(), NodeKind::Yield { value: None }));
+
+    for (code, message, span) in [
+      ("yield = 1", "cannot assign to yield expression", Span::new(0, 5)),
+      ("f(yield)", "invalid atom", Span::new(2, 7)),
+      ("(yield from)", "invalid atom", Span::new(11, 12)),
+    ] {
+      assert_parse_error(code, message, span);
+    }
+  }
+
+  // 测试：SourceMap 中第二个文件的 span 使用全局偏移，错误定位到文件行列
+  #[test]
+  fn parse_with_source_map() {
+    let mut map = crate::SourceMap::new();
+    map.add_file("a.cat", "1 + 2");
+    let file = map.add_file("b.cat", "f'{x!z}'");
+    
+    let mut lexer = Lexer::from_file(file.clone());
+    let mut parser = Parser::new(&mut lexer);
+    let err = parser.parse().expect_err("invalid conversion");
+    assert_eq!(err.span(), &Span::new(11, 12));
+    assert_eq!(err.to_string(), "SyntaxError: f-string: invalid conversion character 'z': expected 's', 'r', or 'a' at b.cat:1:6");
+    
+    let mut lexer = Lexer::from_file(file);
+    let err = lexer.stream().find_map(|tok| tok.err());
+    assert!(err.is_none());
+    
+    let file = map.add_file("c.cat", "'é' + \\\n  $");
+    let mut lexer = Lexer::from_file(file.clone());
+    let mut parser = Parser::new(&mut lexer);
+    let err = parser.parse().expect_err("invalid character");
+    assert_eq!(map.file(err.span().start).map(|f| f.name()), Some("c.cat"));
+    let location = err.location().expect("located");
+    assert_eq!((location.start.line, location.start.column), (2, 3));
+  }
 }
\ No newline at end of file
@@ -5,9 +5,11 @@ use crate::Span;
 pub enum TokenKind {
   Endmarker,
   Name(String),
+  Keyword(Keyword),
   Int(i64),
   Float(f64),
   String(String),
+  Bytes(Vec<u8>),
   Newline,
   Indent(usize),
   Dedent(usize),
@@ -35,8 +37,8 @@ pub enum TokenKind {
   NotEqual,
   LessEqual,
   GreaterEqual,
-  DoubleVBar,  // &&
-  DoubleAmper,  // ||
+  DoubleVBar,  // ||
+  DoubleAmper,  // &&
   Tilde,
   Circumflex,  // ^
   LeftShift,
@@ -61,15 +63,16 @@ pub enum TokenKind {
   Ellipsis,
   ColonEqual,
   Exclamation,
+  Question,  // ?
   OP,
   TypeIgnore,
   TypeComment,
-  SoftKeyword,
+  SoftKeyword(String),
   FStringStart,
-  FStringMiddle,
+  FStringMiddle(String),
   FStringEnd,
   TStringStart,
-  TStringMiddle,
+  TStringMiddle(String),
   TStringEnd,
   Comment,
   Nl,
@@ -78,15 +81,167 @@ pub enum TokenKind {
   NTokens,
 }
 
+/// 硬关键字：任何位置都不能作为标识符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Keyword {
+  True,
+  False,
+  Null,
+  Inf,
+  NaN,
+  And,
+  Or,
+  Not,
+  Is,
+  In,
+  If,
+  Elif,
+  Else,
+  While,
+  For,
+  Break,
+  Continue,
+  Pass,
+  Def,
+  Return,
+  Lambda,
+  Class,
+  Try,
+  Except,
+  Finally,
+  Raise,
+  Global,
+  Nonlocal,
+  Del,
+  Yield,
+  From,
+  Import,
+  As,
+  With,
+  Assert,
+  Async,
+  Await,
+}
+
+/// 关键字表
+const KEYWORDS: &[(&str, Keyword)] = &[
+  ("true", Keyword::True),
+  ("false", Keyword::False),
+  ("null", Keyword::Null),
+  ("Inf", Keyword::Inf),
+  ("NaN", Keyword::NaN),
+  ("and", Keyword::And),
+  ("or", Keyword::Or),
+  ("not", Keyword::Not),
+  ("is", Keyword::Is),
+  ("in", Keyword::In),
+  ("if", Keyword::If),
+  ("elif", Keyword::Elif),
+  ("else", Keyword::Else),
+  ("while", Keyword::While),
+  ("for", Keyword::For),
+  ("break", Keyword::Break),
+  ("continue", Keyword::Continue),
+  ("pass", Keyword::Pass),
+  ("def", Keyword::Def),
+  ("return", Keyword::Return),
+  ("lambda", Keyword::Lambda),
+  ("class", Keyword::Class),
+  ("try", Keyword::Try),
+  ("except", Keyword::Except),
+  ("finally", Keyword::Finally),
+  ("raise", Keyword::Raise),
+  ("global", Keyword::Global),
+  ("nonlocal", Keyword::Nonlocal),
+  ("del", Keyword::Del),
+  ("yield", Keyword::Yield),
+  ("from", Keyword::From),
+  ("import", Keyword::Import),
+  ("as", Keyword::As),
+  ("with", Keyword::With),
+  ("assert", Keyword::Assert),
+  ("async", Keyword::Async),
+  ("await", Keyword::Await),
+];
+
+/// 软关键字：仅在特定语法位置有特殊含义，其余位置按普通标识符处理
+pub const SOFT_KEYWORDS: &[&str] = &["match", "case", "type", "_"];
+
+impl Keyword {
+  /// 查找关键字，不是关键字时返回 None
+  pub fn lookup(name: &str) -> Option<Keyword> {
+    KEYWORDS.iter().find(|(text, _)| *text == name).map(|(_, kw)| *kw)
+  }
+  
+  pub fn as_str(&self) -> &'static str {
+    KEYWORDS.iter().find(|(_, kw)| kw == self).map(|(text, _)| *text).expect("every keyword is in KEYWORDS")
+  }
+}
+
+impl TokenKind {
+  /// 注释与非逻辑换行 (Nl) 不参与语法分析
+  pub fn is_trivia(&self) -> bool {
+    matches!(self, TokenKind::Comment | TokenKind::Nl)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+  /// 空格、制表符与换页符
+  Whitespace,
+  /// `#` 注释（不含行尾换行）
+  Comment,
+  /// 不结束逻辑行的换行（空行、括号内换行）
+  Nl,
+  /// `\` 续行（含其后的换行）
+  Continuation,
+}
+
+/// 附着在 token 上的无语法意义的源码片段
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia {
+  kind: TriviaKind,
+  span: Span,
+  text: String,
+}
+
+impl Trivia {
+  pub fn new(kind: TriviaKind, span: Span, text: String) -> Self {
+    Self { kind, span, text }
+  }
+  
+  pub fn kind(&self) -> TriviaKind {
+    self.kind
+  }
+  
+  pub fn span(&self) -> Span {
+    self.span
+  }
+  
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+}
+
 #[derive(Debug, Clone)]
 pub struct Token {
   kind: TokenKind,
   span: Span,
+  /// 前导 trivia（仅无损模式下填充）
+  leading: Vec<Trivia>,
+  /// 同一行内的尾随 trivia（仅无损模式下填充）
+  trailing: Vec<Trivia>,
 }
 
 impl Token {
   pub fn new(kind: TokenKind, span: Span) -> Self {
-    Self { kind, span }
+    Self { kind, span, leading: Vec::new(), trailing: Vec::new() }
+  }
+  
+  pub fn with_trivia(mut self, leading: Vec<Trivia>, trailing: Vec<Trivia>) -> Self {
+    self.leading = leading;
+    self.trailing = trailing;
+    self
   }
   
   pub fn kind(&self) -> &TokenKind {
@@ -96,13 +251,27 @@ impl Token {
   pub fn span(&self) -> Span {
     self.span
   }
+  
+  pub fn leading(&self) -> &[Trivia] {
+    &self.leading
+  }
+  
+  pub fn trailing(&self) -> &[Trivia] {
+    &self.trailing
+  }
+  
+  /// 将 token 及其 trivia 的 span 整体平移 n 个字符
+  pub(crate) fn offset(mut self, n: usize) -> Self {
+    self.span = self.span.offset(n);
+    for trivia in self.leading.iter_mut().chain(self.trailing.iter_mut()) {
+      trivia.span = trivia.span.offset(n);
+    }
+    self
+  }
 }
 
 impl Token {
   pub fn eof(pos: usize) -> Self {
-    Self {
-      kind: TokenKind::Endmarker,
-      span: Span::new(pos, pos),
-    }
+    Self::new(TokenKind::Endmarker, Span::new(pos, pos))
   }
 }
\ No newline at end of file
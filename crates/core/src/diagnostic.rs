@@ -0,0 +1,314 @@
+use super::{Error, ErrorKind, SourceFile, SourceMap, Span};
+use std::fmt;
+use std::rc::Rc;
+use unicode_width::UnicodeWidthChar;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+  Error,
+  Warning,
+  Note,
+  Help,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let name = match self {
+      Severity::Error => "error",
+      Severity::Warning => "warning",
+      Severity::Note => "note",
+      Severity::Help => "help",
+    };
+    write!(f, "{}", name)
+  }
+}
+
+/// 诊断中标注的一段源码；主标注用 `^` 下划线，次标注用 `-`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+  pub span: Span,
+  pub message: String,
+  pub primary: bool,
+}
+
+/// 一条完整的诊断信息：严重程度、错误码、消息、标注的源码片段以及附加说明
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub code: Option<String>,
+  pub message: String,
+  pub labels: Vec<Label>,
+  pub notes: Vec<String>,
+  pub help: Vec<String>,
+}
+
+impl Diagnostic {
+  pub fn new<M: Into<String>>(severity: Severity, message: M) -> Self {
+    Self {
+      severity,
+      code: None,
+      message: message.into(),
+      labels: Vec::new(),
+      notes: Vec::new(),
+      help: Vec::new(),
+    }
+  }
+
+  pub fn error<M: Into<String>>(message: M) -> Self {
+    Self::new(Severity::Error, message)
+  }
+
+  pub fn warning<M: Into<String>>(message: M) -> Self {
+    Self::new(Severity::Warning, message)
+  }
+
+  pub fn with_code<C: Into<String>>(mut self, code: C) -> Self {
+    self.code = Some(code.into());
+    self
+  }
+
+  pub fn with_primary<M: Into<String>>(mut self, span: Span, message: M) -> Self {
+    self.labels.push(Label { span, message: message.into(), primary: true });
+    self
+  }
+
+  pub fn with_label<M: Into<String>>(mut self, span: Span, message: M) -> Self {
+    self.labels.push(Label { span, message: message.into(), primary: false });
+    self
+  }
+
+  pub fn with_note<M: Into<String>>(mut self, note: M) -> Self {
+    self.notes.push(note.into());
+    self
+  }
+
+  pub fn with_help<M: Into<String>>(mut self, help: M) -> Self {
+    self.help.push(help.into());
+    self
+  }
+
+  /// 主标注（没有时取第一个标注）
+  pub fn primary_label(&self) -> Option<&Label> {
+    self.labels.iter().find(|label| label.primary).or(self.labels.first())
+  }
+
+  /// 渲染为类似 rustc 的文本：标题、`-->` 位置、带行号的源码行与下划线、note/help
+  pub fn render(&self, map: &SourceMap, color: bool) -> String {
+    Renderer { map, color }.render(self)
+  }
+}
+
+impl From<&Error> for Diagnostic {
+  fn from(err: &Error) -> Self {
+    let diagnostic = Diagnostic::error(err.message())
+      .with_code(err.kind().name())
+      .with_primary(*err.span(), "");
+    match err.kind() {
+      ErrorKind::Tab => diagnostic.with_help("indent with either tabs or spaces, not both"),
+      _ => diagnostic,
+    }
+  }
+}
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const BLUE: &str = "\x1b[1;34m";
+
+struct Renderer<'a> {
+  map: &'a SourceMap,
+  color: bool,
+}
+
+impl Renderer<'_> {
+  fn paint(&self, style: &str, text: &str) -> String {
+    if self.color && !text.is_empty() {
+      format!("{}{}{}", style, text, RESET)
+    } else {
+      text.to_string()
+    }
+  }
+
+  fn severity_style(severity: Severity) -> &'static str {
+    match severity {
+      Severity::Error => RED,
+      Severity::Warning => YELLOW,
+      Severity::Note => GREEN,
+      Severity::Help => BLUE,
+    }
+  }
+
+  fn render(&self, diag: &Diagnostic) -> String {
+    let style = Self::severity_style(diag.severity);
+    let mut out = match &diag.code {
+      Some(code) => self.paint(style, &format!("{}[{}]", diag.severity, code)),
+      None => self.paint(style, &diag.severity.to_string()),
+    };
+    out.push_str(&self.paint(BOLD, &format!(": {}", diag.message)));
+    out.push('\n');
+
+    // 按文件分组，主标注所在文件优先
+    let mut groups: Vec<(Rc<SourceFile>, Vec<&Label>)> = Vec::new();
+    let mut labels: Vec<&Label> = diag.labels.iter().collect();
+    labels.sort_by_key(|label| !label.primary);
+    for label in labels {
+      let Some(file) = self.map.file(label.span.start) else { continue };
+      match groups.iter_mut().find(|(f, _)| Rc::ptr_eq(f, file)) {
+        Some((_, group)) => group.push(label),
+        None => groups.push((file.clone(), vec![label])),
+      }
+    }
+
+    let width = groups.iter()
+      .flat_map(|(file, labels)| labels.iter().map(|label| file.location(label.span.end).line))
+      .max()
+      .map_or(0, |line| line.to_string().len());
+    let gutter = " ".repeat(width);
+    let bar = self.paint(BLUE, "|");
+
+    for (i, (file, labels)) in groups.iter().enumerate() {
+      let arrow = if i == 0 { "-->" } else { ":::" };
+      let location = file.span_location(labels[0].span);
+      out.push_str(&format!("{}{} {}\n", gutter, self.paint(BLUE, arrow), location));
+      out.push_str(&format!("{} {}\n", gutter, bar));
+      self.render_lines(&mut out, file, labels, width);
+    }
+
+    if !groups.is_empty() && (!diag.notes.is_empty() || !diag.help.is_empty()) {
+      out.push_str(&format!("{} {}\n", gutter, bar));
+    }
+    for (kind, texts) in [("note", &diag.notes), ("help", &diag.help)] {
+      for text in texts {
+        out.push_str(&format!("{} {} {}: {}\n", gutter, self.paint(BLUE, "="), self.paint(BOLD, kind), text));
+      }
+    }
+    out
+  }
+
+  /// 输出被标注的源码行，每个标注在对应行下方画一行下划线
+  fn render_lines(&self, out: &mut String, file: &SourceFile, labels: &[&Label], width: usize) {
+    // (行号, 起始列, 结束列, 标注, 是否为标注的最后一行)，列为按字符计、从 1 开始
+    let mut marks = Vec::new();
+    for label in labels {
+      let start = file.location(label.span.start);
+      let mut end = file.location(label.span.end);
+      // 以换行结尾的 span（如 Newline token）不延伸到下一行
+      if end.line > start.line && end.column == 1 {
+        end.line -= 1;
+        end.column = file.line(end.line).map_or(0, |text| text.chars().count()) + 1;
+      }
+      for line in start.line..=end.line {
+        let text_len = file.line(line).map_or(0, |text| text.chars().count());
+        let from = if line == start.line { start.column } else { 1 };
+        let to = if line == end.line { end.column } else { text_len + 1 };
+        marks.push((line, from, to, *label, line == end.line));
+      }
+    }
+    let mut lines: Vec<usize> = marks.iter().map(|mark| mark.0).collect();
+    lines.sort();
+    lines.dedup();
+
+    let bar = self.paint(BLUE, "|");
+    let mut previous = None;
+    for line in lines {
+      if previous.is_some_and(|prev| line > prev + 1) {
+        out.push_str(&format!("{}\n", self.paint(BLUE, "...")));
+      }
+      previous = Some(line);
+      let text = file.line(line).unwrap_or("");
+      let number = self.paint(BLUE, &format!("{:>width$}", line, width = width));
+      out.push_str(format!("{} {} {}", number, bar, expand_tabs(text)).trim_end());
+      out.push('\n');
+
+      let mut on_line: Vec<_> = marks.iter().filter(|mark| mark.0 == line).collect();
+      on_line.sort_by_key(|mark| (mark.1, !mark.3.primary));
+      for (_, from, to, label, last) in on_line {
+        let chars: Vec<char> = text.chars().collect();
+        let offset = display_width(&chars[..(from - 1).min(chars.len())]);
+        let underline_width = display_width(&chars[(from - 1).min(chars.len())..(to - 1).min(chars.len())]).max(1);
+        let (mark, style) = if label.primary {
+          ('^', Self::severity_style(Severity::Error))
+        } else {
+          ('-', BLUE)
+        };
+        let mut underline = self.paint(style, &mark.to_string().repeat(underline_width));
+        if *last && !label.message.is_empty() {
+          underline.push(' ');
+          underline.push_str(&self.paint(style, &label.message));
+        }
+        out.push_str(&format!("{} {} {}{}\n", " ".repeat(width), bar, " ".repeat(offset), underline));
+      }
+    }
+  }
+}
+
+/// 制表符按 4 个空格显示
+fn expand_tabs(text: &str) -> String {
+  text.replace('\t', "    ")
+}
+
+/// 终端显示宽度：制表符计 4 列，全角字符计 2 列
+fn display_width(chars: &[char]) -> usize {
+  chars.iter().map(|&c| if c == '\t' { 4 } else { c.width().unwrap_or(0) }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{SyntaxError, TabError};
+
+  #[test]
+  fn render_error_with_snippet() {
+    let mut map = SourceMap::new();
+    map.add_file("main.cat", "x = 1\ny = (2 +\n  $)\n");
+    let err = SyntaxError::new("invalid character '$' (U+0024)", Span::new(17, 18));
+    let text = Diagnostic::from(&err).render(&map, false);
+    assert_eq!(text, "\
+error[SyntaxError]: invalid character '$' (U+0024)
+ --> main.cat:3:3
+  |
+3 |   $)
+  |   ^
+");
+  }
+
+  #[test]
+  fn render_labels_notes_and_help() {
+    let mut map = SourceMap::new();
+    map.add_file("a.cat", "名字 = 1\n\n\n\nprint(名字 + 'x')");
+    let diag = Diagnostic::error("unsupported operand type(s) for +: 'int' and 'str'")
+      .with_code("TypeError")
+      .with_primary(Span::new(16, 24), "int + str")
+      .with_label(Span::new(0, 2), "defined as int here")
+      .with_note("both operands must have compatible types")
+      .with_help("convert with str(...)");
+    assert_eq!(diag.render(&map, false), "\
+error[TypeError]: unsupported operand type(s) for +: 'int' and 'str'
+ --> a.cat:5:7
+  |
+1 | 名字 = 1
+  | ---- defined as int here
+...
+5 | print(名字 + 'x')
+  |       ^^^^^^^^^^ int + str
+  |
+  = note: both operands must have compatible types
+  = help: convert with str(...)
+");
+  }
+
+  #[test]
+  fn render_without_source_and_with_color() {
+    let map = SourceMap::new();
+    let err = TabError::new("inconsistent use of tabs and spaces in indentation", Span::new(0, 1));
+    assert_eq!(Diagnostic::from(&err).render(&map, false), "\
+error[TabError]: inconsistent use of tabs and spaces in indentation
+ = help: indent with either tabs or spaces, not both
+");
+
+    let text = Diagnostic::warning("unused").render(&map, true);
+    assert_eq!(text, "\x1b[1;33mwarning\x1b[0m\x1b[1m: unused\x1b[0m\n");
+  }
+}
@@ -1,4 +1,5 @@
 use super::Span;
+use super::{SourceFile, SpanLocation};
 use std::fmt;
 
 
@@ -10,11 +11,24 @@ pub enum ErrorKind {
   Tab,
 }
 
+impl ErrorKind {
+  /// Return the exception-style name, e.g. `SyntaxError`.
+  pub fn name(&self) -> &'static str {
+    match self {
+      ErrorKind::Syntax => "SyntaxError",
+      ErrorKind::Indentation => "IndentationError",
+      ErrorKind::Runtime => "RuntimeError",
+      ErrorKind::Tab => "TabError",
+    }
+  }
+}
+
 #[derive(Debug)]
 pub struct Error {
   kind: ErrorKind,
   message: String,
   span: Span,
+  location: Option<Box<SpanLocation>>,
 }
 
 impl Error {
@@ -23,6 +37,7 @@ impl Error {
       kind,
       message: message.into(),
       span,
+      location: None,
     }
   }
 
@@ -40,17 +55,33 @@ impl Error {
   pub fn span(&self) -> &Span {
     &self.span
   }
+  
+  /// Return the file, line and column of the span, if resolved.
+  pub fn location(&self) -> Option<&SpanLocation> {
+    self.location.as_deref()
+  }
+  
+  /// Resolve the span against `file` (no-op if the span lies outside it).
+  pub fn with_location(mut self, file: &SourceFile) -> Self {
+    if file.contains(self.span.start) {
+      self.location = Some(Box::new(file.span_location(self.span)));
+    }
+    self
+  }
+  
+  pub(crate) fn offset(mut self, n: usize) -> Self {
+    self.span = self.span.offset(n);
+    self
+  }
 }
 
 impl fmt::Display for Error {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    let kind_name = match self.kind {
-      ErrorKind::Syntax => "SyntaxError",
-      ErrorKind::Indentation => "IndentationError",
-      ErrorKind::Runtime => "RuntimeError",
-      ErrorKind::Tab => "TabError",
-    };
-    write!(f, "{}: {} at {}..{}", kind_name, self.message, self.span.start, self.span.end)
+    let kind_name = self.kind.name();
+    match &self.location {
+      Some(location) => write!(f, "{}: {} at {}", kind_name, self.message, location),
+      None => write!(f, "{}: {} at {}..{}", kind_name, self.message, self.span.start, self.span.end),
+    }
   }
 }
 
@@ -63,24 +94,28 @@ pub struct RuntimeError;
 pub struct TabError;
 
 impl SyntaxError {
+  #[allow(clippy::new_ret_no_self)]
   pub fn new<M: Into<String>>(message: M, span: Span) -> Error {
     Error::new(ErrorKind::Syntax, message, span)
   }
 }
 
 impl TabError {
+  #[allow(clippy::new_ret_no_self)]
   pub fn new<M: Into<String>>(message: M, span: Span) -> Error {
     Error::new(ErrorKind::Tab, message, span)
   }
 }
 
 impl IndentationError {
+  #[allow(clippy::new_ret_no_self)]
   pub fn new<M: Into<String>>(message: M, span: Span) -> Error {
     Error::new(ErrorKind::Indentation, message, span)
   }
 }
 
 impl RuntimeError {
+  #[allow(clippy::new_ret_no_self)]
   pub fn new<M: Into<String>>(message: M, span: Span) -> Error {
     Error::new(ErrorKind::Runtime, message, span)
   }
@@ -94,7 +129,7 @@ mod tests {
   #[test]
   fn create_and_inspect_errors() {
     let s = Span::new(1, 4);
-    let se = SyntaxError::new("unexpected token", s.clone());
+    let se = SyntaxError::new("unexpected token", s);
     assert_eq!(se.message(), "unexpected token");
     assert_eq!(se.span(), &s);
     assert_eq!(se.kind(), ErrorKind::Syntax);
@@ -103,13 +138,23 @@ mod tests {
   #[test]
   fn other_error_kinds() {
     let s = Span::new(0, 0);
-    let ie = IndentationError::new("bad indent", s.clone());
+    let ie = IndentationError::new("bad indent", s);
     assert_eq!(ie.kind(), ErrorKind::Indentation);
   
-    let re = RuntimeError::new("divide by zero", s.clone());
+    let re = RuntimeError::new("divide by zero", s);
     assert_eq!(re.kind(), ErrorKind::Runtime);
   
-    let te = TabError::new("tab found", s.clone());
+    let te = TabError::new("tab found", s);
     assert_eq!(te.kind(), ErrorKind::Tab);
   }
+  
+  #[test]
+  fn display_with_location() {
+    let file = SourceFile::new("main.cat", "x = 1\ny = $");
+    let err = SyntaxError::new("invalid syntax", Span::new(10, 11));
+    assert_eq!(err.to_string(), "SyntaxError: invalid syntax at 10..11");
+    let err = err.with_location(&file);
+    assert_eq!(err.location().map(|l| (l.start.line, l.start.column)), Some((2, 5)));
+    assert_eq!(err.to_string(), "SyntaxError: invalid syntax at main.cat:2:5");
+  }
 }
\ No newline at end of file
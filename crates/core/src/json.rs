@@ -0,0 +1,134 @@
+use super::{Diagnostic, Error, Label, SourceMap, Span};
+
+/// 机器可读的 JSON 诊断输出；字段顺序固定，便于 CI 比对
+///
+/// ```text
+/// {"kind":"SyntaxError","severity":"error","message":"...","file":"main.cat",
+///  "range":{"start":{"offset":4,"byte":4,"line":1,"column":5,"utf16_column":5},"end":{...}},
+///  "labels":[{"primary":true,"message":"","file":"main.cat","range":{...}}],
+///  "notes":[],"help":[]}
+/// ```
+///
+/// offset 为 SourceMap 中的全局字符偏移，byte 为文件内的 UTF-8 字节偏移，行列从 1 开始；
+/// 无法定位到文件时 file 与 range 为 null
+pub trait ToJson {
+  fn to_json(&self, map: &SourceMap) -> String;
+}
+
+impl ToJson for Diagnostic {
+  fn to_json(&self, map: &SourceMap) -> String {
+    let kind = self.code.clone().unwrap_or_else(|| self.severity.to_string());
+    let (file, range) = match self.primary_label() {
+      Some(label) => (file_json(map, label.span), range_json(map, label.span)),
+      None => ("null".to_string(), "null".to_string()),
+    };
+    let labels: Vec<String> = self.labels.iter().map(|label| label_json(map, label)).collect();
+    format!(
+      "{{\"kind\":{},\"severity\":{},\"message\":{},\"file\":{},\"range\":{},\"labels\":[{}],\"notes\":{},\"help\":{}}}",
+      escape(&kind),
+      escape(&self.severity.to_string()),
+      escape(&self.message),
+      file,
+      range,
+      labels.join(","),
+      strings_json(&self.notes),
+      strings_json(&self.help),
+    )
+  }
+}
+
+impl ToJson for Error {
+  fn to_json(&self, map: &SourceMap) -> String {
+    Diagnostic::from(self).to_json(map)
+  }
+}
+
+fn label_json(map: &SourceMap, label: &Label) -> String {
+  format!(
+    "{{\"primary\":{},\"message\":{},\"file\":{},\"range\":{}}}",
+    label.primary,
+    escape(&label.message),
+    file_json(map, label.span),
+    range_json(map, label.span),
+  )
+}
+
+fn file_json(map: &SourceMap, span: Span) -> String {
+  map.file(span.start).map_or("null".to_string(), |file| escape(file.name()))
+}
+
+fn range_json(map: &SourceMap, span: Span) -> String {
+  let Some(file) = map.file(span.start) else {
+    return "null".to_string();
+  };
+  let position = |pos: usize| {
+    let location = file.location(pos);
+    format!(
+      "{{\"offset\":{},\"byte\":{},\"line\":{},\"column\":{},\"utf16_column\":{}}}",
+      pos.clamp(file.start(), file.end()),
+      file.byte_offset(pos),
+      location.line,
+      location.column,
+      location.utf16_column,
+    )
+  };
+  format!("{{\"start\":{},\"end\":{}}}", position(span.start), position(span.end))
+}
+
+fn strings_json(items: &[String]) -> String {
+  let items: Vec<String> = items.iter().map(|item| escape(item)).collect();
+  format!("[{}]", items.join(","))
+}
+
+/// JSON 字符串字面量（含两侧引号）
+fn escape(s: &str) -> String {
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push('"');
+  for c in s.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::SyntaxError;
+
+  #[test]
+  fn error_to_json() {
+    let mut map = SourceMap::new();
+    map.add_file("a.cat", "1");
+    map.add_file("main.cat", "s = 'é'\nx = \"$\"");
+    let err = SyntaxError::new("invalid \"$\"", Span::new(15, 16));
+    assert_eq!(err.to_json(&map), concat!(
+      r#"{"kind":"SyntaxError","severity":"error","message":"invalid \"$\"","file":"main.cat","#,
+      r#""range":{"start":{"offset":15,"byte":14,"line":2,"column":6,"utf16_column":6},"#,
+      r#""end":{"offset":16,"byte":15,"line":2,"column":7,"utf16_column":7}},"#,
+      r#""labels":[{"primary":true,"message":"","file":"main.cat","range":{"start":{"offset":15,"byte":14,"line":2,"column":6,"utf16_column":6},"#,
+      r#""end":{"offset":16,"byte":15,"line":2,"column":7,"utf16_column":7}}}],"#,
+      r#""notes":[],"help":[]}"#,
+    ));
+  }
+
+  #[test]
+  fn diagnostic_without_location() {
+    let map = SourceMap::new();
+    let diag = Diagnostic::error("division by zero")
+      .with_code("ZeroDivisionError")
+      .with_note("line 1\ttab");
+    assert_eq!(diag.to_json(&map), concat!(
+      r#"{"kind":"ZeroDivisionError","severity":"error","message":"division by zero","file":null,"range":null,"#,
+      r#""labels":[],"notes":["line 1\ttab"],"help":[]}"#,
+    ));
+  }
+}
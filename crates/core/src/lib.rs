@@ -1,8 +1,20 @@
 mod span;
 mod errors;
+mod source;
+mod diagnostic;
+mod json;
 pub mod ast;
 pub use span::Span;
+pub use source::SourceFile;
+pub use source::SourceMap;
+pub use source::Location;
+pub use source::SpanLocation;
+pub use diagnostic::Diagnostic;
+pub use diagnostic::Label;
+pub use diagnostic::Severity;
+pub use json::ToJson;
 pub use errors::Error;
+pub use errors::ErrorKind;
 pub use errors::SyntaxError;
 pub use errors::TabError;
 pub use errors::IndentationError;
@@ -0,0 +1,204 @@
+use super::Span;
+use std::fmt;
+use std::rc::Rc;
+
+/// 源码中的一个位置；行号与各列号均从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+  pub line: usize,
+  /// 按字符计的列号
+  pub column: usize,
+  /// 按 UTF-8 字节计的列号
+  pub utf8_column: usize,
+  /// 按 UTF-16 码元计的列号（供 LSP 等编辑器协议使用）
+  pub utf16_column: usize,
+}
+
+/// Span 对应的文件名与起止位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanLocation {
+  pub name: String,
+  pub start: Location,
+  pub end: Location,
+}
+
+impl fmt::Display for SpanLocation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}:{}:{}", self.name, self.start.line, self.start.column)
+  }
+}
+
+/// 一个源文件；在 SourceMap 中占据 [start, end] 的全局字符偏移区间
+#[derive(Debug)]
+pub struct SourceFile {
+  name: String,
+  text: String,
+  start: usize,
+  len: usize,
+  /// 每行行首的 (字符偏移, 字节偏移)，字符偏移相对于文件开头
+  line_starts: Vec<(usize, usize)>,
+}
+
+impl SourceFile {
+  pub fn new<N: Into<String>, T: Into<String>>(name: N, text: T) -> Self {
+    Self::with_start(name.into(), text.into(), 0)
+  }
+
+  fn with_start(name: String, text: String, start: usize) -> Self {
+    let mut line_starts = vec![(0, 0)];
+    let mut len = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((byte, c)) = chars.next() {
+      len += 1;
+      // \r\n 视为一个换行
+      if c == '\r' && chars.peek().is_some_and(|&(_, next)| next == '\n') {
+        continue;
+      }
+      if c == '\n' || c == '\r' {
+        line_starts.push((len, byte + 1));
+      }
+    }
+    Self { name, text, start, len, line_starts }
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  /// 文件第一个字符的全局偏移
+  pub fn start(&self) -> usize {
+    self.start
+  }
+
+  /// 文件末尾（EOF 位置）的全局偏移
+  pub fn end(&self) -> usize {
+    self.start + self.len
+  }
+
+  /// pos 是否落在本文件内（含 EOF 位置）
+  pub fn contains(&self, pos: usize) -> bool {
+    self.start <= pos && pos <= self.end()
+  }
+
+  pub fn line_count(&self) -> usize {
+    self.line_starts.len()
+  }
+
+  /// 第 line 行（从 1 开始）的文本，不含行尾换行
+  pub fn line(&self, line: usize) -> Option<&str> {
+    let &(_, begin) = self.line_starts.get(line.checked_sub(1)?)?;
+    let end = self.line_starts.get(line).map_or(self.text.len(), |&(_, byte)| byte);
+    Some(self.text[begin..end].trim_end_matches(['\n', '\r']))
+  }
+
+  /// 全局偏移 pos 对应的行列；超出文件范围时截断到文件首尾
+  pub fn location(&self, pos: usize) -> Location {
+    let offset = pos.clamp(self.start, self.end()) - self.start;
+    let index = self.line_starts.partition_point(|&(char_start, _)| char_start <= offset) - 1;
+    let (char_start, byte_start) = self.line_starts[index];
+    let (mut utf8, mut utf16) = (0, 0);
+    for c in self.text[byte_start..].chars().take(offset - char_start) {
+      utf8 += c.len_utf8();
+      utf16 += c.len_utf16();
+    }
+    Location {
+      line: index + 1,
+      column: offset - char_start + 1,
+      utf8_column: utf8 + 1,
+      utf16_column: utf16 + 1,
+    }
+  }
+
+  /// 全局偏移 pos 对应的文件内 UTF-8 字节偏移
+  pub fn byte_offset(&self, pos: usize) -> usize {
+    let location = self.location(pos);
+    self.line_starts[location.line - 1].1 + location.utf8_column - 1
+  }
+
+  pub fn span_location(&self, span: Span) -> SpanLocation {
+    SpanLocation {
+      name: self.name.clone(),
+      start: self.location(span.start),
+      end: self.location(span.end),
+    }
+  }
+}
+
+/// 多个源文件的注册表；每个文件占据互不相交的全局偏移区间，Span 因此可以唯一确定所属文件
+#[derive(Debug, Default)]
+pub struct SourceMap {
+  files: Vec<Rc<SourceFile>>,
+  next_start: usize,
+}
+
+impl SourceMap {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 注册一个源文件，返回其在全局偏移空间中的句柄
+  pub fn add_file<N: Into<String>, T: Into<String>>(&mut self, name: N, text: T) -> Rc<SourceFile> {
+    let file = Rc::new(SourceFile::with_start(name.into(), text.into(), self.next_start));
+    // 留出 1 的间隔，使上一个文件的 EOF 位置不与下一个文件重叠
+    self.next_start = file.end() + 1;
+    self.files.push(file.clone());
+    file
+  }
+
+  pub fn files(&self) -> &[Rc<SourceFile>] {
+    &self.files
+  }
+
+  /// 查找包含全局偏移 pos 的文件
+  pub fn file(&self, pos: usize) -> Option<&Rc<SourceFile>> {
+    let index = self.files.partition_point(|file| file.start() <= pos);
+    self.files.get(index.checked_sub(1)?).filter(|file| file.contains(pos))
+  }
+
+  pub fn span_location(&self, span: Span) -> Option<SpanLocation> {
+    Some(self.file(span.start)?.span_location(span))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn line_and_columns() {
+    let file = SourceFile::new("a.cat", "x = 1\r\ny = '中😀z'\nlast");
+    assert_eq!(file.line_count(), 3);
+    assert_eq!(file.line(2), Some("y = '中😀z'"));
+    assert_eq!(file.line(3), Some("last"));
+    assert_eq!(file.line(4), None);
+
+    let loc = file.location(4);
+    assert_eq!((loc.line, loc.column), (1, 5));
+    assert_eq!(file.byte_offset(14), 19);
+    // 'z' 位于第 2 行：前面有 5 个 ASCII 字符、一个 BMP 字符和一个增补平面字符
+    let loc = file.location(14);
+    assert_eq!(loc, Location { line: 2, column: 8, utf8_column: 13, utf16_column: 9 });
+    assert_eq!(file.location(100), Location { line: 3, column: 5, utf8_column: 5, utf16_column: 5 });
+  }
+
+  #[test]
+  fn multiple_files() {
+    let mut map = SourceMap::new();
+    let a = map.add_file("a.cat", "1 +\n2");
+    let b = map.add_file("b.cat", "print(x)");
+    assert_eq!((a.start(), a.end()), (0, 5));
+    assert_eq!((b.start(), b.end()), (6, 14));
+
+    assert_eq!(map.file(5).map(|f| f.name()), Some("a.cat"));
+    assert_eq!(map.file(6).map(|f| f.name()), Some("b.cat"));
+    assert_eq!(map.file(15).map(|f| f.name()), None);
+
+    let loc = map.span_location(Span::new(12, 13)).unwrap();
+    assert_eq!(loc.to_string(), "b.cat:1:7");
+    assert_eq!(map.span_location(Span::new(4, 5)).unwrap().to_string(), "a.cat:2:1");
+  }
+}
@@ -16,4 +16,9 @@ impl Span {
   pub fn is_empty(&self) -> bool {
     self.start == self.end
   }
+  
+  /// 整体平移 n 个字符
+  pub fn offset(&self, n: usize) -> Span {
+    Span::new(self.start + n, self.end + n)
+  }
 }
\ No newline at end of file
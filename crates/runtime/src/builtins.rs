@@ -1,13 +1,16 @@
 use crate::value::{Value, NativeFn};
+use crate::class::{Class, Super};
+use crate::vm::RuntimeError;
 use std::rc::Rc;
 
 pub fn make_print() -> Value {
     Value::NativeFunction(NativeFn {
         name: "print".to_string(),
-        func: |args| {
-            let output: Vec<String> = args.iter()
-                .map(|v| format!("{}", v))
-                .collect();
+        func: |vm, args| {
+            let mut output = Vec::with_capacity(args.len());
+            for arg in &args {
+                output.push(vm.str(arg)?);
+            }
             println!("{}", output.join(" "));
             Ok(Value::None)
         },
@@ -17,15 +20,11 @@ pub fn make_print() -> Value {
 pub fn make_len() -> Value {
     Value::NativeFunction(NativeFn {
         name: "len".to_string(),
-        func: |args| {
+        func: |vm, args| {
             if args.len() != 1 {
-                return Err("len() takes exactly one argument".to_string());
-            }
-            match &args[0] {
-                Value::String(s) => Ok(Value::Int(s.len() as i64)),
-                Value::List(list) => Ok(Value::Int(list.borrow().len() as i64)),
-                other => Err(format!("object of type '{}' has no len()", other.type_name())),
+                return Err(RuntimeError::TypeError("len() takes exactly one argument".to_string()));
             }
+            vm.len(&args[0]).map(Value::Int)
         },
     })
 }
@@ -33,9 +32,13 @@ pub fn make_len() -> Value {
 pub fn make_type() -> Value {
     Value::NativeFunction(NativeFn {
         name: "type".to_string(),
-        func: |args| {
+        func: |_, args| {
             if args.len() != 1 {
-                return Err("type() takes exactly one argument".to_string());
+                return Err(RuntimeError::TypeError("type() takes exactly one argument".to_string()));
+            }
+            // 实例与异常对象返回其类，其余内置类型没有类对象
+            if let Some(class) = args[0].class() {
+                return Ok(Value::Class(Rc::clone(class)));
             }
             Ok(Value::String(Rc::new(format!("<class '{}'>", args[0].type_name()))))
         },
@@ -45,29 +48,37 @@ pub fn make_type() -> Value {
 pub fn make_range() -> Value {
     Value::NativeFunction(NativeFn {
         name: "range".to_string(),
-        func: |args| {
-            let (start, end) = match args.len() {
-                1 => match &args[0] {
-                    Value::Int(n) => (0, *n),
-                    _ => return Err("range() integer expected".to_string()),
-                },
-                2 => match (&args[0], &args[1]) {
-                    (Value::Int(a), Value::Int(b)) => (*a, *b),
-                    _ => return Err("range() integers expected".to_string()),
-                },
-                _ => return Err("range() takes 1 or 2 arguments".to_string()),
+        func: |_, args| {
+            let mut ints = Vec::with_capacity(args.len());
+            for arg in &args {
+                match arg {
+                    Value::Int(n) => ints.push(*n),
+                    Value::Bool(b) => ints.push(*b as i64),
+                    other => return Err(RuntimeError::TypeError(
+                        format!("'{}' object cannot be interpreted as an integer", other.type_name())
+                    )),
+                }
+            }
+            let (start, stop, step) = match ints[..] {
+                [stop] => (0, stop, 1),
+                [start, stop] => (start, stop, 1),
+                [start, stop, step] => (start, stop, step),
+                [] => return Err(RuntimeError::TypeError("range expected at least 1 argument, got 0".to_string())),
+                _ => return Err(RuntimeError::TypeError(format!("range expected at most 3 arguments, got {}", ints.len()))),
             };
-            
-            let list: Vec<Value> = (start..end).map(Value::Int).collect();
-            Ok(Value::List(Rc::new(std::cell::RefCell::new(list))))
+            if step == 0 {
+                return Err(RuntimeError::ValueError("range() arg 3 must not be zero".to_string()));
+            }
+            Ok(Value::Range { start, stop, step })
         },
     })
 }
 
+#[allow(dead_code)]
 pub fn make_input() -> Value {
     Value::NativeFunction(NativeFn {
         name: "input".to_string(),
-        func: |args| {
+        func: |_, args| {
             if let Some(Value::String(prompt)) = args.first() {
                 print!("{}", prompt);
                 use std::io::Write;
@@ -79,4 +90,202 @@ pub fn make_input() -> Value {
             Ok(Value::String(Rc::new(input.trim().to_string())))
         },
     })
-}
\ No newline at end of file
+}
+
+pub fn make_isinstance() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "isinstance".to_string(),
+        func: |_, args| {
+            let [obj, classinfo] = &args[..] else {
+                return Err(RuntimeError::TypeError(format!("isinstance expected 2 arguments, got {}", args.len())));
+            };
+            subclass_of(obj.class().map(|class| &**class), classinfo, "isinstance() arg 2 must be a type or tuple of types")
+                .map(Value::Bool)
+        },
+    })
+}
+
+pub fn make_issubclass() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "issubclass".to_string(),
+        func: |_, args| {
+            let [cls, classinfo] = &args[..] else {
+                return Err(RuntimeError::TypeError(format!("issubclass expected 2 arguments, got {}", args.len())));
+            };
+            let Value::Class(cls) = cls else {
+                return Err(RuntimeError::TypeError("issubclass() arg 1 must be a class".to_string()));
+            };
+            subclass_of(Some(cls), classinfo, "issubclass() arg 2 must be a class or tuple of classes").map(Value::Bool)
+        },
+    })
+}
+
+/// class 是否为 classinfo (类或类的元组) 中某个类的子类；没有类的内置值只属于 object
+fn subclass_of(class: Option<&Class>, classinfo: &Value, message: &str) -> Result<bool, RuntimeError> {
+    match classinfo {
+        Value::Class(other) => Ok(match class {
+            Some(class) => class.is_subclass(other),
+            None => other.bases.is_empty(),
+        }),
+        Value::Tuple(items) => {
+            let mut found = false;
+            for item in items.iter() {
+                found |= subclass_of(class, item, message)?;
+            }
+            Ok(found)
+        },
+        _ => Err(RuntimeError::TypeError(message.to_string())),
+    }
+}
+
+/// super(type, obj)；方法中的零参数 super() 由编译器补全两个参数
+pub fn make_super() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "super".to_string(),
+        func: |_, args| match &args[..] {
+            [Value::Class(class), obj] => {
+                if !obj.class().is_some_and(|other| other.is_subclass(class)) {
+                    return Err(RuntimeError::TypeError(
+                        "super(type, obj): obj must be an instance or subtype of type".to_string()
+                    ));
+                }
+                Ok(Value::Super(Rc::new(Super { class: Rc::clone(class), obj: obj.clone() })))
+            },
+            [_, _] => Err(RuntimeError::TypeError("super() argument 1 must be a type".to_string())),
+            [] => Err(RuntimeError::NativeError("super(): no arguments".to_string())),
+            _ => Err(RuntimeError::TypeError(format!("super() expected 2 arguments, got {}", args.len()))),
+        },
+    })
+}
+
+/// object.__init__：不接受实例之外的参数
+pub fn make_object_init() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "object.__init__".to_string(),
+        func: |_, args| {
+            if args.len() != 1 {
+                return Err(RuntimeError::TypeError(
+                    "object.__init__() takes exactly one argument (the instance to initialize)".to_string()
+                ));
+            }
+            Ok(Value::None)
+        },
+    })
+}
+
+/// BaseException.__init__：以其余参数重新设置异常的 args
+pub fn make_exception_init() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "BaseException.__init__".to_string(),
+        func: |_, args| match args.split_first() {
+            Some((Value::Exception(exc), rest)) => {
+                *exc.args.borrow_mut() = rest.to_vec();
+                Ok(Value::None)
+            },
+            _ => Err(RuntimeError::TypeError("descriptor '__init__' requires a 'BaseException' object".to_string())),
+        },
+    })
+}
+
+/// str(value)：实例调用 __str__，没有时使用 __repr__
+pub fn make_str() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "str".to_string(),
+        func: |vm, args| match &args[..] {
+            [] => Ok(Value::String(Rc::new(String::new()))),
+            [value] => vm.str(value).map(|s| Value::String(Rc::new(s))),
+            _ => Err(RuntimeError::TypeError(format!("str expected at most 1 argument, got {}", args.len()))),
+        },
+    })
+}
+
+/// repr(value)：实例调用 __repr__
+pub fn make_repr() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "repr".to_string(),
+        func: |vm, args| match &args[..] {
+            [value] => vm.repr(value).map(|s| Value::String(Rc::new(s))),
+            _ => Err(RuntimeError::TypeError(format!("repr() takes exactly one argument ({} given)", args.len()))),
+        },
+    })
+}
+
+/// bool(value)：实例依次尝试 __bool__ 与 __len__
+pub fn make_bool() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "bool".to_string(),
+        func: |vm, args| match &args[..] {
+            [] => Ok(Value::Bool(false)),
+            [value] => vm.truthy(value).map(Value::Bool),
+            _ => Err(RuntimeError::TypeError(format!("bool expected at most 1 argument, got {}", args.len()))),
+        },
+    })
+}
+
+/// hash(value)：实例调用 __hash__
+pub fn make_hash() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "hash".to_string(),
+        func: |vm, args| match &args[..] {
+            [value] => vm.hash(value).map(Value::Int),
+            _ => Err(RuntimeError::TypeError(format!("hash() takes exactly one argument ({} given)", args.len()))),
+        },
+    })
+}
+
+/// iter(value)：实例调用 __iter__
+pub fn make_iter() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "iter".to_string(),
+        func: |vm, args| match &args[..] {
+            [value] => vm.iter(value.clone()),
+            _ => Err(RuntimeError::TypeError(format!("iter expected 1 argument, got {}", args.len()))),
+        },
+    })
+}
+
+/// next(iterator[, default])：实例调用 __next__；迭代器耗尽时返回 default，没有 default 时抛出 StopIteration
+pub fn make_next() -> Value {
+    Value::NativeFunction(NativeFn {
+        name: "next".to_string(),
+        func: |vm, args| {
+            let (iter, default) = match &args[..] {
+                [iter] => (iter, None),
+                [iter, default] => (iter, Some(default)),
+                _ => return Err(RuntimeError::TypeError(format!("next expected 1 or 2 arguments, got {}", args.len()))),
+            };
+            // 没有 default 时生成器的返回值由 StopIteration 携带
+            if let (Value::Generator(generator), None) = (iter, default) {
+                return vm.send(generator, Value::None);
+            }
+            match vm.next(iter)? {
+                Some(value) => Ok(value),
+                None => default.cloned().ok_or(RuntimeError::StopIteration(Value::None)),
+            }
+        },
+    })
+}
+
+/// 生成器的方法 send、throw、close 与 __next__，第一个参数为生成器本身
+pub fn generator_method(name: &str) -> Option<Value> {
+    let func: fn(&mut crate::vm::VM, Vec<Value>) -> Result<Value, RuntimeError> = match name {
+        "send" => |vm, args| match &args[..] {
+            [Value::Generator(generator), value] => vm.send(generator, value.clone()),
+            _ => Err(RuntimeError::TypeError(format!("send() takes exactly one argument ({} given)", args.len() - 1))),
+        },
+        "throw" => |vm, args| match &args[..] {
+            [Value::Generator(generator), exc] => vm.throw(generator, exc.clone()),
+            _ => Err(RuntimeError::TypeError(format!("throw expected 1 argument, got {}", args.len() - 1))),
+        },
+        "close" => |vm, args| match &args[..] {
+            [Value::Generator(generator)] => vm.close(generator),
+            _ => Err(RuntimeError::TypeError(format!("close() takes no arguments ({} given)", args.len() - 1))),
+        },
+        "__next__" => |vm, args| match &args[..] {
+            [Value::Generator(generator)] => vm.send(generator, Value::None),
+            _ => Err(RuntimeError::TypeError(format!("__next__() takes no arguments ({} given)", args.len() - 1))),
+        },
+        _ => return None,
+    };
+    Some(Value::NativeFunction(NativeFn { name: format!("generator.{}", name), func }))
+}
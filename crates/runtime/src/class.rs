@@ -0,0 +1,183 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use indexmap::IndexMap;
+use crate::value::Value;
+
+/// 类：用户定义的类与内置的 object、异常类型
+#[derive(Debug)]
+pub struct Class {
+  pub name: String,
+  /// 直接基类；只有 object 没有基类
+  pub bases: Vec<Rc<Class>>,
+  /// C3 线性化得到的方法解析顺序，不含自身 (避免循环引用)
+  pub mro: Vec<Rc<Class>>,
+  /// 类属性与方法
+  pub attrs: RefCell<IndexMap<String, Value>>,
+}
+
+impl Class {
+  /// 内置的 object 类
+  pub fn object(attrs: IndexMap<String, Value>) -> Rc<Self> {
+    Rc::new(Self { name: "object".to_string(), bases: Vec::new(), mro: Vec::new(), attrs: RefCell::new(attrs) })
+  }
+
+  /// 按 C3 线性化计算 MRO；基类重复或无法得到一致的顺序时返回 TypeError 的消息
+  pub fn new(name: String, bases: Vec<Rc<Class>>, attrs: IndexMap<String, Value>) -> Result<Rc<Self>, String> {
+    for (i, base) in bases.iter().enumerate() {
+      if bases[..i].iter().any(|other| Rc::ptr_eq(other, base)) {
+        return Err(format!("duplicate base class {}", base.name));
+      }
+    }
+    let mut sequences: Vec<Vec<Rc<Class>>> = bases.iter()
+      .map(|base| std::iter::once(Rc::clone(base)).chain(base.mro.iter().cloned()).collect())
+      .collect();
+    sequences.push(bases.clone());
+    let mut mro = Vec::new();
+    loop {
+      sequences.retain(|sequence| !sequence.is_empty());
+      if sequences.is_empty() {
+        break;
+      }
+      // 选择第一个不出现在任何序列尾部的头部
+      let head = sequences.iter()
+        .map(|sequence| &sequence[0])
+        .find(|head| !sequences.iter().any(|sequence| sequence[1..].iter().any(|class| Rc::ptr_eq(class, head))))
+        .cloned();
+      // 与 CPython 一致，列出剩余序列中无法合并的头部 (去重)
+      let Some(head) = head else {
+        let mut heads: Vec<&Rc<Class>> = Vec::new();
+        for sequence in &sequences {
+          if !heads.iter().any(|head| Rc::ptr_eq(head, &sequence[0])) {
+            heads.push(&sequence[0]);
+          }
+        }
+        let names: Vec<&str> = heads.iter().map(|head| head.name.as_str()).collect();
+        return Err(format!("Cannot create a consistent method resolution order (MRO) for bases {}", names.join(", ")));
+      };
+      for sequence in sequences.iter_mut() {
+        if Rc::ptr_eq(&sequence[0], &head) {
+          sequence.remove(0);
+        }
+      }
+      mro.push(head);
+    }
+    Ok(Rc::new(Self { name, bases, mro, attrs: RefCell::new(attrs) }))
+  }
+
+  /// 自身及 MRO 中的各个类
+  pub fn ancestors(&self) -> impl Iterator<Item = &Class> {
+    std::iter::once(self).chain(self.mro.iter().map(|class| &**class))
+  }
+
+  /// self 是否为 other 本身或其子类
+  pub fn is_subclass(&self, other: &Class) -> bool {
+    self.ancestors().any(|class| std::ptr::eq(class, other))
+  }
+
+  /// 是否为异常类型 (BaseException 的子类)
+  pub fn is_exception(&self) -> bool {
+    self.ancestors().any(|class| class.name == "BaseException" && class.mro.len() == 1)
+  }
+
+  /// 按 MRO 查找属性
+  pub fn lookup(&self, name: &str) -> Option<Value> {
+    self.ancestors().find_map(|class| class.attrs.borrow().get(name).cloned())
+  }
+
+  /// super()：在 MRO 中 after 之后的类里查找属性
+  pub fn lookup_after(&self, after: &Class, name: &str) -> Option<Value> {
+    self.ancestors()
+      .skip_while(|class| !std::ptr::eq(*class, after))
+      .skip(1)
+      .find_map(|class| class.attrs.borrow().get(name).cloned())
+  }
+
+  /// except 子句的类型可以是异常类型或异常类型的元组；不是异常类型时返回 TypeError 的消息
+  pub fn matches(&self, ty: &Value) -> Result<bool, String> {
+    match ty {
+      Value::Class(class) if class.is_exception() => Ok(self.is_subclass(class)),
+      Value::Tuple(items) => {
+        let mut matched = false;
+        for item in items.iter() {
+          matched |= self.matches(item)?;
+        }
+        Ok(matched)
+      },
+      _ => Err("catching classes that do not inherit from BaseException is not allowed".to_string()),
+    }
+  }
+}
+
+/// 用户定义的类的实例
+#[derive(Debug)]
+pub struct Instance {
+  pub class: Rc<Class>,
+  /// 实例属性，优先于类属性
+  pub attrs: RefCell<IndexMap<String, Value>>,
+}
+
+impl Instance {
+  pub fn new(class: Rc<Class>) -> Self {
+    Self { class, attrs: RefCell::new(IndexMap::new()) }
+  }
+}
+
+/// 绑定方法：通过实例访问类中的函数得到，调用时 receiver 作为第一个参数
+#[derive(Debug)]
+pub struct Method {
+  pub func: Value,
+  pub receiver: Value,
+}
+
+/// super(class, obj)：在 obj 的类的 MRO 中从 class 之后开始查找属性
+#[derive(Debug)]
+pub struct Super {
+  pub class: Rc<Class>,
+  pub obj: Value,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn c3_linearization() {
+    let object = Class::object(IndexMap::new());
+    let class = |name: &str, bases: &[&Rc<Class>]| {
+      Class::new(name.to_string(), bases.iter().map(|base| Rc::clone(base)).collect(), IndexMap::new())
+    };
+    let names = |class: &Class| class.ancestors().map(|class| class.name.as_str()).collect::<Vec<_>>().join(" ");
+
+    // C3 线性化的经典示例
+    let a = class("A", &[&object]).unwrap();
+    let b = class("B", &[&object]).unwrap();
+    let c = class("C", &[&object]).unwrap();
+    let d = class("D", &[&object]).unwrap();
+    let e = class("E", &[&object]).unwrap();
+    let k1 = class("K1", &[&a, &b, &c]).unwrap();
+    let k2 = class("K2", &[&d, &b, &e]).unwrap();
+    let k3 = class("K3", &[&d, &a]).unwrap();
+    let z = class("Z", &[&k1, &k2, &k3]).unwrap();
+    assert_eq!(names(&z), "Z K1 K2 K3 D A B C E object");
+    assert!(z.is_subclass(&b) && !b.is_subclass(&z));
+    assert!(z.lookup_after(&k1, "x").is_none());
+
+    a.attrs.borrow_mut().insert("x".to_string(), Value::Int(1));
+    d.attrs.borrow_mut().insert("x".to_string(), Value::Int(2));
+    assert_eq!(z.lookup("x"), Some(Value::Int(2)));
+    assert_eq!(z.lookup_after(&d, "x"), Some(Value::Int(1)));
+
+    assert_eq!(class("X", &[&a, &a]).unwrap_err(), "duplicate base class A");
+    assert_eq!(
+      class("Y", &[&a, &k1]).unwrap_err(),
+      "Cannot create a consistent method resolution order (MRO) for bases A, K1",
+    );
+    // 直接基类可以合并，冲突出现在更深的层次
+    let ab = class("AB", &[&a, &b]).unwrap();
+    let ba = class("BA", &[&b, &a]).unwrap();
+    assert_eq!(
+      class("W", &[&ab, &ba]).unwrap_err(),
+      "Cannot create a consistent method resolution order (MRO) for bases A, B",
+    );
+  }
+}
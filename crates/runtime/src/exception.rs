@@ -0,0 +1,107 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use indexmap::IndexMap;
+use cathon_core::Span;
+use crate::class::Class;
+use crate::value::Value;
+
+/// 内置异常类型及其基类，基类总在子类之前；BaseException 的基类为 object
+const BUILTIN_TYPES: &[(&str, Option<&str>)] = &[
+  ("BaseException", None),
+  ("Exception", Some("BaseException")),
+  ("GeneratorExit", Some("BaseException")),
+  ("ArithmeticError", Some("Exception")),
+  ("OverflowError", Some("ArithmeticError")),
+  ("ZeroDivisionError", Some("ArithmeticError")),
+  ("LookupError", Some("Exception")),
+  ("IndexError", Some("LookupError")),
+  ("KeyError", Some("LookupError")),
+  ("NameError", Some("Exception")),
+  ("UnboundLocalError", Some("NameError")),
+  ("AttributeError", Some("Exception")),
+  ("TypeError", Some("Exception")),
+  ("ValueError", Some("Exception")),
+  ("RuntimeError", Some("Exception")),
+  ("RecursionError", Some("RuntimeError")),
+  ("StopIteration", Some("Exception")),
+  ("SystemError", Some("Exception")),
+];
+
+/// 按 BUILTIN_TYPES 构建内置异常类型的层次
+pub fn builtin_types(object: &Rc<Class>) -> Vec<Rc<Class>> {
+  let mut types: Vec<Rc<Class>> = Vec::with_capacity(BUILTIN_TYPES.len());
+  for (name, base) in BUILTIN_TYPES {
+    let base = match base {
+      Some(base) => types.iter().find(|ty| ty.name == *base).expect("base defined first"),
+      None => object,
+    };
+    types.push(Class::new(name.to_string(), vec![Rc::clone(base)], IndexMap::new()).expect("single inheritance"));
+  }
+  types
+}
+
+/// 异常对象：异常类型 (含用户定义的子类) 的实例
+#[derive(Debug)]
+pub struct Exception {
+  pub kind: Rc<Class>,
+  /// 构造时的参数，BaseException.__init__ 可以重新设置
+  pub args: RefCell<Vec<Value>>,
+  /// 实例属性
+  pub attrs: RefCell<IndexMap<String, Value>>,
+  /// 回溯：异常经过的每一帧的函数名与出错位置，最内层在前
+  pub traceback: RefCell<Vec<(String, Span)>>,
+}
+
+impl Exception {
+  pub fn new(kind: Rc<Class>, args: Vec<Value>) -> Self {
+    Self { kind, args: RefCell::new(args), attrs: RefCell::new(IndexMap::new()), traceback: RefCell::new(Vec::new()) }
+  }
+
+  /// str(e)：没有参数时为空，一个参数时为该参数 (KeyError 为其 repr)，多个参数时为参数元组
+  pub fn message(&self) -> String {
+    match &self.args.borrow()[..] {
+      [] => String::new(),
+      [arg] if self.kind.ancestors().any(|ty| ty.name == "KeyError") => arg.repr(),
+      [arg] => arg.to_string(),
+      args => Value::Tuple(Rc::new(args.to_vec())).to_string(),
+    }
+  }
+
+  /// repr(e)，如 ValueError('bad value')
+  pub fn repr(&self) -> String {
+    let args: Vec<String> = self.args.borrow().iter().map(Value::repr).collect();
+    format!("{}({})", self.kind.name, args.join(", "))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn builtin_hierarchy() {
+    let object = Class::object(IndexMap::new());
+    let types = builtin_types(&object);
+    let ty = |name: &str| Rc::clone(types.iter().find(|ty| ty.name == name).unwrap());
+    let value = |name: &str| Value::Class(ty(name));
+    let zero = ty("ZeroDivisionError");
+    assert!(zero.is_subclass(&ty("ArithmeticError")));
+    assert!(zero.is_subclass(&ty("BaseException")));
+    assert!(!zero.is_subclass(&ty("LookupError")));
+    assert!(!ty("Exception").is_subclass(&zero));
+
+    let tuple = Value::Tuple(Rc::new(vec![value("KeyError"), value("ArithmeticError")]));
+    assert_eq!(zero.matches(&tuple), Ok(true));
+    assert_eq!(ty("TypeError").matches(&tuple), Ok(false));
+    assert!(zero.matches(&Value::Int(1)).is_err());
+    assert!(zero.matches(&Value::Class(object)).is_err());
+    assert!(zero.is_exception() && ty("BaseException").is_exception());
+
+    let string = |s: &str| Value::String(Rc::new(s.to_string()));
+    assert_eq!(Exception::new(ty("KeyError"), vec![string("k")]).message(), "'k'");
+    assert_eq!(Exception::new(ty("ValueError"), vec![string("bad")]).message(), "bad");
+    assert_eq!(Exception::new(ty("ValueError"), vec![string("a"), Value::Int(1)]).message(), "('a', 1)");
+    assert_eq!(Exception::new(ty("ValueError"), vec![string("bad")]).repr(), "ValueError('bad')");
+    assert_eq!(Exception::new(ty("TypeError"), Vec::new()).repr(), "TypeError()");
+  }
+}
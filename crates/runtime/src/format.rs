@@ -0,0 +1,350 @@
+use crate::value::{Value, float_repr};
+use crate::vm::RuntimeError;
+
+/// 格式说明: [[fill]align][sign][z][#][0][width][grouping][.precision][type]
+#[derive(Debug)]
+struct FormatSpec {
+  fill: char,
+  align: Option<char>,
+  sign: char,
+  alternate: bool,
+  zero: bool,
+  width: usize,
+  grouping: Option<char>,
+  precision: Option<usize>,
+  kind: Option<char>,
+}
+
+fn parse_spec(spec: &str, value: &Value) -> Result<FormatSpec, RuntimeError> {
+  let chars: Vec<char> = spec.chars().collect();
+  let invalid = || RuntimeError::ValueError(
+    format!("Invalid format specifier '{}' for object of type '{}'", spec, value.type_name())
+  );
+  let mut result = FormatSpec {
+    fill: ' ',
+    align: None,
+    sign: '-',
+    alternate: false,
+    zero: false,
+    width: 0,
+    grouping: None,
+    precision: None,
+    kind: None,
+  };
+  let mut i = 0;
+
+  let is_align = |c: char| matches!(c, '<' | '>' | '=' | '^');
+  if chars.len() >= 2 && is_align(chars[1]) {
+    result.fill = chars[0];
+    result.align = Some(chars[1]);
+    i = 2;
+  } else if !chars.is_empty() && is_align(chars[0]) {
+    result.align = Some(chars[0]);
+    i = 1;
+  }
+  if let Some(&c @ ('+' | '-' | ' ')) = chars.get(i) {
+    result.sign = c;
+    i += 1;
+  }
+  if chars.get(i) == Some(&'z') {
+    i += 1;
+  }
+  if chars.get(i) == Some(&'#') {
+    result.alternate = true;
+    i += 1;
+  }
+  if chars.get(i) == Some(&'0') {
+    result.zero = true;
+    i += 1;
+  }
+  let start = i;
+  while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+    i += 1;
+  }
+  if i > start {
+    result.width = chars[start..i].iter().collect::<String>().parse().map_err(|_| invalid())?;
+  }
+  if let Some(&c @ (',' | '_')) = chars.get(i) {
+    result.grouping = Some(c);
+    i += 1;
+  }
+  if chars.get(i) == Some(&'.') {
+    i += 1;
+    let start = i;
+    while chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+      i += 1;
+    }
+    if i == start {
+      return Err(RuntimeError::ValueError("Format specifier missing precision".to_string()));
+    }
+    result.precision = Some(chars[start..i].iter().collect::<String>().parse().map_err(|_| invalid())?);
+  }
+  if let Some(&c) = chars.get(i) {
+    result.kind = Some(c);
+    i += 1;
+  }
+  if i != chars.len() {
+    return Err(invalid());
+  }
+  Ok(result)
+}
+
+/// format(value, spec)，即 f-string 中 `{value:spec}` 的结果
+pub fn format_value(value: &Value, spec: &str) -> Result<String, RuntimeError> {
+  if spec.is_empty() {
+    return Ok(value.to_string());
+  }
+  let spec_info = parse_spec(spec, value)?;
+  let unknown = |code: char| RuntimeError::ValueError(
+    format!("Unknown format code '{}' for object of type '{}'", code, value.type_name())
+  );
+
+  match value {
+    Value::String(s) => {
+      if let Some(code) = spec_info.kind.filter(|&c| c != 's') {
+        return Err(unknown(code));
+      }
+      if spec_info.sign != '-' {
+        return Err(RuntimeError::ValueError("Sign not allowed in string format specifier".to_string()));
+      }
+      if spec_info.align == Some('=') {
+        return Err(RuntimeError::ValueError("'=' alignment not allowed in string format specifier".to_string()));
+      }
+      let text: String = match spec_info.precision {
+        Some(p) => s.chars().take(p).collect(),
+        None => s.to_string(),
+      };
+      Ok(pad(&spec_info, "", &text, '<'))
+    },
+    Value::Int(_) | Value::Bool(_) => {
+      let n = match value {
+        Value::Int(n) => *n,
+        _ => value.as_float().expect("bool") as i64,
+      };
+      match spec_info.kind {
+        Some('e' | 'E' | 'f' | 'F' | 'g' | 'G' | '%') => format_float(&spec_info, n as f64),
+        Some(code @ ('d' | 'n' | 'b' | 'o' | 'x' | 'X' | 'c')) => format_int(&spec_info, n, code),
+        None => format_int(&spec_info, n, 'd'),
+        Some(code) => Err(unknown(code)),
+      }
+    },
+    Value::Float(f) => match spec_info.kind {
+      None | Some('e' | 'E' | 'f' | 'F' | 'g' | 'G' | '%' | 'n') => format_float(&spec_info, *f),
+      Some(code) => Err(unknown(code)),
+    },
+    _ => Err(RuntimeError::TypeError(
+      format!("unsupported format string passed to {}.__format__", value.type_name())
+    )),
+  }
+}
+
+fn format_int(spec: &FormatSpec, n: i64, code: char) -> Result<String, RuntimeError> {
+  if spec.precision.is_some() {
+    return Err(RuntimeError::ValueError("Precision not allowed in integer format specifier".to_string()));
+  }
+  if code == 'c' {
+    let c = u32::try_from(n).ok().and_then(char::from_u32)
+      .ok_or_else(|| RuntimeError::OverflowError("%c arg not in range(0x110000)".to_string()))?;
+    return Ok(pad(spec, "", &c.to_string(), '<'));
+  }
+  let magnitude = n.unsigned_abs();
+  let (digits, prefix, group_size) = match code {
+    'b' => (format!("{:b}", magnitude), "0b", 4),
+    'o' => (format!("{:o}", magnitude), "0o", 4),
+    'x' => (format!("{:x}", magnitude), "0x", 4),
+    'X' => (format!("{:X}", magnitude), "0X", 4),
+    _ => (magnitude.to_string(), "", 3),
+  };
+  let digits = match spec.grouping {
+    Some(sep) => group_digits(&digits, sep, group_size),
+    None => digits,
+  };
+  let mut head = sign_prefix(spec, n < 0).to_string();
+  if spec.alternate {
+    head.push_str(prefix);
+  }
+  Ok(pad(spec, &head, &digits, '>'))
+}
+
+fn format_float(spec: &FormatSpec, f: f64) -> Result<String, RuntimeError> {
+  let negative = f.is_sign_negative() && !f.is_nan();
+  let x = f.abs();
+  let upper = matches!(spec.kind, Some('E' | 'F' | 'G'));
+
+  let body = if !x.is_finite() {
+    let text = if x.is_nan() { "nan" } else { "inf" };
+    let text = if upper { text.to_uppercase() } else { text.to_string() };
+    if spec.kind == Some('%') { format!("{}%", text) } else { text }
+  } else {
+    match spec.kind {
+      Some('f' | 'F') => format!("{:.*}", spec.precision.unwrap_or(6), x),
+      Some('e' | 'E') => format_exp(x, spec.precision.unwrap_or(6), upper),
+      Some('g' | 'G' | 'n') => format_general(x, spec.precision.unwrap_or(6), spec.alternate, upper),
+      Some('%') => format!("{:.*}%", spec.precision.unwrap_or(6), x * 100.0),
+      _ => match spec.precision {
+        // 无类型且有精度：同 g，但定点表示至少保留一位小数
+        Some(p) => {
+          let text = format_general(x, p, spec.alternate, false);
+          if text.contains(['.', 'e']) { text } else { format!("{}.0", text) }
+        },
+        None => float_repr(x),
+      },
+    }
+  };
+
+  let body = match spec.grouping {
+    Some(sep) => {
+      let split = body.find(|c: char| !c.is_ascii_digit()).unwrap_or(body.len());
+      format!("{}{}", group_digits(&body[..split], sep, 3), &body[split..])
+    },
+    None => body,
+  };
+  Ok(pad(spec, sign_prefix(spec, negative), &body, '>'))
+}
+
+/// 科学计数法，指数至少两位: 1.500000e+03
+fn format_exp(x: f64, precision: usize, upper: bool) -> String {
+  let text = format!("{:.*e}", precision, x);
+  let (mantissa, exp) = text.split_once('e').expect("has exponent");
+  let exp: i32 = exp.parse().expect("valid exponent");
+  let e = if upper { 'E' } else { 'e' };
+  format!("{}{}{}{:02}", mantissa, e, if exp < 0 { '-' } else { '+' }, exp.abs())
+}
+
+/// 通用格式 g：根据指数选择定点或科学计数法，并去掉末尾的 0
+fn format_general(x: f64, precision: usize, alternate: bool, upper: bool) -> String {
+  let p = precision.max(1);
+  let exp = if x == 0.0 {
+    0
+  } else {
+    let text = format!("{:.*e}", p - 1, x);
+    text.split_once('e').expect("has exponent").1.parse::<i32>().expect("valid exponent")
+  };
+  let text = if -4 <= exp && exp < p as i32 {
+    format!("{:.*}", (p as i32 - 1 - exp) as usize, x)
+  } else {
+    format_exp(x, p - 1, upper)
+  };
+  if alternate {
+    return text;
+  }
+  // 去掉尾数部分末尾的 0 与小数点
+  let (mantissa, exponent) = match text.find(['e', 'E']) {
+    Some(i) => text.split_at(i),
+    None => (text.as_str(), ""),
+  };
+  let mantissa = if mantissa.contains('.') {
+    mantissa.trim_end_matches('0').trim_end_matches('.')
+  } else {
+    mantissa
+  };
+  format!("{}{}", mantissa, exponent)
+}
+
+fn sign_prefix(spec: &FormatSpec, negative: bool) -> &'static str {
+  match (negative, spec.sign) {
+    (true, _) => "-",
+    (false, '+') => "+",
+    (false, ' ') => " ",
+    _ => "",
+  }
+}
+
+/// 按 size 位一组插入分隔符
+fn group_digits(digits: &str, sep: char, size: usize) -> String {
+  let chars: Vec<char> = digits.chars().collect();
+  let mut out = String::new();
+  for (i, c) in chars.iter().enumerate() {
+    if i > 0 && (chars.len() - i).is_multiple_of(size) {
+      out.push(sep);
+    }
+    out.push(*c);
+  }
+  out
+}
+
+/// 按宽度与对齐方式填充；head 为符号与进制前缀，'=' 对齐时填充在 head 与 body 之间
+fn pad(spec: &FormatSpec, head: &str, body: &str, default_align: char) -> String {
+  let (fill, align) = match spec.align {
+    Some(align) => (spec.fill, align),
+    None if spec.zero && default_align == '>' => ('0', '='),
+    None => (spec.fill, default_align),
+  };
+  let len = head.chars().count() + body.chars().count();
+  if spec.width <= len {
+    return format!("{}{}", head, body);
+  }
+  let padding = spec.width - len;
+  let repeat = |n: usize| fill.to_string().repeat(n);
+  match align {
+    '<' => format!("{}{}{}", head, body, repeat(padding)),
+    '^' => format!("{}{}{}{}", repeat(padding / 2), head, body, repeat(padding - padding / 2)),
+    '=' => format!("{}{}{}", head, repeat(padding), body),
+    _ => format!("{}{}{}", repeat(padding), head, body),
+  }
+}
+
+/// ascii()：将 repr 中的非 ASCII 字符转义为 \x、\u 或 \U 形式
+pub fn ascii(s: &str) -> String {
+  let mut out = String::new();
+  for c in s.chars() {
+    match c as u32 {
+      0..=0x7f => out.push(c),
+      n @ 0x80..=0xff => out.push_str(&format!("\\x{:02x}", n)),
+      n @ 0x100..=0xffff => out.push_str(&format!("\\u{:04x}", n)),
+      n => out.push_str(&format!("\\U{:08x}", n)),
+    }
+  }
+  out
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Rc;
+
+  fn fmt(value: Value, spec: &str) -> String {
+    format_value(&value, spec).expect("format")
+  }
+
+  #[test]
+  fn format_numbers() {
+    assert_eq!(fmt(Value::Int(42), "05d"), "00042");
+    assert_eq!(fmt(Value::Int(-42), "+>6"), "+++-42");
+    assert_eq!(fmt(Value::Int(1234567), ","), "1,234,567");
+    assert_eq!(fmt(Value::Int(255), "#x"), "0xff");
+    assert_eq!(fmt(Value::Int(255), "#010b"), "0b11111111");
+    assert_eq!(fmt(Value::Int(65), "c"), "A");
+    assert_eq!(fmt(Value::Int(5), ".2f"), "5.00");
+    assert_eq!(fmt(Value::Float(2.75), ".1f"), "2.8");
+    assert_eq!(fmt(Value::Float(-0.5), "+.3e"), "-5.000e-01");
+    assert_eq!(fmt(Value::Float(1234.5), "g"), "1234.5");
+    assert_eq!(fmt(Value::Float(1e20), "g"), "1e+20");
+    assert_eq!(fmt(Value::Float(1234.0), ".3"), "1.23e+03");
+    assert_eq!(fmt(Value::Float(1.0), ".3"), "1.0");
+    assert_eq!(fmt(Value::Float(0.25), ".0%"), "25%");
+    assert_eq!(fmt(Value::Float(1234567.5), "_.1f"), "1_234_567.5");
+    assert_eq!(fmt(Value::Float(f64::INFINITY), "F"), "INF");
+  }
+
+  #[test]
+  fn format_strings() {
+    let s = || Value::String(Rc::new("cat".to_string()));
+    assert_eq!(fmt(s(), "<5"), "cat  ");
+    assert_eq!(fmt(s(), "*^7"), "**cat**");
+    assert_eq!(fmt(s(), ".2"), "ca");
+    assert_eq!(ascii("'é中😀'"), "'\\xe9\\u4e2d\\U0001f600'");
+  }
+
+  #[test]
+  fn format_errors() {
+    let err = |value: Value, spec: &str| match format_value(&value, spec) {
+      Err(RuntimeError::ValueError(msg)) | Err(RuntimeError::TypeError(msg)) => msg,
+      other => panic!("unexpected {:?}", other),
+    };
+    assert_eq!(err(Value::Float(1.0), "d"), "Unknown format code 'd' for object of type 'float'");
+    assert_eq!(err(Value::Int(1), ".2d"), "Precision not allowed in integer format specifier");
+    assert_eq!(err(Value::Int(1), "5q1"), "Invalid format specifier '5q1' for object of type 'int'");
+    assert_eq!(err(Value::None, "5"), "unsupported format string passed to NoneType.__format__");
+  }
+}
@@ -1,8 +1,10 @@
 use cathon_compiler::CodeObject;
-use crate::value::Value;
+use crate::value::{Value, Cell};
+use crate::exception::Exception;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use indexmap::IndexMap;
 
 /// 调用帧
 pub struct Frame {
@@ -10,23 +12,36 @@ pub struct Frame {
   pub code: CodeObject,
   /// 指令指针
   pub ip: usize,
+  /// 正在执行 (调用方帧中为最近执行) 的指令的偏移，用于定位错误与查找异常处理器
+  pub lasti: usize,
   /// 操作数栈
   pub stack: Vec<Value>,
-  /// 局部变量
-  pub locals: Vec<Value>,
+  /// 局部变量，None 表示尚未赋值
+  pub locals: Vec<Option<Value>>,
+  /// 本帧新建的 cell (对应 cellvars) 与函数捕获的 cell (对应 freevars)
+  pub cells: Vec<Cell>,
   /// 全局变量引用
   pub globals: Rc<RefCell<HashMap<String, Value>>>,
+  /// 类体的命名空间：LoadName / StoreName 先于全局变量使用
+  pub namespace: Option<Rc<RefCell<IndexMap<String, Value>>>>,
+  /// except / finally 子句中正在处理的异常，最内层在末尾
+  pub handled: Vec<Rc<Exception>>,
 }
 
 impl Frame {
   pub fn new(code: CodeObject, globals: Rc<RefCell<HashMap<String, Value>>>) -> Self {
     let locals_count = code.varnames.len();
+    let cells = code.cellvars.iter().map(|_| Rc::new(RefCell::new(None))).collect();
     Self {
       code,
       ip: 0,
+      lasti: 0,
       stack: Vec::with_capacity(256),
-      locals: vec![Value::None; locals_count],
+      locals: vec![None; locals_count],
+      cells,
       globals,
+      namespace: None,
+      handled: Vec::new(),
     }
   }
 
@@ -58,4 +73,9 @@ impl Frame {
   pub fn peek(&self) -> &Value {
     self.stack.last().expect("Stack is empty")
   }
+
+  /// 查看自栈顶起第 depth 个元素 (1 为栈顶)
+  pub fn peek_at(&self, depth: usize) -> &Value {
+    &self.stack[self.stack.len() - depth]
+  }
 }
\ No newline at end of file
@@ -1,5 +1,9 @@
 mod frame;
 mod value;
+mod class;
+mod exception;
 mod builtins;
+mod format;
 mod vm;
-pub use vm::VM;
\ No newline at end of file
+pub use vm::VM;
+pub use vm::RuntimeError;
\ No newline at end of file
@@ -1,20 +1,197 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use indexmap::{IndexMap, IndexSet};
+use std::hash::{Hash, Hasher};
 use cathon_compiler::CodeObject;
+use crate::frame::Frame;
+use crate::class::{Class, Instance, Method, Super};
+use crate::exception::Exception;
+use crate::vm::{VM, RuntimeError};
+
+thread_local! {
+  /// 正在生成 repr 的可变容器；与 CPython 的 Py_ReprEnter 一样按线程记录，再次遇到时输出 [...] 或 {...}
+  static REPR_ACTIVE: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
 
 /// 运行时值
 #[derive(Debug, Clone)]
 pub enum Value {
   None,
+  /// 二元运算的特殊方法不支持给定的操作数时返回的 NotImplemented
+  NotImplemented,
   Bool(bool),
   Int(i64),
   Float(f64),
   String(Rc<String>),
+  Bytes(Rc<Vec<u8>>),
   List(Rc<RefCell<Vec<Value>>>),
-  Dict(Rc<RefCell<HashMap<String, Value>>>),
+  Tuple(Rc<Vec<Value>>),
+  /// range(start, stop, step)，按需产生整数
+  Range { start: i64, stop: i64, step: i64 },
+  /// GetIter 产生的迭代器
+  Iterator(Rc<RefCell<Iter>>),
+  /// 保持插入顺序的字典
+  Dict(Rc<RefCell<IndexMap<HashKey, Value>>>),
+  /// 保持插入顺序的集合
+  Set(Rc<RefCell<IndexSet<HashKey>>>),
+  /// BuildSlice 产生的切片对象
+  Slice(Rc<Slice>),
   Function(Rc<Function>),
   NativeFunction(NativeFn),
+  /// 调用生成器函数 (如生成器表达式) 得到的生成器
+  Generator(Rc<RefCell<Generator>>),
+  /// 类，包括内置的 object 与异常类型
+  Class(Rc<Class>),
+  /// 用户定义的类的实例 (异常类型的实例为 Exception)
+  Instance(Rc<Instance>),
+  /// 绑定方法
+  Method(Rc<Method>),
+  /// super() 的结果
+  Super(Rc<Super>),
+  /// 异常对象
+  Exception(Rc<Exception>),
+  /// LoadClosure 压入的 cell，仅出现在 MakeFunction 的闭包元组中
+  Cell(Cell),
+}
+
+/// dict 的键与 set 的元素：只能由可哈希的值构造，相等的数值 (如 1、1.0 与 true) 哈希相同；
+/// 定义了 __hash__ 的实例带有虚拟机预先算出的哈希
+#[derive(Debug, Clone)]
+pub struct HashKey(Value, Option<i64>);
+
+impl HashKey {
+  /// 不可哈希时返回 TypeError 的消息
+  pub fn new(value: Value) -> Result<Self, String> {
+    if let Some(unhashable) = value.unhashable() {
+      return Err(format!("unhashable type: '{}'", unhashable.type_name()));
+    }
+    Ok(Self(value, None))
+  }
+
+  /// 使用 __hash__ 的结果作为哈希
+  pub fn with_hash(value: Value, hash: i64) -> Self {
+    Self(value, Some(hash))
+  }
+
+  /// __hash__ 的结果
+  pub fn special_hash(&self) -> Option<i64> {
+    self.1
+  }
+
+  pub fn value(&self) -> &Value {
+    &self.0
+  }
+}
+
+impl From<&str> for HashKey {
+  fn from(key: &str) -> Self {
+    Self(Value::String(Rc::new(key.to_string())), None)
+  }
+}
+
+impl PartialEq for HashKey {
+  fn eq(&self, other: &Self) -> bool {
+    self.0 == other.0
+  }
+}
+
+impl Eq for HashKey {}
+
+impl Hash for HashKey {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    match self.1 {
+      Some(hash) => hash.hash(state),
+      None => hash_value(&self.0, state),
+    }
+  }
+}
+
+fn hash_value<H: Hasher>(value: &Value, state: &mut H) {
+  match value {
+    Value::None => 0u8.hash(state),
+    Value::NotImplemented => 1u8.hash(state),
+    Value::Bool(b) => (*b as i64).hash(state),
+    Value::Int(n) => n.hash(state),
+    // 整数值的浮点数与对应的整数哈希相同
+    Value::Float(f) if f.fract() == 0.0 && f.abs() < i64::MAX as f64 => (*f as i64).hash(state),
+    Value::Float(f) => f.to_bits().hash(state),
+    Value::String(s) => s.hash(state),
+    Value::Bytes(b) => b.hash(state),
+    Value::Tuple(items) => {
+      items.len().hash(state);
+      for item in items.iter() {
+        hash_value(item, state);
+      }
+    },
+    Value::Range { start, stop, step } => (start, stop, step).hash(state),
+    Value::Function(func) => Rc::as_ptr(func).hash(state),
+    Value::NativeFunction(native) => native.name.hash(state),
+    Value::Iterator(iter) => Rc::as_ptr(iter).hash(state),
+    Value::Generator(generator) => Rc::as_ptr(generator).hash(state),
+    Value::Class(class) => Rc::as_ptr(class).hash(state),
+    Value::Instance(instance) => Rc::as_ptr(instance).hash(state),
+    Value::Method(method) => hash_value(&method.func, state),
+    Value::Super(sup) => Rc::as_ptr(sup).hash(state),
+    Value::Exception(exc) => Rc::as_ptr(exc).hash(state),
+    Value::Cell(cell) => Rc::as_ptr(cell).hash(state),
+    Value::List(_) | Value::Dict(_) | Value::Set(_) | Value::Slice(_) => unreachable!("unhashable"),
+  }
+}
+
+/// 被闭包共享的变量，None 表示尚未赋值
+pub type Cell = Rc<RefCell<Option<Value>>>;
+
+/// 迭代器的状态
+#[derive(Debug)]
+pub enum Iter {
+  /// 按下标迭代列表，循环中对列表的修改可见
+  List(Rc<RefCell<Vec<Value>>>, usize),
+  /// 迭代开始时取得的元素快照 (tuple、str 的字符、dict 的键)
+  Items(std::vec::IntoIter<Value>),
+  Range { next: i64, stop: i64, step: i64 },
+}
+
+impl Iterator for Iter {
+  type Item = Value;
+
+  fn next(&mut self) -> Option<Value> {
+    match self {
+      Iter::List(list, index) => {
+        let item = list.borrow().get(*index).cloned();
+        *index += 1;
+        item
+      },
+      Iter::Items(items) => items.next(),
+      Iter::Range { next, stop, step } => {
+        if (*step > 0 && *next < *stop) || (*step < 0 && *next > *stop) {
+          let value = *next;
+          *next = next.saturating_add(*step);
+          Some(Value::Int(value))
+        } else {
+          None
+        }
+      },
+    }
+  }
+}
+
+/// range 中元素的个数；可能超出 i64，如 range(-2**63, 2**63 - 1)
+pub fn range_len(start: i64, stop: i64, step: i64) -> i128 {
+  let (span, step) = if step > 0 {
+    (stop as i128 - start as i128, step as i128)
+  } else {
+    (start as i128 - stop as i128, -(step as i128))
+  };
+  if span <= 0 { 0 } else { (span + step - 1) / step }
+}
+
+/// slice(start, stop, step)，省略的部分为 None
+#[derive(Debug)]
+pub struct Slice {
+  pub start: Value,
+  pub stop: Value,
+  pub step: Value,
 }
 
 /// 函数对象
@@ -22,13 +199,19 @@ pub enum Value {
 pub struct Function {
   pub code: CodeObject,
   pub globals: Rc<RefCell<HashMap<String, Value>>>,
+  /// 最后 defaults.len() 个位置参数的默认值
+  pub defaults: Vec<Value>,
+  /// 仅关键字参数的默认值
+  pub kwdefaults: IndexMap<String, Value>,
+  /// 按 code.freevars 的顺序捕获的外层 cell
+  pub closure: Vec<Cell>,
 }
 
 /// 原生函数
 #[derive(Clone)]
 pub struct NativeFn {
   pub name: String,
-  pub func: fn(Vec<Value>) -> Result<Value, String>,
+  pub func: fn(&mut VM, Vec<Value>) -> Result<Value, RuntimeError>,
 }
 
 impl std::fmt::Debug for NativeFn {
@@ -37,6 +220,22 @@ impl std::fmt::Debug for NativeFn {
   }
 }
 
+/// 生成器：每次恢复时将挂起的帧压回调用栈执行，直到 yield 或返回
+pub struct Generator {
+  /// 生成器函数的名字，如 <genexpr>
+  pub name: String,
+  /// 挂起的帧；正在执行或已经结束时为 None
+  pub frame: Option<Frame>,
+  /// 帧正在调用栈上执行
+  pub running: bool,
+}
+
+impl std::fmt::Debug for Generator {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "<generator object {}>", self.name)
+  }
+}
+
 impl Value {
   pub fn is_truthy(&self) -> bool {
     match self {
@@ -45,22 +244,176 @@ impl Value {
       Value::Int(n) => *n != 0,
       Value::Float(f) => *f != 0.0,
       Value::String(s) => !s.is_empty(),
+      Value::Bytes(b) => !b.is_empty(),
       Value::List(list) => !list.borrow().is_empty(),
+      Value::Tuple(items) => !items.is_empty(),
+      Value::Dict(dict) => !dict.borrow().is_empty(),
+      Value::Set(set) => !set.borrow().is_empty(),
+      Value::Range { start, stop, step } => range_len(*start, *stop, *step) > 0,
       _ => true,
     }
   }
 
-  pub fn type_name(&self) -> &'static str {
+  /// 妨碍作为 dict 的键或 set 的元素的值：自身不可哈希时为自身，元组为其中第一个不可哈希的元素
+  pub fn unhashable(&self) -> Option<&Value> {
+    match self {
+      Value::List(_) | Value::Dict(_) | Value::Set(_) | Value::Slice(_) => Some(self),
+      Value::Tuple(items) => items.iter().find_map(Value::unhashable),
+      _ => None,
+    }
+  }
+
+  /// 数值转换为浮点数 (bool/int/float)，其他类型返回 None
+  pub fn as_float(&self) -> Option<f64> {
+    match self {
+      Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+      Value::Int(n) => Some(*n as f64),
+      Value::Float(f) => Some(*f),
+      _ => None,
+    }
+  }
+
+  /// repr(): 字符串带引号并转义，容器内元素同样使用 repr
+  pub fn repr(&self) -> String {
+    match self {
+      Value::String(s) => string_repr(s),
+      Value::Exception(exc) => exc.repr(),
+      _ => self.to_string(),
+    }
+  }
+
+  /// 容器 (list、tuple、dict、set) 的 repr，元素由 repr 转换；其他值返回 None。
+  /// 先取出元素再转换，转换 (如调用 __repr__) 时可以修改容器；包含自身的容器输出 [...] 或 {...}
+  pub fn container_repr<E>(&self, repr: impl FnMut(&Value) -> Result<String, E>) -> Option<Result<String, E>> {
+    let (id, placeholder) = match self {
+      Value::List(list) => (Rc::as_ptr(list) as *const (), "[...]"),
+      Value::Dict(dict) => (Rc::as_ptr(dict) as *const (), "{...}"),
+      Value::Set(set) => (Rc::as_ptr(set) as *const (), "{...}"),
+      Value::Tuple(_) => return self.items_repr(repr),
+      _ => return None,
+    };
+    if REPR_ACTIVE.with(|active| active.borrow().contains(&id)) {
+      return Some(Ok(placeholder.to_string()));
+    }
+    REPR_ACTIVE.with(|active| active.borrow_mut().push(id));
+    let result = self.items_repr(repr);
+    REPR_ACTIVE.with(|active| active.borrow_mut().pop());
+    result
+  }
+
+  fn items_repr<E>(&self, mut repr: impl FnMut(&Value) -> Result<String, E>) -> Option<Result<String, E>> {
+    let mut join = |items: &[Value]| -> Result<Vec<String>, E> {
+      items.iter().map(&mut repr).collect()
+    };
+    let result = match self {
+      Value::List(list) => {
+        let items = list.borrow().clone();
+        join(&items).map(|items| format!("[{}]", items.join(", ")))
+      },
+      // 单元素元组带尾随逗号: (1,)
+      Value::Tuple(items) => join(items).map(|items| match &items[..] {
+        [item] => format!("({},)", item),
+        _ => format!("({})", items.join(", ")),
+      }),
+      Value::Dict(dict) => {
+        let items: Vec<Value> = dict.borrow().iter()
+          .flat_map(|(key, value)| [key.value().clone(), value.clone()])
+          .collect();
+        join(&items).map(|items| {
+          let pairs: Vec<String> = items.chunks(2).map(|pair| format!("{}: {}", pair[0], pair[1])).collect();
+          format!("{{{}}}", pairs.join(", "))
+        })
+      },
+      // 空集合没有字面量，{} 是空字典
+      Value::Set(set) => {
+        let items: Vec<Value> = set.borrow().iter().map(|item| item.value().clone()).collect();
+        join(&items).map(|items| if items.is_empty() {
+          "set()".to_string()
+        } else {
+          format!("{{{}}}", items.join(", "))
+        })
+      },
+      _ => return None,
+    };
+    Some(result)
+  }
+
+  /// 用户定义的类的实例与异常对象所属的类
+  pub fn class(&self) -> Option<&Rc<Class>> {
+    match self {
+      Value::Instance(instance) => Some(&instance.class),
+      Value::Exception(exc) => Some(&exc.kind),
+      _ => None,
+    }
+  }
+
+  pub fn type_name(&self) -> &str {
     match self {
       Value::None => "NoneType",
+      Value::NotImplemented => "NotImplementedType",
       Value::Bool(_) => "bool",
       Value::Int(_) => "int",
       Value::Float(_) => "float",
       Value::String(_) => "str",
+      Value::Bytes(_) => "bytes",
       Value::List(_) => "list",
+      Value::Tuple(_) => "tuple",
+      Value::Range { .. } => "range",
+      Value::Iterator(_) => "iterator",
       Value::Dict(_) => "dict",
+      Value::Set(_) => "set",
+      Value::Slice(_) => "slice",
       Value::Function(_) => "function",
       Value::NativeFunction(_) => "builtin_function",
+      Value::Generator(_) => "generator",
+      Value::Class(_) => "type",
+      Value::Instance(instance) => &instance.class.name,
+      Value::Method(_) => "method",
+      Value::Super(_) => "super",
+      Value::Exception(exc) => &exc.kind.name,
+      Value::Cell(_) => "cell",
+    }
+  }
+}
+
+/// 与 == 运算相同的比较：数值跨类型比较，容器逐元素比较，函数比较身份
+impl PartialEq for Value {
+  fn eq(&self, other: &Self) -> bool {
+    match (self, other) {
+      (Value::None, Value::None) => true,
+      (Value::NotImplemented, Value::NotImplemented) => true,
+      (Value::Bool(a), Value::Bool(b)) => a == b,
+      (Value::Int(a), Value::Int(b)) => a == b,
+      (Value::String(a), Value::String(b)) => a == b,
+      (Value::Bytes(a), Value::Bytes(b)) => a == b,
+      (Value::List(a), Value::List(b)) => Rc::ptr_eq(a, b) || *a.borrow() == *b.borrow(),
+      (Value::Tuple(a), Value::Tuple(b)) => a == b,
+      (Value::Dict(a), Value::Dict(b)) => {
+        let (a, b) = (a.borrow(), b.borrow());
+        a.len() == b.len() && a.iter().all(|(key, x)| b.get(key).is_some_and(|y| x == y))
+      },
+      (Value::Set(a), Value::Set(b)) => {
+        let (a, b) = (a.borrow(), b.borrow());
+        a.len() == b.len() && a.iter().all(|item| b.contains(item))
+      },
+      (Value::Range { start: a, stop: b, step: c }, Value::Range { start: x, stop: y, step: z }) => {
+        // 元素相同的 range 相等，如 range(0) == range(5, 5)
+        let (len, other_len) = (range_len(*a, *b, *c), range_len(*x, *y, *z));
+        len == other_len && (len == 0 || (a == x && (len == 1 || c == z)))
+      },
+      (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+      (Value::NativeFunction(a), Value::NativeFunction(b)) => a.name == b.name,
+      (Value::Iterator(a), Value::Iterator(b)) => Rc::ptr_eq(a, b),
+      (Value::Generator(a), Value::Generator(b)) => Rc::ptr_eq(a, b),
+      (Value::Class(a), Value::Class(b)) => Rc::ptr_eq(a, b),
+      (Value::Instance(a), Value::Instance(b)) => Rc::ptr_eq(a, b),
+      (Value::Method(a), Value::Method(b)) => a.func == b.func && a.receiver == b.receiver,
+      (Value::Super(a), Value::Super(b)) => Rc::ptr_eq(a, b),
+      (Value::Exception(a), Value::Exception(b)) => Rc::ptr_eq(a, b),
+      _ => match (self.as_float(), other.as_float()) {
+        (Some(a), Some(b)) => a == b,
+        _ => false,
+      },
     }
   }
 }
@@ -69,19 +422,155 @@ impl std::fmt::Display for Value {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     match self {
       Value::None => write!(f, "None"),
+      Value::NotImplemented => write!(f, "NotImplemented"),
       Value::Bool(b) => write!(f, "{}", if *b { "True" } else { "False" }),
       Value::Int(n) => write!(f, "{}", n),
-      Value::Float(n) => write!(f, "{}", n),
+      Value::Float(n) => write!(f, "{}", float_repr(*n)),
       Value::String(s) => write!(f, "{}", s),
-      Value::List(list) => {
-          let items: Vec<String> = list.borrow().iter()
-              .map(|v| format!("{:?}", v))
+      Value::Bytes(b) => {
+          let escaped: String = b.iter()
+              .map(|&c| match c {
+                  b'\\' => "\\\\".to_string(),
+                  b'\'' => "\\'".to_string(),
+                  b'\t' => "\\t".to_string(),
+                  b'\n' => "\\n".to_string(),
+                  b'\r' => "\\r".to_string(),
+                  0x20..=0x7e => (c as char).to_string(),
+                  _ => format!("\\x{:02x}", c),
+              })
               .collect();
-          write!(f, "[{}]", items.join(", "))
+          write!(f, "b'{}'", escaped)
       }
+      Value::List(_) | Value::Tuple(_) | Value::Dict(_) | Value::Set(_) => {
+          let repr = self.container_repr(|item| Ok::<_, std::convert::Infallible>(item.repr()));
+          match repr {
+              Some(Ok(repr)) => write!(f, "{}", repr),
+              _ => unreachable!("containers have a repr"),
+          }
+      }
+      Value::Slice(slice) => write!(f, "slice({}, {}, {})", slice.start.repr(), slice.stop.repr(), slice.step.repr()),
+      Value::Range { start, stop, step: 1 } => write!(f, "range({}, {})", start, stop),
+      Value::Range { start, stop, step } => write!(f, "range({}, {}, {})", start, stop, step),
       Value::Function(func) => write!(f, "<function {}>", func.code.name),
       Value::NativeFunction(nf) => write!(f, "{:?}", nf),
+      Value::Generator(generator) => write!(f, "{:?}", generator.borrow()),
+      Value::Class(class) => write!(f, "<class '{}'>", class.name),
+      Value::Instance(instance) => write!(f, "<{} object>", instance.class.name),
+      Value::Method(method) => {
+          let name = match &method.func {
+              Value::Function(func) => func.code.name.as_str(),
+              Value::NativeFunction(native) => native.name.as_str(),
+              other => other.type_name(),
+          };
+          write!(f, "<bound method {} of {}>", name, method.receiver.repr())
+      }
+      Value::Super(sup) => write!(f, "<super: <class '{}'>, <{} object>>", sup.class.name, sup.obj.type_name()),
+      Value::Exception(exc) => write!(f, "{}", exc.message()),
       _ => write!(f, "<{}>", self.type_name()),
     }
   }
-}
\ No newline at end of file
+}
+
+/// 字符串的 repr：默认使用单引号，内容含单引号且不含双引号时使用双引号
+pub fn string_repr(s: &str) -> String {
+  let quote = if s.contains('\'') && !s.contains('"') { '"' } else { '\'' };
+  let mut out = String::with_capacity(s.len() + 2);
+  out.push(quote);
+  for c in s.chars() {
+    match c {
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if c == quote => {
+        out.push('\\');
+        out.push(c);
+      },
+      c if (c as u32) < 0x20 || c == '\x7f' => out.push_str(&format!("\\x{:02x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push(quote);
+  out
+}
+
+/// 浮点数的 repr：最短往返表示，指数小于 -4 或不小于 16 时使用科学计数法
+pub fn float_repr(f: f64) -> String {
+  if f.is_nan() {
+    return "nan".to_string();
+  }
+  if f.is_infinite() {
+    return if f > 0.0 { "inf" } else { "-inf" }.to_string();
+  }
+  // Rust 的 {:e} 给出最短往返的有效数字，如 "1.2345e3"
+  let sci = format!("{:e}", f);
+  let (mantissa, exp) = sci.split_once('e').expect("has exponent");
+  let exp: i32 = exp.parse().expect("valid exponent");
+  let negative = mantissa.starts_with('-');
+  let digits: String = mantissa.chars().filter(|c| c.is_ascii_digit()).collect();
+  let sign = if negative { "-" } else { "" };
+  
+  if (-4..16).contains(&exp) {
+    let point = exp + 1;
+    let body = if point <= 0 {
+      format!("0.{}{}", "0".repeat((-point) as usize), digits)
+    } else if point as usize >= digits.len() {
+      format!("{}{}.0", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+      format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    };
+    format!("{}{}", sign, body)
+  } else {
+    let body = if digits.len() > 1 {
+      format!("{}.{}", &digits[..1], &digits[1..])
+    } else {
+      digits
+    };
+    format!("{}{}e{}{:02}", sign, body, if exp < 0 { '-' } else { '+' }, exp.abs())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn range_iteration() {
+    let collect = |start, stop, step| {
+      Iter::Range { next: start, stop, step }
+        .map(|v| match v { Value::Int(n) => n, _ => unreachable!() })
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(collect(0, 5, 2), vec![0, 2, 4]);
+    assert_eq!(collect(10, 0, -3), vec![10, 7, 4, 1]);
+    assert_eq!(collect(3, 3, 1), Vec::<i64>::new());
+    assert_eq!(range_len(0, 5, 2), 3);
+    assert_eq!(range_len(10, 0, -3), 4);
+    assert_eq!(range_len(5, 0, 1), 0);
+    assert_eq!(range_len(i64::MIN, i64::MAX, 1), u64::MAX as i128);
+    assert_eq!(Value::Range { start: 1, stop: 9, step: 2 }.to_string(), "range(1, 9, 2)");
+  }
+
+  #[test]
+  fn hash_keys() {
+    let key = |value| HashKey::new(value).unwrap();
+    let tuple = |items: Vec<Value>| Value::Tuple(Rc::new(items));
+    let list = Value::List(Rc::new(RefCell::new(vec![Value::Int(1)])));
+
+    // 1、1.0 与 true 是同一个键
+    let mut set = IndexSet::new();
+    for value in [Value::Int(1), Value::Float(1.0), Value::Bool(true), Value::Float(1.5)] {
+      set.insert(key(value));
+    }
+    assert_eq!(set.len(), 2);
+    assert!(!set.contains(&key(tuple(vec![]))));
+    set.insert(key(tuple(vec![Value::Int(2), Value::String(Rc::new("a".into()))])));
+    assert!(set.contains(&key(tuple(vec![Value::Float(2.0), Value::String(Rc::new("a".into()))]))));
+    assert!(!set.contains(&HashKey::from("a")));
+
+    assert_eq!(HashKey::new(list.clone()).unwrap_err(), "unhashable type: 'list'");
+    assert_eq!(HashKey::new(tuple(vec![Value::Int(1), list])).unwrap_err(), "unhashable type: 'list'");
+    assert_eq!(Value::Set(Rc::new(RefCell::new(set))).to_string(), "{1, 1.5, (2, 'a')}");
+    assert_eq!(Value::Set(Rc::new(RefCell::new(IndexSet::new()))).to_string(), "set()");
+  }
+}
@@ -1,17 +1,42 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::HashMap;
+use indexmap::{IndexMap, IndexSet};
+use std::fmt;
+use std::hash::{DefaultHasher, Hash, Hasher};
 
+use cathon_core::Diagnostic;
 use cathon_compiler::{OpCode, CodeObject, Constant};
 use crate::frame::Frame;
-use crate::value::{Value, Function, NativeFn};
+use crate::value::{Value, Function, Generator, Iter, Slice, HashKey};
+use crate::class::{Class, Instance, Method};
+use crate::exception::{self, Exception};
 use crate::builtins;
+use crate::format;
+
+/// 调用栈的最大深度，超过时抛出 RecursionError
+const MAX_FRAMES: usize = 1000;
+/// execute 的最大嵌套层数：虚拟机发起的调用 (如 __init__ 与特殊方法) 在宿主栈上递归，
+/// 需要在宿主栈溢出之前抛出 RecursionError
+const MAX_NESTING: usize = 150;
 
 pub struct VM {
   /// 调用栈
   frames: Vec<Frame>,
   /// 全局变量
   globals: Rc<RefCell<HashMap<String, Value>>>,
+  /// 内置异常类型，用于将 RuntimeError 转换为异常对象
+  exception_types: HashMap<String, Rc<Class>>,
+  /// 没有写出基类的类以 object 为基类
+  object: Rc<Class>,
+  /// 正在进行的 execute 的嵌套层数
+  nesting: usize,
+}
+
+impl Default for VM {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 impl VM {
@@ -24,19 +49,55 @@ impl VM {
     g.insert("len".to_string(), builtins::make_len());
     g.insert("type".to_string(), builtins::make_type());
     g.insert("range".to_string(), builtins::make_range());
+    g.insert("isinstance".to_string(), builtins::make_isinstance());
+    g.insert("issubclass".to_string(), builtins::make_issubclass());
+    g.insert("super".to_string(), builtins::make_super());
+    g.insert("str".to_string(), builtins::make_str());
+    g.insert("repr".to_string(), builtins::make_repr());
+    g.insert("bool".to_string(), builtins::make_bool());
+    g.insert("hash".to_string(), builtins::make_hash());
+    g.insert("iter".to_string(), builtins::make_iter());
+    g.insert("next".to_string(), builtins::make_next());
+    g.insert("NotImplemented".to_string(), Value::NotImplemented);
+    let object = Class::object(IndexMap::from([("__init__".to_string(), builtins::make_object_init())]));
+    g.insert("object".to_string(), Value::Class(Rc::clone(&object)));
+    let mut exception_types = HashMap::new();
+    for ty in exception::builtin_types(&object) {
+      g.insert(ty.name.clone(), Value::Class(Rc::clone(&ty)));
+      exception_types.insert(ty.name.clone(), ty);
+    }
+    exception_types["BaseException"].attrs.borrow_mut().insert("__init__".to_string(), builtins::make_exception_init());
     drop(g);
     
     Self {
       frames: Vec::new(),
       globals,
+      exception_types,
+      object,
+      nesting: 0,
     }
   }
 
   /// 执行代码对象
   pub fn run(&mut self, code: CodeObject) -> Result<Value, RuntimeError> {
     let frame = Frame::new(code, Rc::clone(&self.globals));
+    let base = self.frames.len();
+    self.frames.push(frame);
+    self.execute(base)
+  }
+
+  /// 压入新的调用帧；调用栈过深时抛出 RecursionError，由调用方的帧按通常的方式处理
+  fn push_frame(&mut self, frame: Frame) -> Result<(), RuntimeError> {
+    if self.too_deep() {
+      return Err(RuntimeError::RecursionError);
+    }
     self.frames.push(frame);
-    self.execute()
+    Ok(())
+  }
+
+  /// 调用栈或 execute 的嵌套是否已达上限
+  fn too_deep(&self) -> bool {
+    self.frames.len() >= MAX_FRAMES || self.nesting >= MAX_NESTING
   }
 
   /// 当前帧
@@ -44,11 +105,62 @@ impl VM {
     self.frames.last_mut().expect("No frame")
   }
 
-  /// 主执行循环
-  fn execute(&mut self) -> Result<Value, RuntimeError> {
+  /// 执行调用栈 base 层之上的帧：指令出错时在出错的帧中按异常表寻找处理器，
+  /// 找不到则弹出该帧并在调用方继续寻找；回到 base 层仍未处理时返回异常，其回溯记录了经过的每一帧
+  fn execute(&mut self, base: usize) -> Result<Value, RuntimeError> {
+    self.execute_with(base, None)
+  }
+
+  /// 同 execute；pending 为恢复执行时在当前指令处抛出的异常 (如向生成器 throw 的异常)
+  fn execute_with(&mut self, base: usize, pending: Option<RuntimeError>) -> Result<Value, RuntimeError> {
+    self.nesting += 1;
+    let result = self.unwind(base, pending);
+    self.nesting -= 1;
+    result
+  }
+
+  /// execute_with 的主体：执行指令，出错时按异常表展开调用栈
+  fn unwind(&mut self, base: usize, mut pending: Option<RuntimeError>) -> Result<Value, RuntimeError> {
+    loop {
+      let result = match pending.take() {
+        Some(err) => Err(err),
+        None => self.dispatch(base),
+      };
+      let err = match result {
+        Ok(value) => return Ok(value),
+        Err(err) => err,
+      };
+      let exc = self.exception(err);
+      // 重新抛出的异常已带有出错位置
+      let mut reraise = self.reraising() && !exc.traceback.borrow().is_empty();
+      loop {
+        let frame = self.frames.last_mut().expect("frame");
+        if !reraise && let Some(span) = frame.code.location(frame.lasti) {
+          exc.traceback.borrow_mut().push((frame.code.name.clone(), span));
+        }
+        reraise = false;
+        if let Some(entry) = frame.code.handler(frame.lasti).copied() {
+          frame.stack.truncate(entry.depth);
+          frame.push(Value::Exception(exc));
+          frame.ip = entry.handler;
+          break;
+        }
+        self.frames.pop();
+        if self.frames.len() == base {
+          return Err(RuntimeError::Exception(exc));
+        }
+      }
+    }
+  }
+
+  /// 主执行循环：调用栈回到 base 层时返回最后一帧的返回值；
+  /// 生成器的帧执行 YieldValue 时留在调用栈上并返回产出的值
+  fn dispatch(&mut self, base: usize) -> Result<Value, RuntimeError> {
     loop {
       // 读取操作码
-      let opcode = OpCode::from(self.frame().read_byte());
+      let frame = self.frame();
+      frame.lasti = frame.ip;
+      let opcode = OpCode::from(frame.read_byte());
       
       match opcode {
         // ============ 常量加载 ============
@@ -64,11 +176,12 @@ impl VM {
           let idx = self.frame().read_u16() as usize;
           let name = self.frame().code.names[idx].clone();
           
-          // 先查局部，再查全局
-          let value = self.globals.borrow()
-            .get(&name)
-            .cloned()
-            .ok_or_else(|| RuntimeError::NameError(name))?;
+          // 类体中先查找类的命名空间；模块级的名字即全局变量 (内置函数也注册在全局变量中)
+          let found = self.frame().namespace.as_ref().and_then(|namespace| namespace.borrow().get(&name).cloned());
+          let value = match found {
+            Some(value) => value,
+            None => self.load_global(name)?,
+          };
           self.frame().push(value);
         }
 
@@ -76,19 +189,97 @@ impl VM {
           let idx = self.frame().read_u16() as usize;
           let name = self.frame().code.names[idx].clone();
           let value = self.frame().pop();
-          self.globals.borrow_mut().insert(name, value);
+          let frame = self.frame();
+          match &frame.namespace {
+            Some(namespace) => namespace.borrow_mut().insert(name, value),
+            None => frame.globals.borrow_mut().insert(name, value),
+          };
         }
 
         OpCode::LoadFast => {
           let idx = self.frame().read_u16() as usize;
-          let value = self.frame().locals[idx].clone();
+          let value = match &self.frame().locals[idx] {
+            Some(value) => value.clone(),
+            None => return Err(self.unbound_local(self.frames.last().expect("frame").code.varnames[idx].clone())),
+          };
           self.frame().push(value);
         }
 
         OpCode::StoreFast => {
           let idx = self.frame().read_u16() as usize;
           let value = self.frame().pop();
-          self.frame().locals[idx] = value;
+          self.frame().locals[idx] = Some(value);
+        }
+
+        OpCode::LoadGlobal => {
+          let idx = self.frame().read_u16() as usize;
+          let name = self.frame().code.names[idx].clone();
+          let value = self.load_global(name)?;
+          self.frame().push(value);
+        }
+
+        OpCode::StoreGlobal => {
+          let idx = self.frame().read_u16() as usize;
+          let name = self.frame().code.names[idx].clone();
+          let value = self.frame().pop();
+          self.frame().globals.borrow_mut().insert(name, value);
+        }
+
+        OpCode::LoadDeref => {
+          let idx = self.frame().read_u16() as usize;
+          let value = self.frame().cells[idx].borrow().clone();
+          match value {
+            Some(value) => self.frame().push(value),
+            None => return Err(self.unbound_deref(idx)),
+          }
+        }
+
+        OpCode::StoreDeref => {
+          let idx = self.frame().read_u16() as usize;
+          let value = self.frame().pop();
+          *self.frame().cells[idx].borrow_mut() = Some(value);
+        }
+
+        OpCode::LoadClosure => {
+          let idx = self.frame().read_u16() as usize;
+          let cell = Rc::clone(&self.frame().cells[idx]);
+          self.frame().push(Value::Cell(cell));
+        }
+
+        // ============ 删除变量 ============
+        OpCode::DeleteName => {
+          let idx = self.frame().read_u16() as usize;
+          let name = self.frame().code.names[idx].clone();
+          let frame = self.frame();
+          let removed = match &frame.namespace {
+            Some(namespace) => namespace.borrow_mut().shift_remove(&name),
+            None => frame.globals.borrow_mut().remove(&name),
+          };
+          if removed.is_none() {
+            return Err(RuntimeError::NameError(format!("name '{}' is not defined", name)));
+          }
+        }
+
+        OpCode::DeleteFast => {
+          let idx = self.frame().read_u16() as usize;
+          if self.frame().locals[idx].take().is_none() {
+            return Err(self.unbound_local(self.frames.last().expect("frame").code.varnames[idx].clone()));
+          }
+        }
+
+        OpCode::DeleteGlobal => {
+          let idx = self.frame().read_u16() as usize;
+          let name = self.frame().code.names[idx].clone();
+          if self.frame().globals.borrow_mut().remove(&name).is_none() {
+            return Err(RuntimeError::NameError(format!("name '{}' is not defined", name)));
+          }
+        }
+
+        OpCode::DeleteDeref => {
+          let idx = self.frame().read_u16() as usize;
+          if self.frame().cells[idx].borrow_mut().take().is_none() {
+            return Err(self.unbound_deref(idx));
+          }
         }
 
         // ============ 栈操作 ============
@@ -101,66 +292,68 @@ impl VM {
           self.frame().push(value);
         }
 
-        // ============ 二元运算 ============
-        OpCode::BinaryAdd => {
-          let right = self.frame().pop();
-          let left = self.frame().pop();
-          let result = self.binary_add(left, right)?;
-          self.frame().push(result);
+        OpCode::Swap => {
+          let len = self.frame().stack.len();
+          self.frame().stack.swap(len - 1, len - 2);
         }
 
-        OpCode::BinarySub => {
-          let right = self.frame().pop();
-          let left = self.frame().pop();
-          let result = self.binary_sub(left, right)?;
-          self.frame().push(result);
+        OpCode::DupTwo => {
+          let len = self.frame().stack.len();
+          let top = self.frame().stack[len - 2..].to_vec();
+          self.frame().stack.extend(top);
+        }
+
+        OpCode::RotThree => {
+          let value = self.frame().pop();
+          let len = self.frame().stack.len();
+          self.frame().stack.insert(len - 2, value);
         }
 
-        OpCode::BinaryMul => {
+        // ============ 二元运算 ============
+        OpCode::BinaryAdd | OpCode::BinarySub | OpCode::BinaryMul | OpCode::BinaryDiv |
+        OpCode::BinaryFloorDiv | OpCode::BinaryMod | OpCode::BinaryPow | OpCode::BinaryMatMul => {
           let right = self.frame().pop();
           let left = self.frame().pop();
-          let result = self.binary_mul(left, right)?;
+          let result = self.binary_op(opcode, left, right)?;
           self.frame().push(result);
         }
 
-        OpCode::BinaryDiv => {
+        OpCode::InplaceOp => {
+          let op = OpCode::from(self.frame().read_u16() as u8);
           let right = self.frame().pop();
           let left = self.frame().pop();
-          let result = self.binary_div(left, right)?;
+          let result = self.inplace_op(op, left, right)?;
           self.frame().push(result);
         }
 
-        // ============ 比较运算 ============
-        OpCode::CompareEq => {
+        // ============ 位运算 ============
+        OpCode::BinaryLShift | OpCode::BinaryRShift |
+        OpCode::BinaryAnd | OpCode::BinaryOr | OpCode::BinaryXor => {
           let right = self.frame().pop();
           let left = self.frame().pop();
-          let result = Value::Bool(self.equals(&left, &right));
+          let result = self.binary_op(opcode, left, right)?;
           self.frame().push(result);
         }
 
-        OpCode::CompareLt => {
+        // ============ 比较运算 ============
+        OpCode::CompareEq | OpCode::CompareNe | OpCode::CompareLt |
+        OpCode::CompareLe | OpCode::CompareGt | OpCode::CompareGe => {
           let right = self.frame().pop();
           let left = self.frame().pop();
-          let result = self.compare_lt(left, right)?;
+          let result = self.compare(opcode, left, right)?;
           self.frame().push(result);
         }
 
         // ============ 一元运算 ============
-        OpCode::UnaryNeg => {
+        OpCode::UnaryNeg | OpCode::UnaryPos | OpCode::UnaryInvert => {
           let value = self.frame().pop();
-          let result = match value {
-              Value::Int(n) => Value::Int(-n),
-              Value::Float(f) => Value::Float(-f),
-              _ => return Err(RuntimeError::TypeError(
-                  format!("bad operand type for unary -: '{}'", value.type_name())
-              )),
-          };
+          let result = self.unary_op(opcode, value)?;
           self.frame().push(result);
         }
 
         OpCode::UnaryNot => {
           let value = self.frame().pop();
-          let result = Value::Bool(!value.is_truthy());
+          let result = Value::Bool(!self.truthy(&value)?);
           self.frame().push(result);
         }
 
@@ -172,14 +365,16 @@ impl VM {
 
         OpCode::JumpIfFalse => {
           let offset = self.frame().read_u16() as usize;
-          if !self.frame().peek().is_truthy() {
+          let value = self.frame().peek().clone();
+          if !self.truthy(&value)? {
             self.frame().ip = offset;
           }
         }
 
         OpCode::JumpIfTrue => {
           let offset = self.frame().read_u16() as usize;
-          if self.frame().peek().is_truthy() {
+          let value = self.frame().peek().clone();
+          if self.truthy(&value)? {
             self.frame().ip = offset;
           }
         }
@@ -191,24 +386,101 @@ impl VM {
 
         // ============ 函数相关 ============
         OpCode::MakeFunction => {
+          let flags = self.frame().read_u16();
           let code = match self.frame().pop() {
-            Value::None => unreachable!(),
-            // 这里需要特殊处理，从常量中获取 CodeObject
-            _ => unreachable!(),
+            Value::Function(func) => func.code.clone(),
+            other => unreachable!("MakeFunction expects a code object, got {}", other.type_name()),
+          };
+          let closure = if flags & 4 != 0 {
+            match self.frame().pop() {
+              Value::Tuple(cells) => cells.iter().map(|cell| match cell {
+                Value::Cell(cell) => Rc::clone(cell),
+                _ => unreachable!("closure items must be cells"),
+              }).collect(),
+              _ => unreachable!("closure must be a tuple"),
+            }
+          } else {
+            Vec::new()
           };
-          // 简化处理，实际需要从栈上获取 CodeObject
+          let kwdefaults = if flags & 2 != 0 {
+            match self.frame().pop() {
+              Value::Dict(dict) => dict.borrow().iter()
+                .map(|(key, value)| (key.value().to_string(), value.clone()))
+                .collect(),
+              _ => unreachable!("keyword-only defaults must be a dict"),
+            }
+          } else {
+            IndexMap::new()
+          };
+          let defaults = if flags & 1 != 0 {
+            match self.frame().pop() {
+              Value::Tuple(items) => items.to_vec(),
+              _ => unreachable!("defaults must be a tuple"),
+            }
+          } else {
+            Vec::new()
+          };
+          let globals = Rc::clone(&self.frame().globals);
+          self.frame().push(Value::Function(Rc::new(Function { code, globals, defaults, kwdefaults, closure })));
+        }
+
+        OpCode::BuildClass => {
+          let count = self.frame().read_u16() as usize;
+          let len = self.frame().stack.len();
+          let bases = self.frame().stack.split_off(len - count);
+          let name = self.frame().pop().to_string();
+          let body = self.frame().pop();
+          let class = self.build_class(name, body, bases)?;
+          self.frame().push(class);
+        }
+
+        OpCode::YieldValue => {
+          let value = self.frame().pop();
+          return Ok(value);
+        }
+
+        OpCode::Send => {
+          let target = self.frame().read_u16() as usize;
+          let value = self.frame().pop();
+          let iter = self.frame().peek().clone();
+          match self.send_into(&iter, value)? {
+            Resumed::Yielded(value) => self.frame().push(value),
+            Resumed::Returned(value) => {
+              let frame = self.frame();
+              frame.pop();
+              frame.push(value);
+              frame.ip = target;
+            },
+          }
         }
 
         OpCode::Call => {
           let argc = self.frame().read_u16() as usize;
-          self.call_function(argc)?;
+          let len = self.frame().stack.len();
+          let args = self.frame().stack.split_off(len - argc);
+          let callee = self.frame().pop();
+          self.call_function(callee, args, Vec::new())?;
+        }
+
+        OpCode::CallKw => {
+          let argc = self.frame().read_u16() as usize;
+          let names = match self.frame().pop() {
+            Value::Tuple(names) => names,
+            _ => unreachable!("CallKw expects a tuple of keyword names"),
+          };
+          let len = self.frame().stack.len();
+          let mut args = self.frame().stack.split_off(len - argc);
+          let values = args.split_off(argc - names.len());
+          let kwargs = names.iter().map(|name| name.to_string()).zip(values).collect();
+          let callee = self.frame().pop();
+          self.call_function(callee, args, kwargs)?;
         }
 
         OpCode::Return => {
           let result = self.frame().pop();
           self.frames.pop();
           
-          if self.frames.is_empty() {
+          if self.frames.len() == base {
               return Ok(result);
           }
           
@@ -229,6 +501,184 @@ impl VM {
           self.frame().push(list);
         }
 
+        OpCode::BuildDict => {
+          let count = self.frame().read_u16() as usize;
+          let len = self.frame().stack.len();
+          let items = self.frame().stack.split_off(len - count * 2);
+          let mut dict = IndexMap::with_capacity(count);
+          for pair in items.chunks(2) {
+              let key = self.hash_key(pair[0].clone(), |hash| colliding(dict.keys(), hash))?;
+              dict.insert(key, pair[1].clone());
+          }
+          self.frame().push(Value::Dict(Rc::new(RefCell::new(dict))));
+        }
+
+        OpCode::BuildSet => {
+          let count = self.frame().read_u16() as usize;
+          let len = self.frame().stack.len();
+          let items = self.frame().stack.split_off(len - count);
+          let mut set = IndexSet::with_capacity(count);
+          for item in items {
+              let item = self.hash_key(item, |hash| colliding(set.iter(), hash))?;
+              set.insert(item);
+          }
+          self.frame().push(Value::Set(Rc::new(RefCell::new(set))));
+        }
+
+        OpCode::BuildTuple => {
+          let count = self.frame().read_u16() as usize;
+          let len = self.frame().stack.len();
+          let items = self.frame().stack.split_off(len - count);
+          self.frame().push(Value::Tuple(Rc::new(items)));
+        }
+
+        OpCode::UnpackEx => {
+          let counts = self.frame().read_u16() as usize;
+          let (before, after) = (counts & 0xFF, counts >> 8);
+          let value = self.frame().pop();
+          let items = self.unpack_ex(value, before, after)?;
+          for item in items.into_iter().rev() {
+              self.frame().push(item);
+          }
+        }
+
+        OpCode::UnpackSequence => {
+          let count = self.frame().read_u16() as usize;
+          let value = self.frame().pop();
+          let items = self.unpack(value, count)?;
+          // 倒序压栈，使第一个元素位于栈顶
+          for item in items.into_iter().rev() {
+              self.frame().push(item);
+          }
+        }
+
+        OpCode::BuildSlice => {
+          let count = self.frame().read_u16();
+          let step = if count == 3 { self.frame().pop() } else { Value::None };
+          let stop = self.frame().pop();
+          let start = self.frame().pop();
+          self.frame().push(Value::Slice(Rc::new(Slice { start, stop, step })));
+        }
+
+        OpCode::BuildString => {
+          let count = self.frame().read_u16() as usize;
+          let mut parts = Vec::with_capacity(count);
+          for _ in 0..count {
+              parts.push(self.frame().pop());
+          }
+          let result: String = parts.iter().rev().map(|part| part.to_string()).collect();
+          self.frame().push(Value::String(Rc::new(result)));
+        }
+
+        OpCode::FormatValue => {
+          let flags = self.frame().read_u16();
+          let spec = if flags & 4 != 0 {
+              self.frame().pop().to_string()
+          } else {
+              String::new()
+          };
+          let value = self.frame().pop();
+          let value = match flags & 3 {
+              1 => Value::String(Rc::new(self.str(&value)?)),
+              2 => Value::String(Rc::new(self.repr(&value)?)),
+              3 => Value::String(Rc::new(format::ascii(&self.repr(&value)?))),
+              // 没有格式说明时即 str(value)
+              _ if spec.is_empty() => Value::String(Rc::new(self.str(&value)?)),
+              _ => value,
+          };
+          let result = format::format_value(&value, &spec)?;
+          self.frame().push(Value::String(Rc::new(result)));
+        }
+
+        OpCode::GetIter => {
+          let value = self.frame().pop();
+          let iter = self.iter(value)?;
+          self.frame().push(iter);
+        }
+
+        OpCode::ForIter => {
+          let target = self.frame().read_u16() as usize;
+          let iter = self.frame().peek().clone();
+          match self.next(&iter)? {
+            Some(value) => self.frame().push(value),
+            None => {
+              self.frame().pop();
+              self.frame().ip = target;
+            },
+          }
+        }
+
+        OpCode::ListAppend => {
+          let depth = self.frame().read_u16() as usize;
+          let item = self.frame().pop();
+          match self.frame().peek_at(depth) {
+            Value::List(list) => list.borrow_mut().push(item),
+            _ => unreachable!("ListAppend expects a list"),
+          }
+        }
+
+        OpCode::ListExtend | OpCode::SetUpdate => {
+          let depth = self.frame().read_u16() as usize;
+          let value = self.frame().pop();
+          let items = self.collect(&value)?.ok_or_else(|| RuntimeError::TypeError(
+            format!("Value after * must be an iterable, not {}", value.type_name())
+          ))?;
+          match self.frame().peek_at(depth).clone() {
+            Value::List(list) => list.borrow_mut().extend(items),
+            Value::Set(set) => {
+              for item in items {
+                let item = self.hash_key(item, |hash| colliding(set.borrow().iter(), hash))?;
+                set.borrow_mut().insert(item);
+              }
+            },
+            _ => unreachable!("{:?} expects a list or set", opcode),
+          }
+        }
+
+        OpCode::SetAdd => {
+          let depth = self.frame().read_u16() as usize;
+          let item = self.frame().pop();
+          let Value::Set(set) = self.frame().peek_at(depth).clone() else {
+            unreachable!("SetAdd expects a set");
+          };
+          let item = self.hash_key(item, |hash| colliding(set.borrow().iter(), hash))?;
+          set.borrow_mut().insert(item);
+        }
+
+        OpCode::MapAdd => {
+          let depth = self.frame().read_u16() as usize;
+          let value = self.frame().pop();
+          let key = self.frame().pop();
+          let Value::Dict(dict) = self.frame().peek_at(depth).clone() else {
+            unreachable!("MapAdd expects a dict");
+          };
+          let key = self.hash_key(key, |hash| colliding(dict.borrow().keys(), hash))?;
+          dict.borrow_mut().insert(key, value);
+        }
+
+        OpCode::DictUpdate => {
+          let depth = self.frame().read_u16() as usize;
+          let value = self.frame().pop();
+          let Value::Dict(other) = value else {
+            return Err(RuntimeError::TypeError(
+              format!("'{}' object is not a mapping", value.type_name())
+            ));
+          };
+          let items: Vec<(HashKey, Value)> = other.borrow().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+          match self.frame().peek_at(depth) {
+            Value::Dict(dict) => dict.borrow_mut().extend(items),
+            _ => unreachable!("DictUpdate expects a dict"),
+          }
+        }
+
+        OpCode::ListToTuple => {
+          let items = match self.frame().pop() {
+            Value::List(list) => list.borrow().clone(),
+            _ => unreachable!("ListToTuple expects a list"),
+          };
+          self.frame().push(Value::Tuple(Rc::new(items)));
+        }
+
         OpCode::BinarySubscr => {
           let index = self.frame().pop();
           let obj = self.frame().pop();
@@ -236,6 +686,73 @@ impl VM {
           self.frame().push(result);
         }
 
+        OpCode::StoreSubscr => {
+          let index = self.frame().pop();
+          let obj = self.frame().pop();
+          let value = self.frame().pop();
+          self.store_subscript(obj, index, value)?;
+        }
+
+        // ============ 异常处理 ============
+        OpCode::Raise => {
+          let argc = self.frame().read_u16();
+          let exc = if argc == 0 {
+            self.frames.iter().rev()
+              .find_map(|frame| frame.handled.last().cloned())
+              .ok_or_else(|| RuntimeError::NativeError("No active exception to reraise".to_string()))?
+          } else {
+            let value = self.frame().pop();
+            self.instantiate_exception(value)?
+          };
+          return Err(RuntimeError::Exception(exc));
+        }
+
+        OpCode::Reraise => {
+          match self.frame().pop() {
+            Value::Exception(exc) => return Err(RuntimeError::Exception(exc)),
+            _ => unreachable!("Reraise expects an exception"),
+          }
+        }
+
+        OpCode::PushExcInfo => {
+          let exc = match self.frame().peek() {
+            Value::Exception(exc) => Rc::clone(exc),
+            _ => unreachable!("PushExcInfo expects an exception"),
+          };
+          self.frame().handled.push(exc);
+        }
+
+        OpCode::PopExcept => {
+          self.frame().handled.pop();
+        }
+
+        OpCode::CheckExcMatch => {
+          let ty = self.frame().pop();
+          let kind = match self.frame().peek() {
+            Value::Exception(exc) => Rc::clone(&exc.kind),
+            _ => unreachable!("CheckExcMatch expects an exception"),
+          };
+          let matched = kind.matches(&ty).map_err(RuntimeError::TypeError)?;
+          self.frame().push(Value::Bool(matched));
+        }
+
+        // ============ 其他 ============
+        OpCode::GetAttr => {
+          let idx = self.frame().read_u16() as usize;
+          let name = self.frame().code.names[idx].clone();
+          let obj = self.frame().pop();
+          let value = self.get_attr(obj, &name)?;
+          self.frame().push(value);
+        }
+
+        OpCode::SetAttr => {
+          let idx = self.frame().read_u16() as usize;
+          let name = self.frame().code.names[idx].clone();
+          let obj = self.frame().pop();
+          let value = self.frame().pop();
+          self.set_attr(obj, &name, value)?;
+        }
+
         _ => {
           return Err(RuntimeError::UnknownOpcode(opcode as u8));
         }
@@ -243,83 +760,507 @@ impl VM {
     }
   }
 
-  fn call_function(&mut self, argc: usize) -> Result<(), RuntimeError> {
-    // 收集参数
-    let mut args = Vec::with_capacity(argc);
-    for _ in 0..argc {
-      args.push(self.frame().pop());
-    }
-    args.reverse();
-    
-    let callee = self.frame().pop();
-    
+  fn call_function(&mut self, callee: Value, args: Vec<Value>, kwargs: Vec<(String, Value)>) -> Result<(), RuntimeError> {
     match callee {
       Value::Function(func) => {
-        let mut frame = Frame::new(func.code.clone(), Rc::clone(&self.globals));
-        
-        // 绑定参数到局部变量
-        for (i, arg) in args.into_iter().enumerate() {
-          frame.locals[i] = arg;
-        }
-        
-        self.frames.push(frame);
+        let locals = bind_arguments(&func, args, kwargs)?;
+        let mut frame = Frame::new(func.code.clone(), Rc::clone(&func.globals));
+        for (slot, value) in frame.locals.iter_mut().zip(locals) {
+          *slot = Some(value);
+        }
+        // 被内层函数引用的参数存放在 cell 中
+        for (cell, name) in frame.cells.iter().zip(&func.code.cellvars) {
+          if let Some(idx) = func.code.varnames.iter().position(|n| n == name) {
+            *cell.borrow_mut() = frame.locals[idx].take();
+          }
+        }
+        frame.cells.extend(func.closure.iter().cloned());
+        if func.code.generator {
+          let name = func.code.name.clone();
+          let generator = Generator { name, frame: Some(frame), running: false };
+          self.frame().push(Value::Generator(Rc::new(RefCell::new(generator))));
+        } else {
+          self.push_frame(frame)?;
+        }
+      }
+      
+      // 创建实例后调用 __init__；异常类型的实例为异常对象，参数记为其 args
+      Value::Class(class) => {
+        let instance = if class.is_exception() {
+          Value::Exception(Rc::new(Exception::new(Rc::clone(&class), args.clone())))
+        } else {
+          Value::Instance(Rc::new(Instance::new(Rc::clone(&class))))
+        };
+        match class.lookup("__init__") {
+          Some(init @ Value::Function(_)) => {
+            let args = std::iter::once(instance.clone()).chain(args).collect();
+            let result = self.call(init, args, kwargs)?;
+            if !matches!(result, Value::None) {
+              return Err(RuntimeError::TypeError(
+                format!("__init__() should return None, not '{}'", result.type_name())
+              ));
+            }
+          }
+          _ if !kwargs.is_empty() => {
+            return Err(RuntimeError::TypeError(
+              format!("{}() takes no keyword arguments", class.name)
+            ));
+          }
+          _ if !args.is_empty() && !class.is_exception() => {
+            return Err(RuntimeError::TypeError(
+              format!("{}() takes no arguments", class.name)
+            ));
+          }
+          _ => {}
+        }
+        self.frame().push(instance);
+      }
+
+      Value::Method(method) => {
+        let args = std::iter::once(method.receiver.clone()).chain(args).collect();
+        self.call_function(method.func.clone(), args, kwargs)?;
       }
       
       Value::NativeFunction(native) => {
-        let result = (native.func)(args)
-          .map_err(RuntimeError::NativeError)?;
+        if !kwargs.is_empty() {
+          return Err(RuntimeError::TypeError(
+            format!("{}() takes no keyword arguments", native.name)
+          ));
+        }
+        let result = (native.func)(self, args)?;
         self.frame().push(result);
       }
       
-      _ => {
-        return Err(RuntimeError::TypeError(
-          format!("'{}' object is not callable", callee.type_name())
-        ));
-      }
+      // 定义了 __call__ 的实例
+      _ => match callee.class().and_then(|class| class.lookup("__call__")) {
+        Some(method) => {
+          let args = std::iter::once(callee).chain(args).collect();
+          self.call_function(method, args, kwargs)?;
+        }
+        None => {
+          return Err(RuntimeError::TypeError(
+            format!("'{}' object is not callable", callee.type_name())
+          ));
+        }
+      },
     }
     
     Ok(())
   }
 
-  fn constant_to_value(&self, constant: Constant) -> Value {
-    match constant {
-      Constant::None => Value::None,
-      Constant::Bool(b) => Value::Bool(b),
-      Constant::Int(n) => Value::Int(n),
-      Constant::Float(f) => Value::Float(f),
-      Constant::String(s) => Value::String(Rc::new(s)),
-      Constant::Code(code) => {
-        Value::Function(Rc::new(Function {
-          code: *code,
-          globals: Rc::clone(&self.globals),
-        }))
-      }
+  /// 调用 callee 并执行到返回，得到调用的结果 (用于虚拟机发起的调用，如 __init__)
+  fn call(&mut self, callee: Value, args: Vec<Value>, kwargs: Vec<(String, Value)>) -> Result<Value, RuntimeError> {
+    let base = self.frames.len();
+    self.call_function(callee, args, kwargs)?;
+    if self.frames.len() > base {
+      self.execute(base)
+    } else {
+      Ok(self.frame().pop())
     }
   }
 
-  // 辅助方法
-  fn binary_add(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
-    match (left, right) {
-      (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a + b)),
-      (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a + b)),
-      (Value::Int(a), Value::Float(b)) => Ok(Value::Float(a as f64 + b)),
-      (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a + b as f64)),
-      (Value::String(a), Value::String(b)) => {
-        Ok(Value::String(Rc::new(format!("{}{}", a, b))))
-      },
-      (l, r) => Err(RuntimeError::TypeError(
-        format!(
-          "unsupported operand type(s) for +: '{}' and '{}'", 
-          l.type_name(), 
-          r.type_name(),
-        )
-      )),
+  /// 调用 value 的类中定义的特殊方法 (如 __len__)，value 作为第一个参数；
+  /// value 不是实例或类中没有该方法时返回 None
+  fn call_special(&mut self, value: &Value, name: &str, args: Vec<Value>) -> Result<Option<Value>, RuntimeError> {
+    let Some(method) = value.class().and_then(|class| class.lookup(name)) else {
+      return Ok(None);
+    };
+    let args = std::iter::once(value.clone()).chain(args).collect();
+    self.call(method, args, Vec::new()).map(Some)
+  }
+
+  /// 在新的命名空间中执行类体函数，再以命名空间中的名字为属性创建类；
+  /// 类体留下的 __classcell__ 即方法中 super() 使用的 __class__ cell。
+  /// 定义了 __eq__ 而没有定义 __hash__ 的类的实例不可哈希
+  fn build_class(&mut self, name: String, body: Value, bases: Vec<Value>) -> Result<Value, RuntimeError> {
+    let Value::Function(body) = body else {
+      unreachable!("BuildClass expects a function");
+    };
+    let mut classes = Vec::with_capacity(bases.len());
+    for base in bases {
+      match base {
+        Value::Class(class) => classes.push(class),
+        other => return Err(RuntimeError::TypeError(
+          format!("bases must be types, not '{}'", other.type_name())
+        )),
+      }
+    }
+    if classes.is_empty() {
+      classes.push(Rc::clone(&self.object));
+    }
+
+    let namespace = Rc::new(RefCell::new(IndexMap::new()));
+    let mut frame = Frame::new(body.code.clone(), Rc::clone(&body.globals));
+    frame.cells.extend(body.closure.iter().cloned());
+    frame.namespace = Some(Rc::clone(&namespace));
+    let base = self.frames.len();
+    self.push_frame(frame)?;
+    self.execute(base)?;
+
+    let mut attrs = namespace.take();
+    let cell = attrs.shift_remove("__classcell__");
+    if attrs.contains_key("__eq__") && !attrs.contains_key("__hash__") {
+      attrs.insert("__hash__".to_string(), Value::None);
+    }
+    let class = Class::new(name, classes, attrs).map_err(RuntimeError::TypeError)?;
+    if let Some(Value::Cell(cell)) = cell {
+      *cell.borrow_mut() = Some(Value::Class(Rc::clone(&class)));
     }
+    Ok(Value::Class(class))
   }
 
-  fn binary_sub(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
-    match (left, right) {
-      (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a - b)),
+  /// 实例属性优先于类属性；通过实例或 super() 取得的类中的函数绑定为方法
+  fn get_attr(&self, obj: Value, name: &str) -> Result<Value, RuntimeError> {
+    let bind = |value: Value, receiver: &Value| match value {
+      Value::Function(_) | Value::NativeFunction(_) => {
+        Value::Method(Rc::new(Method { func: value, receiver: receiver.clone() }))
+      },
+      value => value,
+    };
+    let found = match &obj {
+      Value::Instance(instance) => instance.attrs.borrow().get(name).cloned()
+        .or_else(|| instance.class.lookup(name).map(|value| bind(value, &obj))),
+      Value::Exception(exc) if name == "args" => Some(Value::Tuple(Rc::new(exc.args.borrow().clone()))),
+      Value::Exception(exc) if name == "value" && exc.kind.is_subclass(&self.exception_types["StopIteration"]) => {
+        Some(stop_iteration_value(exc))
+      },
+      Value::Exception(exc) => exc.attrs.borrow().get(name).cloned()
+        .or_else(|| exc.kind.lookup(name).map(|value| bind(value, &obj))),
+      Value::Class(class) => match name {
+        "__name__" => Some(Value::String(Rc::new(class.name.clone()))),
+        "__mro__" => Some(Value::Tuple(Rc::new(
+          std::iter::once(class).chain(&class.mro).map(|class| Value::Class(Rc::clone(class))).collect()
+        ))),
+        _ => class.lookup(name),
+      },
+      Value::Super(sup) => {
+        let class = sup.obj.class().expect("super() checks the instance");
+        class.lookup_after(&sup.class, name).map(|value| bind(value, &sup.obj))
+      },
+      Value::Generator(_) => builtins::generator_method(name).map(|value| bind(value, &obj)),
+      _ => None,
+    };
+    found.ok_or_else(|| RuntimeError::AttributeError(match &obj {
+      Value::Class(class) => format!("type object '{}' has no attribute '{}'", class.name, name),
+      _ => format!("'{}' object has no attribute '{}'", obj.type_name(), name),
+    }))
+  }
+
+  /// 设置实例、异常对象或类的属性
+  fn set_attr(&self, obj: Value, name: &str, value: Value) -> Result<(), RuntimeError> {
+    let attrs = match &obj {
+      Value::Instance(instance) => &instance.attrs,
+      Value::Exception(exc) => &exc.attrs,
+      Value::Class(class) => &class.attrs,
+      _ => return Err(RuntimeError::AttributeError(
+        format!("'{}' object has no attribute '{}'", obj.type_name(), name)
+      )),
+    };
+    attrs.borrow_mut().insert(name.to_string(), value);
+    Ok(())
+  }
+
+  /// 将运行时错误转换为对应内置类型的异常对象，错误消息作为唯一的参数
+  fn exception(&self, err: RuntimeError) -> Rc<Exception> {
+    let args = match &err {
+      RuntimeError::Exception(exc) => return Rc::clone(exc),
+      RuntimeError::KeyError(key) => vec![key.clone()],
+      RuntimeError::StopIteration(Value::None) => Vec::new(),
+      RuntimeError::StopIteration(value) => vec![value.clone()],
+      _ => vec![Value::String(Rc::new(err.message()))],
+    };
+    let ty = Rc::clone(&self.exception_types[err.name()]);
+    Rc::new(Exception::new(ty, args))
+  }
+
+  /// raise 或 throw() 的异常：异常类型不带参数地实例化
+  fn instantiate_exception(&mut self, value: Value) -> Result<Rc<Exception>, RuntimeError> {
+    match value {
+      Value::Exception(exc) => Ok(exc),
+      Value::Class(class) if class.is_exception() => match self.call(Value::Class(class), Vec::new(), Vec::new())? {
+        Value::Exception(exc) => Ok(exc),
+        _ => unreachable!("exception class instantiates an exception"),
+      },
+      _ => Err(RuntimeError::TypeError("exceptions must derive from BaseException".to_string())),
+    }
+  }
+
+  /// 表示迭代结束的错误 (StopIteration 或其子类的异常) 携带的返回值；其他错误返回 None
+  fn stop_value(&self, err: &RuntimeError) -> Option<Value> {
+    match err {
+      RuntimeError::StopIteration(value) => Some(value.clone()),
+      RuntimeError::Exception(exc) if exc.kind.is_subclass(&self.exception_types["StopIteration"]) => {
+        Some(stop_iteration_value(exc))
+      },
+      _ => None,
+    }
+  }
+
+  /// 出错的指令是否为重新抛出 (Reraise 或不带参数的 Raise)
+  fn reraising(&self) -> bool {
+    let frame = self.frames.last().expect("frame");
+    let code = &frame.code.code;
+    match OpCode::from(code[frame.lasti]) {
+      OpCode::Reraise => true,
+      OpCode::Raise => code[frame.lasti + 1..frame.lasti + 3] == [0, 0],
+      _ => false,
+    }
+  }
+
+  /// 查找全局变量 (含内置函数)
+  fn load_global(&mut self, name: String) -> Result<Value, RuntimeError> {
+    self.frame().globals.borrow()
+      .get(&name)
+      .cloned()
+      .ok_or_else(|| RuntimeError::NameError(format!("name '{}' is not defined", name)))
+  }
+
+  fn unbound_local(&self, name: String) -> RuntimeError {
+    RuntimeError::UnboundLocalError(
+      format!("cannot access local variable '{}' where it is not associated with a value", name)
+    )
+  }
+
+  /// 读取尚未赋值的 cell：本帧的 cell 是局部变量，捕获的 cell 是外层的自由变量
+  fn unbound_deref(&self, idx: usize) -> RuntimeError {
+    let code = &self.frames.last().expect("frame").code;
+    match code.cellvars.get(idx) {
+      Some(name) => self.unbound_local(name.clone()),
+      None => RuntimeError::NameError(format!(
+        "cannot access free variable '{}' where it is not associated with a value in enclosing scope",
+        code.freevars[idx - code.cellvars.len()],
+      )),
+    }
+  }
+
+  fn constant_to_value(&self, constant: Constant) -> Value {
+    match constant {
+      Constant::None => Value::None,
+      Constant::Bool(b) => Value::Bool(b),
+      Constant::Int(n) => Value::Int(n),
+      Constant::Float(f) => Value::Float(f),
+      Constant::String(s) => Value::String(Rc::new(s)),
+      Constant::Bytes(b) => Value::Bytes(Rc::new(b)),
+      Constant::Code(code) => {
+        Value::Function(Rc::new(Function {
+          code: *code,
+          globals: Rc::clone(&self.globals),
+          defaults: Vec::new(),
+          kwdefaults: IndexMap::new(),
+          closure: Vec::new(),
+        }))
+      }
+    }
+  }
+
+  // 辅助方法
+  /// 二元运算：内置类型直接计算，有实例参与时调用特殊方法
+  fn binary_op(&mut self, op: OpCode, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    let result = if left.class().is_none() && right.class().is_none() {
+      match op {
+        OpCode::BinaryAdd => return self.binary_add(left, right),
+        OpCode::BinarySub => return self.binary_sub(left, right),
+        OpCode::BinaryMul => return self.binary_mul(left, right),
+        OpCode::BinaryDiv => return self.binary_div(left, right),
+        OpCode::BinaryFloorDiv => return self.binary_floor_div(left, right),
+        OpCode::BinaryMod => return self.binary_mod(left, right),
+        OpCode::BinaryPow => return self.binary_pow(left, right),
+        // 内置类型都不支持矩阵乘法
+        OpCode::BinaryMatMul => None,
+        _ => return self.binary_bitwise(op, left, right),
+      }
+    } else {
+      self.binary_special(op, &left, &right)?
+    };
+    result.ok_or_else(|| RuntimeError::TypeError(
+      format!(
+        "unsupported operand type(s) for {}: '{}' and '{}'",
+        binary_dunder(op).0,
+        left.type_name(),
+        right.type_name(),
+      )
+    ))
+  }
+
+  /// 增量赋值：先调用左操作数的就地运算方法 (如 __iadd__)，未定义或返回 NotImplemented 时按二元运算计算
+  fn inplace_op(&mut self, op: OpCode, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    let name = format!("__i{}", &binary_dunder(op).1[2..]);
+    match self.call_special(&left, &name, vec![right.clone()])? {
+      Some(Value::NotImplemented) | None => self.binary_op(op, left, right),
+      Some(result) => Ok(result),
+    }
+  }
+
+  /// 调用二元运算的特殊方法：先调用左操作数的方法，未定义或返回 NotImplemented 时调用右操作数的反射方法，
+  /// 右操作数的类是左操作数的类的真子类时先调用反射方法；两个操作数同类时算术运算不反射。
+  /// 都不支持时返回 None
+  fn binary_special(&mut self, op: OpCode, left: &Value, right: &Value) -> Result<Option<Value>, RuntimeError> {
+    let (_, name, reflected) = binary_dunder(op);
+    let compare = matches!(
+      op,
+      OpCode::CompareEq | OpCode::CompareNe | OpCode::CompareLt | OpCode::CompareLe | OpCode::CompareGt | OpCode::CompareGe
+    );
+    let (same, subclass) = match (left.class(), right.class()) {
+      (Some(a), Some(b)) if Rc::ptr_eq(a, b) => (true, false),
+      (Some(a), Some(b)) => (false, b.is_subclass(a)),
+      _ => (false, false),
+    };
+    let mut attempts = vec![(left, right, name)];
+    if compare || !same {
+      attempts.push((right, left, reflected));
+    }
+    if subclass {
+      attempts.reverse();
+    }
+    for (receiver, other, method) in attempts {
+      match self.call_special(receiver, method, vec![other.clone()])? {
+        Some(Value::NotImplemented) | None => {},
+        result => return Ok(result),
+      }
+    }
+    Ok(None)
+  }
+
+  /// 比较运算：有实例参与时调用 __eq__、__lt__ 等及其反射方法；
+  /// 都不支持时 == 与 != 比较身份 (!= 先尝试对 __eq__ 的结果取反)，有序比较抛出 TypeError
+  fn compare(&mut self, op: OpCode, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    if left.class().is_none() && right.class().is_none() {
+      return match op {
+        OpCode::CompareEq => Ok(Value::Bool(self.equals(&left, &right)?)),
+        OpCode::CompareNe => Ok(Value::Bool(!self.equals(&left, &right)?)),
+        _ => self.compare_order(op, left, right),
+      };
+    }
+    if let Some(result) = self.binary_special(op, &left, &right)? {
+      return Ok(result);
+    }
+    match op {
+      OpCode::CompareEq => Ok(Value::Bool(left == right)),
+      OpCode::CompareNe => match self.binary_special(OpCode::CompareEq, &left, &right)? {
+        Some(result) => Ok(Value::Bool(!self.truthy(&result)?)),
+        None => Ok(Value::Bool(left != right)),
+      },
+      _ => Err(RuntimeError::TypeError(
+        format!(
+          "'{}' not supported between instances of '{}' and '{}'",
+          binary_dunder(op).0,
+          left.type_name(),
+          right.type_name(),
+        )
+      )),
+    }
+  }
+
+  /// 内置值的 ==：list、tuple、dict 逐个比较元素 (元素可以是定义了 __eq__ 的实例)，同一个容器直接相等；
+  /// 每一层嵌套计入 nesting，相互引用的容器比较过深时抛出 RecursionError
+  fn equals(&mut self, left: &Value, right: &Value) -> Result<bool, RuntimeError> {
+    let pairs: Vec<(Value, Value)> = match (left, right) {
+      (Value::List(a), Value::List(b)) => {
+        if Rc::ptr_eq(a, b) {
+          return Ok(true);
+        }
+        let (a, b) = (a.borrow(), b.borrow());
+        if a.len() != b.len() {
+          return Ok(false);
+        }
+        a.iter().cloned().zip(b.iter().cloned()).collect()
+      },
+      (Value::Tuple(a), Value::Tuple(b)) => {
+        if Rc::ptr_eq(a, b) {
+          return Ok(true);
+        }
+        if a.len() != b.len() {
+          return Ok(false);
+        }
+        a.iter().cloned().zip(b.iter().cloned()).collect()
+      },
+      (Value::Dict(a), Value::Dict(b)) => {
+        if Rc::ptr_eq(a, b) {
+          return Ok(true);
+        }
+        let (a, b) = (a.borrow(), b.borrow());
+        if a.len() != b.len() {
+          return Ok(false);
+        }
+        let mut pairs = Vec::with_capacity(a.len());
+        for (key, x) in a.iter() {
+          match b.get(key) {
+            Some(y) => pairs.push((x.clone(), y.clone())),
+            None => return Ok(false),
+          }
+        }
+        pairs
+      },
+      _ => return Ok(left == right),
+    };
+    if self.too_deep() {
+      return Err(RuntimeError::RecursionError);
+    }
+    self.nesting += 1;
+    let result = self.all_equal(pairs);
+    self.nesting -= 1;
+    result
+  }
+
+  fn all_equal(&mut self, pairs: Vec<(Value, Value)>) -> Result<bool, RuntimeError> {
+    for (x, y) in pairs {
+      let equal = self.compare(OpCode::CompareEq, x, y)?;
+      if !self.truthy(&equal)? {
+        return Ok(false);
+      }
+    }
+    Ok(true)
+  }
+
+  /// 一元运算 (-x, +x, ~x)：实例调用 __neg__、__pos__、__invert__
+  fn unary_op(&mut self, op: OpCode, value: Value) -> Result<Value, RuntimeError> {
+    let (symbol, name) = match op {
+      OpCode::UnaryNeg => ("-", "__neg__"),
+      OpCode::UnaryPos => ("+", "__pos__"),
+      _ => ("~", "__invert__"),
+    };
+    if let Some(result) = self.call_special(&value, name, Vec::new())? {
+      return Ok(result);
+    }
+    match (op, &value) {
+      (OpCode::UnaryNeg, Value::Int(n)) => n.checked_neg()
+        .map(Value::Int)
+        .ok_or_else(|| RuntimeError::OverflowError("integer overflow in unary -".to_string())),
+      (OpCode::UnaryNeg, Value::Float(f)) => Ok(Value::Float(-f)),
+      (OpCode::UnaryPos, Value::Int(_) | Value::Float(_)) => Ok(value.clone()),
+      (OpCode::UnaryInvert, Value::Int(n)) => Ok(Value::Int(!n)),
+      _ => Err(RuntimeError::TypeError(
+        format!("bad operand type for unary {}: '{}'", symbol, value.type_name())
+      )),
+    }
+  }
+
+  fn binary_add(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    match (left, right) {
+      (Value::Int(a), Value::Int(b)) => a.checked_add(b)
+        .map(Value::Int)
+        .ok_or_else(|| RuntimeError::OverflowError("integer overflow in +".to_string())),
+      (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a + b)),
+      (Value::Int(a), Value::Float(b)) => Ok(Value::Float(a as f64 + b)),
+      (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a + b as f64)),
+      (Value::String(a), Value::String(b)) => {
+        Ok(Value::String(Rc::new(format!("{}{}", a, b))))
+      },
+      (l, r) => Err(RuntimeError::TypeError(
+        format!(
+          "unsupported operand type(s) for +: '{}' and '{}'", 
+          l.type_name(), 
+          r.type_name(),
+        )
+      )),
+    }
+  }
+
+  fn binary_sub(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    match (left, right) {
+      (Value::Int(a), Value::Int(b)) => a.checked_sub(b)
+        .map(Value::Int)
+        .ok_or_else(|| RuntimeError::OverflowError("integer overflow in -".to_string())),
       (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a - b)),
       (Value::Int(a), Value::Float(b)) => Ok(Value::Float(a as f64 - b)),
       (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a - b as f64)),
@@ -335,7 +1276,9 @@ impl VM {
 
   fn binary_mul(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
     match (left, right) {
-      (Value::Int(a), Value::Int(b)) => Ok(Value::Int(a * b)),
+      (Value::Int(a), Value::Int(b)) => a.checked_mul(b)
+        .map(Value::Int)
+        .ok_or_else(|| RuntimeError::OverflowError("integer overflow in *".to_string())),
       (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a * b)),
       (Value::Int(a), Value::Float(b)) => Ok(Value::Float(a as f64 * b)),
       (Value::Float(a), Value::Int(b)) => Ok(Value::Float(a * b as f64)),
@@ -356,14 +1299,9 @@ impl VM {
   }
 
   fn binary_div(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
-    match (&left, &right) {
-      (Value::Int(a), Value::Int(b)) => {
-        if *b == 0 { return Err(RuntimeError::ZeroDivision); }
-        Ok(Value::Float(*a as f64 / *b as f64))
-      },
-      
-      (Value::Float(a), Value::Float(b)) => {
-        if *b == 0.0 { return Err(RuntimeError::ZeroDivision); }
+    match (left.as_float(), right.as_float()) {
+      (Some(a), Some(b)) => {
+        if b == 0.0 { return Err(RuntimeError::ZeroDivision); }
         Ok(Value::Float(a / b))
       },
       
@@ -377,68 +1315,1654 @@ impl VM {
     }
   }
 
-  fn compare_lt(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
-    match (left, right) {
-      (Value::Int(a), Value::Int(b)) => Ok(Value::Bool(a < b)),
-      (Value::Float(a), Value::Float(b)) => Ok(Value::Bool(a < b)),
-      (l, r) => Err(RuntimeError::TypeError(
+  fn binary_floor_div(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    match (&left, &right) {
+      (Value::Int(a), Value::Int(b)) => {
+        if *b == 0 { return Err(RuntimeError::ZeroDivision); }
+        let q = a.checked_div(*b)
+          .ok_or_else(|| RuntimeError::OverflowError("integer overflow in //".to_string()))?;
+        // 向负无穷取整
+        if a % b != 0 && ((*a < 0) != (*b < 0)) {
+          Ok(Value::Int(q - 1))
+        } else {
+          Ok(Value::Int(q))
+        }
+      },
+      _ => match (left.as_float(), right.as_float()) {
+        (Some(a), Some(b)) => {
+          if b == 0.0 { return Err(RuntimeError::ZeroDivision); }
+          Ok(Value::Float((a / b).floor()))
+        },
+        _ => Err(RuntimeError::TypeError(
+          format!(
+            "unsupported operand type(s) for //: '{}' and '{}'", 
+            left.type_name(), 
+            right.type_name(),
+          )
+        )),
+      },
+    }
+  }
+
+  fn binary_mod(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    match (&left, &right) {
+      (Value::Int(a), Value::Int(b)) => {
+        if *b == 0 { return Err(RuntimeError::ZeroDivision); }
+        // 结果与除数同号；只有 i64::MIN % -1 溢出，其余数为 0
+        let r = a.checked_rem(*b).unwrap_or(0);
+        if r != 0 && ((r < 0) != (*b < 0)) {
+          Ok(Value::Int(r + b))
+        } else {
+          Ok(Value::Int(r))
+        }
+      },
+      _ => match (left.as_float(), right.as_float()) {
+        (Some(a), Some(b)) => {
+          if b == 0.0 { return Err(RuntimeError::ZeroDivision); }
+          let r = a % b;
+          if r != 0.0 && ((r < 0.0) != (b < 0.0)) {
+            Ok(Value::Float(r + b))
+          } else {
+            Ok(Value::Float(r))
+          }
+        },
+        _ => Err(RuntimeError::TypeError(
+          format!(
+            "unsupported operand type(s) for %: '{}' and '{}'", 
+            left.type_name(), 
+            right.type_name(),
+          )
+        )),
+      },
+    }
+  }
+
+  fn binary_pow(&self, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    match (&left, &right) {
+      (Value::Int(a), Value::Int(b)) if *b >= 0 => {
+        let exp = u32::try_from(*b)
+          .map_err(|_| RuntimeError::OverflowError("exponent too large".to_string()))?;
+        a.checked_pow(exp)
+          .map(Value::Int)
+          .ok_or_else(|| RuntimeError::OverflowError("integer overflow in **".to_string()))
+      },
+      _ => match (left.as_float(), right.as_float()) {
+        (Some(a), Some(b)) => {
+          if a == 0.0 && b < 0.0 { return Err(RuntimeError::ZeroDivision); }
+          Ok(Value::Float(a.powf(b)))
+        },
+        _ => Err(RuntimeError::TypeError(
+          format!(
+            "unsupported operand type(s) for ** or pow(): '{}' and '{}'", 
+            left.type_name(), 
+            right.type_name(),
+          )
+        )),
+      },
+    }
+  }
+
+  fn binary_bitwise(&self, op: OpCode, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    let symbol = match op {
+      OpCode::BinaryLShift => "<<",
+      OpCode::BinaryRShift => ">>",
+      OpCode::BinaryAnd => "&",
+      OpCode::BinaryOr => "|",
+      _ => "^",
+    };
+    let (a, b) = match (&left, &right) {
+      (Value::Int(a), Value::Int(b)) => (*a, *b),
+      _ => return Err(RuntimeError::TypeError(
         format!(
-          "'<' not supported between '{}' and '{}'",
-          l.type_name(), 
-          r.type_name(),
+          "unsupported operand type(s) for {}: '{}' and '{}'", 
+          symbol,
+          left.type_name(), 
+          right.type_name(),
         )
       )),
-    }
+    };
+    let result = match op {
+      OpCode::BinaryLShift | OpCode::BinaryRShift if b < 0 => {
+        return Err(RuntimeError::ValueError("negative shift count".to_string()));
+      },
+      OpCode::BinaryLShift => {
+        let shifted = if b >= 64 { 0 } else { a << b };
+        if a != 0 && (b >= 64 || shifted >> b != a) {
+          return Err(RuntimeError::OverflowError("integer overflow in <<".to_string()));
+        }
+        shifted
+      },
+      OpCode::BinaryRShift => a >> b.min(63),
+      OpCode::BinaryAnd => a & b,
+      OpCode::BinaryOr => a | b,
+      _ => a ^ b,
+    };
+    Ok(Value::Int(result))
   }
 
-  fn equals(&self, left: &Value, right: &Value) -> bool {
-    match (left, right) {
-      (Value::None, Value::None) => true,
-      (Value::Bool(a), Value::Bool(b)) => a == b,
-      (Value::Int(a), Value::Int(b)) => a == b,
-      (Value::Float(a), Value::Float(b)) => a == b,
-      (Value::String(a), Value::String(b)) => a == b,
-      _ => false,
-    }
+  /// 有序比较 (<, <=, >, >=)
+  fn compare_order(&self, op: OpCode, left: Value, right: Value) -> Result<Value, RuntimeError> {
+    let symbol = match op {
+      OpCode::CompareLt => "<",
+      OpCode::CompareLe => "<=",
+      OpCode::CompareGt => ">",
+      _ => ">=",
+    };
+    let ordering = match (&left, &right) {
+      (Value::Int(a), Value::Int(b)) => a.partial_cmp(b),
+      (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+      _ => match (left.as_float(), right.as_float()) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        _ => return Err(RuntimeError::TypeError(
+          format!(
+            "'{}' not supported between instances of '{}' and '{}'",
+            symbol,
+            left.type_name(), 
+            right.type_name(),
+          )
+        )),
+      },
+    };
+    // NaN 参与的比较均为 false
+    let result = match ordering {
+      None => false,
+      Some(ord) => match op {
+        OpCode::CompareLt => ord.is_lt(),
+        OpCode::CompareLe => ord.is_le(),
+        OpCode::CompareGt => ord.is_gt(),
+        _ => ord.is_ge(),
+      },
+    };
+    Ok(Value::Bool(result))
   }
 
-  fn subscript(&self, obj: Value, index: Value) -> Result<Value, RuntimeError> {
+  /// obj[index]：实例调用 __getitem__
+  fn subscript(&mut self, obj: Value, index: Value) -> Result<Value, RuntimeError> {
+    if let Some(result) = self.call_special(&obj, "__getitem__", vec![index.clone()])? {
+      return Ok(result);
+    }
     match (obj, index) {
       (Value::List(list), Value::Int(i)) => {
         let list = list.borrow();
-        let idx = if i < 0 {
-          (list.len() as i64 + i) as usize
-        } else {
-          i as usize
-        };
-        list.get(idx).cloned()
+        normalize_index(i, list.len())
+          .map(|idx| list[idx].clone())
+          .ok_or(RuntimeError::IndexError)
+      },
+      
+      (Value::Tuple(items), Value::Int(i)) => {
+        normalize_index(i, items.len())
+          .map(|idx| items[idx].clone())
           .ok_or(RuntimeError::IndexError)
       },
       
       (Value::String(s), Value::Int(i)) => {
-        let idx = if i < 0 {
-          (s.len() as i64 + i) as usize
-        } else {
-          i as usize
-        };
-        s.chars().nth(idx)
+        normalize_index(i, s.chars().count())
+          .and_then(|idx| s.chars().nth(idx))
           .map(|c| Value::String(Rc::new(c.to_string())))
           .ok_or(RuntimeError::IndexError)
       },
       
+      (Value::Dict(dict), key) => {
+        let hash_key = self.hash_key(key.clone(), |hash| colliding(dict.borrow().keys(), hash))?;
+        dict.borrow().get(&hash_key).cloned()
+          .ok_or(RuntimeError::KeyError(key))
+      },
+      
+      (Value::List(list), Value::Slice(slice)) => {
+        let list = list.borrow();
+        let items = slice_indices(&slice, list.len())?.map(|idx| list[idx].clone()).collect();
+        Ok(Value::List(Rc::new(RefCell::new(items))))
+      },
+      
+      (Value::Tuple(items), Value::Slice(slice)) => {
+        let items = slice_indices(&slice, items.len())?.map(|idx| items[idx].clone()).collect();
+        Ok(Value::Tuple(Rc::new(items)))
+      },
+      
+      (Value::String(s), Value::Slice(slice)) => {
+        let chars: Vec<char> = s.chars().collect();
+        let result = slice_indices(&slice, chars.len())?.map(|idx| chars[idx]).collect();
+        Ok(Value::String(Rc::new(result)))
+      },
+      
+      (obj @ (Value::List(_) | Value::Tuple(_)), index) => Err(RuntimeError::TypeError(
+        format!("{} indices must be integers or slices, not {}", obj.type_name(), index.type_name())
+      )),
+      
+      (Value::String(_), index) => Err(RuntimeError::TypeError(
+        format!("string indices must be integers, not '{}'", index.type_name())
+      )),
+      
       (obj, _) => Err(RuntimeError::TypeError(
         format!("'{}' object is not subscriptable", obj.type_name())
       )),
     }
   }
+
+  /// obj[index] = value：实例调用 __setitem__
+  fn store_subscript(&mut self, obj: Value, index: Value, value: Value) -> Result<(), RuntimeError> {
+    if self.call_special(&obj, "__setitem__", vec![index.clone(), value.clone()])?.is_some() {
+      return Ok(());
+    }
+    match (obj, index) {
+      (Value::List(list), Value::Int(i)) => {
+        let mut list = list.borrow_mut();
+        let idx = normalize_index(i, list.len()).ok_or(RuntimeError::IndexError)?;
+        list[idx] = value;
+        Ok(())
+      },
+      
+      (Value::List(list), Value::Slice(slice)) => {
+        // 先取出所有元素，右侧可能就是 list 本身
+        let values = self.collect(&value)?.ok_or_else(|| RuntimeError::TypeError(
+          if matches!(slice.step, Value::None | Value::Int(1)) {
+            "can only assign an iterable"
+          } else {
+            "must assign iterable to extended slice"
+          }.to_string()
+        ))?;
+        let mut list = list.borrow_mut();
+        let (start, stop, step) = adjust_slice(&slice, list.len())?;
+        if step == 1 {
+          let (start, stop) = (start as usize, stop.max(start) as usize);
+          list.splice(start..stop, values);
+          return Ok(());
+        }
+        let indices: Vec<usize> = slice_indices(&slice, list.len())?.collect();
+        if indices.len() != values.len() {
+          return Err(RuntimeError::ValueError(format!(
+            "attempt to assign sequence of size {} to extended slice of size {}",
+            values.len(),
+            indices.len(),
+          )));
+        }
+        for (idx, value) in indices.into_iter().zip(values) {
+          list[idx] = value;
+        }
+        Ok(())
+      },
+      
+      (Value::List(_), index) => Err(RuntimeError::TypeError(
+        format!("list indices must be integers or slices, not {}", index.type_name())
+      )),
+      
+      (Value::Dict(dict), key) => {
+        let key = self.hash_key(key, |hash| colliding(dict.borrow().keys(), hash))?;
+        dict.borrow_mut().insert(key, value);
+        Ok(())
+      },
+      
+      (obj, _) => Err(RuntimeError::TypeError(
+        format!("'{}' object does not support item assignment", obj.type_name())
+      )),
+    }
+  }
+
+  /// 取得 value 的迭代器，迭代器本身原样返回；不可迭代时返回 None
+  fn get_iter(&self, value: &Value) -> Option<Rc<RefCell<Iter>>> {
+    let iter = match value {
+      Value::List(list) => Iter::List(Rc::clone(list), 0),
+      Value::Tuple(items) => Iter::Items(items.iter().cloned().collect::<Vec<_>>().into_iter()),
+      Value::String(s) => Iter::Items(
+        s.chars().map(|c| Value::String(Rc::new(c.to_string()))).collect::<Vec<_>>().into_iter()
+      ),
+      Value::Dict(dict) => Iter::Items(
+        dict.borrow().keys().map(|key| key.value().clone()).collect::<Vec<_>>().into_iter()
+      ),
+      Value::Set(set) => Iter::Items(
+        set.borrow().iter().map(|item| item.value().clone()).collect::<Vec<_>>().into_iter()
+      ),
+      Value::Range { start, stop, step } => Iter::Range { next: *start, stop: *stop, step: *step },
+      Value::Iterator(iter) => return Some(Rc::clone(iter)),
+      _ => return None,
+    };
+    Some(Rc::new(RefCell::new(iter)))
+  }
+
+  /// 取出生成器挂起的帧以恢复执行；生成器已经结束时返回 None
+  fn generator_frame(&self, generator: &Rc<RefCell<Generator>>) -> Result<Option<Frame>, RuntimeError> {
+    let mut generator = generator.borrow_mut();
+    if generator.running {
+      return Err(RuntimeError::ValueError("generator already executing".to_string()));
+    }
+    Ok(generator.frame.take())
+  }
+
+  /// 将生成器的帧压回调用栈执行到下一次 yield 或返回，pending 为在挂起处抛出的异常；
+  /// 逃出生成器的 StopIteration 转换为 RuntimeError，以免被误认为迭代结束
+  fn run_generator(&mut self, generator: &Rc<RefCell<Generator>>, frame: Frame, pending: Option<RuntimeError>) -> Result<Resumed, RuntimeError> {
+    if self.too_deep() {
+      generator.borrow_mut().frame = Some(frame);
+      return Err(RuntimeError::RecursionError);
+    }
+    generator.borrow_mut().running = true;
+    let base = self.frames.len();
+    self.frames.push(frame);
+    let result = self.execute_with(base, pending);
+    let mut generator = generator.borrow_mut();
+    generator.running = false;
+    match result {
+      Ok(value) if self.frames.len() > base => {
+        generator.frame = self.frames.pop();
+        Ok(Resumed::Yielded(value))
+      },
+      Ok(value) => Ok(Resumed::Returned(value)),
+      Err(err) => {
+        self.frames.truncate(base);
+        match self.stop_value(&err) {
+          Some(_) => Err(RuntimeError::NativeError("generator raised StopIteration".to_string())),
+          None => Err(err),
+        }
+      },
+    }
+  }
+
+  /// 恢复生成器，value 为挂起处 yield 表达式的结果；首次恢复从头执行，只能传入 None。
+  /// 已经结束的生成器返回 None
+  fn resume(&mut self, generator: &Rc<RefCell<Generator>>, value: Value) -> Result<Resumed, RuntimeError> {
+    let Some(mut frame) = self.generator_frame(generator)? else {
+      return Ok(Resumed::Returned(Value::None));
+    };
+    if frame.ip > 0 {
+      frame.push(value);
+    } else if !matches!(value, Value::None) {
+      generator.borrow_mut().frame = Some(frame);
+      return Err(RuntimeError::TypeError("can't send non-None value to a just-started generator".to_string()));
+    }
+    self.run_generator(generator, frame, None)
+  }
+
+  /// 在生成器挂起处抛出异常；尚未开始或已经结束的生成器直接结束并抛出该异常。
+  /// 挂起在 yield from 中时先抛给子迭代器，子迭代器返回时 yield from 以其返回值继续执行
+  fn throw_into(&mut self, generator: &Rc<RefCell<Generator>>, exc: Value) -> Result<Resumed, RuntimeError> {
+    let exc = self.instantiate_exception(exc)?;
+    let Some(mut frame) = self.generator_frame(generator)? else {
+      return Err(RuntimeError::Exception(exc));
+    };
+    if frame.ip == 0 {
+      return Err(RuntimeError::Exception(exc));
+    }
+    let Some(target) = delegation(&frame) else {
+      return self.run_generator(generator, frame, Some(RuntimeError::Exception(exc)));
+    };
+    let delegate = frame.peek().clone();
+    generator.borrow_mut().running = true;
+    let result = self.throw_delegate(&delegate, &exc);
+    generator.borrow_mut().running = false;
+    match result {
+      Ok(Resumed::Yielded(value)) => {
+        generator.borrow_mut().frame = Some(frame);
+        Ok(Resumed::Yielded(value))
+      },
+      Ok(Resumed::Returned(value)) => {
+        frame.pop();
+        frame.push(value);
+        frame.ip = target;
+        self.run_generator(generator, frame, None)
+      },
+      Err(err) => self.run_generator(generator, frame, Some(err)),
+    }
+  }
+
+  /// 将抛给生成器的异常转交给 yield from 的子迭代器；GeneratorExit 关闭子迭代器后原样抛出，
+  /// 没有 throw 方法的子迭代器也直接抛出
+  fn throw_delegate(&mut self, delegate: &Value, exc: &Rc<Exception>) -> Result<Resumed, RuntimeError> {
+    if exc.kind.is_subclass(&self.exception_types["GeneratorExit"]) {
+      match delegate {
+        Value::Generator(generator) => {
+          self.close(generator)?;
+        },
+        _ => if let Ok(close) = self.get_attr(delegate.clone(), "close") {
+          self.call(close, Vec::new(), Vec::new())?;
+        },
+      }
+      return Err(RuntimeError::Exception(Rc::clone(exc)));
+    }
+    match delegate {
+      Value::Generator(generator) => self.throw_into(generator, Value::Exception(Rc::clone(exc))),
+      _ => match self.get_attr(delegate.clone(), "throw") {
+        Ok(throw) => {
+          let result = self.call(throw, vec![Value::Exception(Rc::clone(exc))], Vec::new());
+          self.delegate_result(result)
+        },
+        Err(_) => Err(RuntimeError::Exception(Rc::clone(exc))),
+      },
+    }
+  }
+
+  /// 子迭代器的 __next__、send 或 throw 的结果：StopIteration 表示子迭代器以其值返回
+  fn delegate_result(&self, result: Result<Value, RuntimeError>) -> Result<Resumed, RuntimeError> {
+    match result {
+      Ok(value) => Ok(Resumed::Yielded(value)),
+      Err(err) => match self.stop_value(&err) {
+        Some(value) => Ok(Resumed::Returned(value)),
+        None => Err(err),
+      },
+    }
+  }
+
+  /// 向迭代器传入值 (yield from 与 for 循环)：生成器直接恢复；其他迭代器传入 None 时取下一个元素，
+  /// 否则调用其 send 方法
+  fn send_into(&mut self, iter: &Value, value: Value) -> Result<Resumed, RuntimeError> {
+    match iter {
+      Value::Generator(generator) => self.resume(generator, value),
+      Value::Iterator(iter) if matches!(value, Value::None) => {
+        Ok(iter.borrow_mut().next().map_or(Resumed::Returned(Value::None), Resumed::Yielded))
+      },
+      _ if matches!(value, Value::None) => match self.call_special(iter, "__next__", Vec::new()) {
+        Ok(None) => Err(RuntimeError::TypeError(
+          format!("'{}' object is not an iterator", iter.type_name())
+        )),
+        result => self.delegate_result(result.map(|value| value.expect("checked above"))),
+      },
+      _ => {
+        let send = self.get_attr(iter.clone(), "send")?;
+        let result = self.call(send, vec![value], Vec::new());
+        self.delegate_result(result)
+      },
+    }
+  }
+
+  /// generator.send(value)：返回产出的值；生成器返回时抛出携带返回值的 StopIteration
+  pub(crate) fn send(&mut self, generator: &Rc<RefCell<Generator>>, value: Value) -> Result<Value, RuntimeError> {
+    match self.resume(generator, value)? {
+      Resumed::Yielded(value) => Ok(value),
+      Resumed::Returned(value) => Err(RuntimeError::StopIteration(value)),
+    }
+  }
+
+  /// generator.throw(exc)：在挂起处抛出 exc (异常类型会被实例化)，生成器处理后产出的值为结果
+  pub(crate) fn throw(&mut self, generator: &Rc<RefCell<Generator>>, exc: Value) -> Result<Value, RuntimeError> {
+    match self.throw_into(generator, exc)? {
+      Resumed::Yielded(value) => Ok(value),
+      Resumed::Returned(value) => Err(RuntimeError::StopIteration(value)),
+    }
+  }
+
+  /// generator.close()：在挂起处抛出 GeneratorExit；生成器不能在处理 GeneratorExit 时继续 yield
+  pub(crate) fn close(&mut self, generator: &Rc<RefCell<Generator>>) -> Result<Value, RuntimeError> {
+    let exit = Rc::clone(&self.exception_types["GeneratorExit"]);
+    match self.throw_into(generator, Value::Class(Rc::clone(&exit))) {
+      Ok(Resumed::Yielded(_)) => Err(RuntimeError::NativeError("generator ignored GeneratorExit".to_string())),
+      Ok(Resumed::Returned(_)) => Ok(Value::None),
+      Err(RuntimeError::Exception(exc)) if exc.kind.is_subclass(&exit) => Ok(Value::None),
+      Err(err) => Err(err),
+    }
+  }
+
+  /// iter(value)：生成器与迭代器原样返回，实例调用 __iter__ 且结果必须是迭代器
+  pub(crate) fn iter(&mut self, value: Value) -> Result<Value, RuntimeError> {
+    let not_iterable = |value: &Value| RuntimeError::TypeError(
+      format!("'{}' object is not iterable", value.type_name())
+    );
+    if let Value::Generator(_) = value {
+      return Ok(value);
+    }
+    if value.class().is_none() {
+      return self.get_iter(&value).map(Value::Iterator).ok_or_else(|| not_iterable(&value));
+    }
+    match self.call_special(&value, "__iter__", Vec::new())? {
+      Some(iter @ (Value::Iterator(_) | Value::Generator(_))) => Ok(iter),
+      Some(iter) if iter.class().is_some_and(|class| class.lookup("__next__").is_some()) => Ok(iter),
+      Some(iter) => Err(RuntimeError::TypeError(
+        format!("iter() returned non-iterator of type '{}'", iter.type_name())
+      )),
+      None => Err(not_iterable(&value)),
+    }
+  }
+
+  /// 迭代器的下一个元素，耗尽时返回 None；实例调用 __next__，抛出 StopIteration 表示耗尽
+  pub(crate) fn next(&mut self, iter: &Value) -> Result<Option<Value>, RuntimeError> {
+    match self.send_into(iter, Value::None)? {
+      Resumed::Yielded(value) => Ok(Some(value)),
+      Resumed::Returned(_) => Ok(None),
+    }
+  }
+
+  /// 真值测试：实例依次尝试 __bool__ 与 __len__，都没有定义时为真
+  pub(crate) fn truthy(&mut self, value: &Value) -> Result<bool, RuntimeError> {
+    let Some(class) = value.class() else {
+      return Ok(value.is_truthy());
+    };
+    if class.lookup("__len__").is_some() && class.lookup("__bool__").is_none() {
+      return Ok(self.len(value)? > 0);
+    }
+    match self.call_special(value, "__bool__", Vec::new())? {
+      Some(Value::Bool(b)) => Ok(b),
+      Some(result) => Err(RuntimeError::TypeError(
+        format!("__bool__ should return bool, returned {}", result.type_name())
+      )),
+      None => Ok(true),
+    }
+  }
+
+  /// len(value)：实例调用 __len__，结果必须是非负整数
+  pub(crate) fn len(&mut self, value: &Value) -> Result<i64, RuntimeError> {
+    let len = match value {
+      Value::String(s) => s.chars().count(),
+      Value::Bytes(b) => b.len(),
+      Value::List(list) => list.borrow().len(),
+      Value::Tuple(items) => items.len(),
+      Value::Dict(dict) => dict.borrow().len(),
+      Value::Set(set) => set.borrow().len(),
+      Value::Range { start, stop, step } => {
+        return i64::try_from(crate::value::range_len(*start, *stop, *step))
+          .map_err(|_| RuntimeError::OverflowError("Python int too large to convert to C ssize_t".to_string()));
+      },
+      _ => return match self.call_special(value, "__len__", Vec::new())? {
+        Some(Value::Int(n)) if n < 0 => Err(RuntimeError::ValueError("__len__() should return >= 0".to_string())),
+        Some(Value::Int(n)) => Ok(n),
+        Some(Value::Bool(b)) => Ok(b as i64),
+        Some(result) => Err(RuntimeError::TypeError(
+          format!("'{}' object cannot be interpreted as an integer", result.type_name())
+        )),
+        None => Err(RuntimeError::TypeError(
+          format!("object of type '{}' has no len()", value.type_name())
+        )),
+      },
+    };
+    Ok(len as i64)
+  }
+
+  /// str(value)：实例调用 __str__，没有定义时与 repr 相同
+  pub(crate) fn str(&mut self, value: &Value) -> Result<String, RuntimeError> {
+    match self.call_special(value, "__str__", Vec::new())? {
+      Some(Value::String(s)) => Ok(s.to_string()),
+      Some(result) => Err(RuntimeError::TypeError(
+        format!("__str__ returned non-string (type {})", result.type_name())
+      )),
+      None => match value {
+        Value::String(_) | Value::Exception(_) => Ok(value.to_string()),
+        _ => self.repr(value),
+      },
+    }
+  }
+
+  /// repr(value)：实例调用 __repr__，容器中的元素同样使用 repr；
+  /// 容器的每一层嵌套计入 nesting，嵌套过深时抛出 RecursionError
+  pub(crate) fn repr(&mut self, value: &Value) -> Result<String, RuntimeError> {
+    match self.call_special(value, "__repr__", Vec::new())? {
+      Some(Value::String(s)) => Ok(s.to_string()),
+      Some(result) => Err(RuntimeError::TypeError(
+        format!("__repr__ returned non-string (type {})", result.type_name())
+      )),
+      None => {
+        if !matches!(value, Value::List(_) | Value::Tuple(_) | Value::Dict(_) | Value::Set(_)) {
+          return Ok(value.repr());
+        }
+        if self.too_deep() {
+          return Err(RuntimeError::RecursionError);
+        }
+        self.nesting += 1;
+        let result = value.container_repr(|item| self.repr(item)).expect("container");
+        self.nesting -= 1;
+        result
+      },
+    }
+  }
+
+  /// hash(value)：实例调用 __hash__，类的 __hash__ 为 None 时不可哈希
+  pub(crate) fn hash(&mut self, value: &Value) -> Result<i64, RuntimeError> {
+    match value.class().and_then(|class| class.lookup("__hash__")) {
+      Some(Value::None) => Err(RuntimeError::TypeError(
+        format!("unhashable type: '{}'", value.type_name())
+      )),
+      Some(method) => match self.call(method, vec![value.clone()], Vec::new())? {
+        Value::Int(n) => Ok(n),
+        Value::Bool(b) => Ok(b as i64),
+        _ => Err(RuntimeError::TypeError("__hash__ method should return an integer".to_string())),
+      },
+      None => {
+        let key = HashKey::new(value.clone()).map_err(RuntimeError::TypeError)?;
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        Ok(hasher.finish() as i64)
+      },
+    }
+  }
+
+  /// 转换为 dict 的键或 set 的元素：定义了 __hash__ 的实例以其结果为哈希，
+  /// colliding 给出容器中哈希相同的键，其中与之相等 (__eq__) 的键代替它，使相等的实例对应同一个键
+  fn hash_key(&mut self, value: Value, colliding: impl FnOnce(i64) -> Vec<Value>) -> Result<HashKey, RuntimeError> {
+    if value.class().is_none_or(|class| class.lookup("__hash__").is_none()) {
+      return HashKey::new(value).map_err(RuntimeError::TypeError);
+    }
+    let hash = self.hash(&value)?;
+    for key in colliding(hash) {
+      let equal = self.compare(OpCode::CompareEq, value.clone(), key.clone())?;
+      if self.truthy(&equal)? {
+        return Ok(HashKey::with_hash(key, hash));
+      }
+    }
+    Ok(HashKey::with_hash(value, hash))
+  }
+
+  /// 取出可迭代对象的全部元素 (生成器会被耗尽)；不可迭代时返回 None
+  fn collect(&mut self, value: &Value) -> Result<Option<Vec<Value>>, RuntimeError> {
+    // 生成器与定义了 __iter__ 的实例逐个取出元素
+    if matches!(value, Value::Generator(_)) || value.class().is_some_and(|class| class.lookup("__iter__").is_some()) {
+      let iter = self.iter(value.clone())?;
+      let mut items = Vec::new();
+      while let Some(item) = self.next(&iter)? {
+        items.push(item);
+      }
+      return Ok(Some(items));
+    }
+    Ok(self.get_iter(value).map(|iter| iter.borrow_mut().by_ref().collect()))
+  }
+
+  /// 将可迭代对象解包为恰好 count 个值
+  fn unpack(&mut self, value: Value, count: usize) -> Result<Vec<Value>, RuntimeError> {
+    let items = self.collect(&value)?
+      .ok_or_else(|| RuntimeError::TypeError(
+        format!("cannot unpack non-iterable {} object", value.type_name())
+      ))?;
+    if items.len() > count {
+      return Err(RuntimeError::ValueError(
+        format!("too many values to unpack (expected {})", count)
+      ));
+    }
+    if items.len() < count {
+      return Err(RuntimeError::ValueError(
+        format!("not enough values to unpack (expected {}, got {})", count, items.len())
+      ));
+    }
+    Ok(items)
+  }
+  /// a, *b, c = value：before 与 after 个元素之外的部分收集为列表
+  fn unpack_ex(&mut self, value: Value, before: usize, after: usize) -> Result<Vec<Value>, RuntimeError> {
+    let mut items = self.collect(&value)?.ok_or_else(|| RuntimeError::TypeError(
+      format!("cannot unpack non-iterable {} object", value.type_name())
+    ))?;
+    if items.len() < before + after {
+      return Err(RuntimeError::ValueError(format!(
+        "not enough values to unpack (expected at least {}, got {})",
+        before + after,
+        items.len(),
+      )));
+    }
+    let tail = items.split_off(items.len() - after);
+    let middle = items.split_off(before);
+    items.push(Value::List(Rc::new(RefCell::new(middle))));
+    items.extend(tail);
+    Ok(items)
+  }
+
+}
+
+/// 按 CPython 的规则将实参绑定到形参，返回函数帧开头的局部变量:
+/// 位置参数、仅关键字参数、*args (tuple)、**kwargs (dict)
+fn bind_arguments(func: &Function, args: Vec<Value>, kwargs: Vec<(String, Value)>) -> Result<Vec<Value>, RuntimeError> {
+  let code = &func.code;
+  let name = &code.name;
+  let argc = code.arg_count;
+  let kwonly = code.kwonly_arg_count;
+  let params = &code.varnames[..argc + kwonly];
+  let mut slots: Vec<Option<Value>> = vec![None; argc + kwonly];
+
+  // 多余的位置参数进入 *args
+  let given = args.len();
+  let mut args = args.into_iter();
+  for (slot, value) in slots[..argc].iter_mut().zip(args.by_ref()) {
+    *slot = Some(value);
+  }
+  let extra: Vec<Value> = args.collect();
+  if !extra.is_empty() && !code.varargs {
+    let required = argc - func.defaults.len();
+    let takes = if required == argc {
+      format!("{} positional argument{}", argc, plural(argc))
+    } else {
+      format!("from {} to {} positional arguments", required, argc)
+    };
+    let was = if given == 1 { "was" } else { "were" };
+    return Err(RuntimeError::TypeError(
+      format!("{}() takes {} but {} {} given", name, takes, given, was)
+    ));
+  }
+
+  let mut varkw = IndexMap::new();
+  for (key, value) in kwargs {
+    match params.iter().position(|param| param == &key) {
+      Some(i) if slots[i].is_some() => {
+        return Err(RuntimeError::TypeError(
+          format!("{}() got multiple values for argument '{}'", name, key)
+        ));
+      },
+      Some(i) => slots[i] = Some(value),
+      None if code.varkeywords => {
+        varkw.insert(HashKey::from(key.as_str()), value);
+      },
+      None => {
+        return Err(RuntimeError::TypeError(
+          format!("{}() got an unexpected keyword argument '{}'", name, key)
+        ));
+      },
+    }
+  }
+
+  // 默认值
+  let first_default = argc - func.defaults.len();
+  for (slot, default) in slots[first_default..argc].iter_mut().zip(&func.defaults) {
+    if slot.is_none() {
+      *slot = Some(default.clone());
+    }
+  }
+  for (slot, param) in slots[argc..].iter_mut().zip(&params[argc..]) {
+    if slot.is_none() {
+      *slot = func.kwdefaults.get(param).cloned();
+    }
+  }
+
+  for (range, kind) in [(0..argc, "positional"), (argc..argc + kwonly, "keyword-only")] {
+    let missing: Vec<String> = range
+      .filter(|&i| slots[i].is_none())
+      .map(|i| format!("'{}'", params[i]))
+      .collect();
+    if !missing.is_empty() {
+      return Err(RuntimeError::TypeError(format!(
+        "{}() missing {} required {} argument{}: {}",
+        name, missing.len(), kind, plural(missing.len()), join_names(&missing),
+      )));
+    }
+  }
+
+  let mut locals: Vec<Value> = slots.into_iter().map(|slot| slot.expect("bound")).collect();
+  if code.varargs {
+    locals.push(Value::Tuple(Rc::new(extra)));
+  }
+  if code.varkeywords {
+    locals.push(Value::Dict(Rc::new(RefCell::new(varkw))));
+  }
+  Ok(locals)
+}
+
+/// keys 中带有 __hash__ 的结果且与 hash 相同的键
+/// 挂起在 yield from 中的帧返回 Send 指令的跳转目标：
+/// yield from 编译为 SEND target; YIELD_VALUE; LOOP send，挂起时 lasti 指向其中的 YIELD_VALUE
+fn delegation(frame: &Frame) -> Option<usize> {
+  let code = &frame.code.code;
+  let send = frame.lasti.checked_sub(3)?;
+  let read = |offset: usize| ((code[offset] as usize) << 8) | code[offset + 1] as usize;
+  let delegating = OpCode::from(code[send]) == OpCode::Send
+    && OpCode::from(code[frame.lasti]) == OpCode::YieldValue
+    && OpCode::from(code[frame.lasti + 1]) == OpCode::Loop
+    && read(frame.lasti + 2) == send;
+  delegating.then(|| read(send + 1))
+}
+
+/// StopIteration 异常的 value 属性：未设置时为第一个参数，没有参数时为 None
+fn stop_iteration_value(exc: &Exception) -> Value {
+  exc.attrs.borrow().get("value").cloned()
+    .or_else(|| exc.args.borrow().first().cloned())
+    .unwrap_or(Value::None)
+}
+
+fn colliding<'a>(keys: impl Iterator<Item = &'a HashKey>, hash: i64) -> Vec<Value> {
+  keys.filter(|key| key.special_hash() == Some(hash)).map(|key| key.value().clone()).collect()
+}
+
+/// 二元运算与比较的符号及对应的特殊方法 (正向、反射)
+fn binary_dunder(op: OpCode) -> (&'static str, &'static str, &'static str) {
+  match op {
+    OpCode::BinaryAdd => ("+", "__add__", "__radd__"),
+    OpCode::BinarySub => ("-", "__sub__", "__rsub__"),
+    OpCode::BinaryMul => ("*", "__mul__", "__rmul__"),
+    OpCode::BinaryDiv => ("/", "__truediv__", "__rtruediv__"),
+    OpCode::BinaryFloorDiv => ("//", "__floordiv__", "__rfloordiv__"),
+    OpCode::BinaryMod => ("%", "__mod__", "__rmod__"),
+    OpCode::BinaryPow => ("** or pow()", "__pow__", "__rpow__"),
+    OpCode::BinaryMatMul => ("@", "__matmul__", "__rmatmul__"),
+    OpCode::BinaryLShift => ("<<", "__lshift__", "__rlshift__"),
+    OpCode::BinaryRShift => (">>", "__rshift__", "__rrshift__"),
+    OpCode::BinaryAnd => ("&", "__and__", "__rand__"),
+    OpCode::BinaryOr => ("|", "__or__", "__ror__"),
+    OpCode::BinaryXor => ("^", "__xor__", "__rxor__"),
+    OpCode::CompareEq => ("==", "__eq__", "__eq__"),
+    OpCode::CompareNe => ("!=", "__ne__", "__ne__"),
+    OpCode::CompareLt => ("<", "__lt__", "__gt__"),
+    OpCode::CompareLe => ("<=", "__le__", "__ge__"),
+    OpCode::CompareGt => (">", "__gt__", "__lt__"),
+    OpCode::CompareGe => (">=", "__ge__", "__le__"),
+    _ => unreachable!("{:?} is not a binary operator", op),
+  }
+}
+
+fn plural(n: usize) -> &'static str {
+  if n == 1 { "" } else { "s" }
+}
+
+/// 'a'、'a' and 'b'、'a', 'b', and 'c'
+fn join_names(names: &[String]) -> String {
+  match names {
+    [one] => one.clone(),
+    [a, b] => format!("{} and {}", a, b),
+    [rest @ .., last] => format!("{}, and {}", rest.join(", "), last),
+    [] => String::new(),
+  }
+}
+
+/// 将可能为负的下标转换为 [0, len) 内的位置
+fn normalize_index(index: i64, len: usize) -> Option<usize> {
+  let idx = if index < 0 { index + len as i64 } else { index };
+  (0..len as i64).contains(&idx).then_some(idx as usize)
+}
+
+/// 按序列长度调整切片的 (start, stop, step)，与 CPython 的 PySlice_AdjustIndices 相同：
+/// 越界的端点截断到 [0, len]，步长为负时截断到 [-1, len - 1]
+fn adjust_slice(slice: &Slice, len: usize) -> Result<(i64, i64, i64), RuntimeError> {
+  let index = |value: &Value| match value {
+    Value::None => Ok(None),
+    Value::Int(n) => Ok(Some(*n)),
+    Value::Bool(b) => Ok(Some(*b as i64)),
+    _ => Err(RuntimeError::TypeError(
+      "slice indices must be integers or None or have an __index__ method".to_string()
+    )),
+  };
+  let step = index(&slice.step)?.unwrap_or(1);
+  if step == 0 {
+    return Err(RuntimeError::ValueError("slice step cannot be zero".to_string()));
+  }
+  let len = len as i64;
+  let (lowest, highest) = if step > 0 { (0, len) } else { (-1, len - 1) };
+  let clamp = |value: Option<i64>, default: i64| match value {
+    None => default,
+    Some(n) if n < 0 => (n + len).max(lowest),
+    Some(n) => n.min(highest),
+  };
+  let (start, stop) = if step > 0 { (0, len) } else { (len - 1, -1) };
+  Ok((clamp(index(&slice.start)?, start), clamp(index(&slice.stop)?, stop), step))
+}
+
+/// 切片选中的下标
+fn slice_indices(slice: &Slice, len: usize) -> Result<impl Iterator<Item = usize>, RuntimeError> {
+  let (start, stop, step) = adjust_slice(slice, len)?;
+  let count = crate::value::range_len(start, stop, step) as i64;
+  Ok((0..count).map(move |i| (start + i * step) as usize))
+}
+
+/// 恢复生成器或向迭代器传入值的结果
+enum Resumed {
+  /// 产出了值，仍可继续恢复
+  Yielded(Value),
+  /// 已经结束，携带返回值
+  Returned(Value),
 }
 
 #[derive(Debug)]
 pub enum RuntimeError {
   TypeError(String),
   NameError(String),
+  UnboundLocalError(String),
   IndexError,
+  /// 找不到的键
+  KeyError(Value),
+  AttributeError(String),
+  ValueError(String),
+  OverflowError(String),
   ZeroDivision,
+  /// 调用栈超过 MAX_FRAMES
+  RecursionError,
+  /// 迭代器耗尽，携带生成器的返回值
+  StopIteration(Value),
   NativeError(String),
   UnknownOpcode(u8),
-}
\ No newline at end of file
+  /// raise 抛出或经过回溯的异常
+  Exception(Rc<Exception>),
+}
+
+impl RuntimeError {
+  /// 对应的异常类型名
+  pub fn name(&self) -> &str {
+    match self {
+      RuntimeError::TypeError(_) => "TypeError",
+      RuntimeError::NameError(_) => "NameError",
+      RuntimeError::UnboundLocalError(_) => "UnboundLocalError",
+      RuntimeError::IndexError => "IndexError",
+      RuntimeError::KeyError(_) => "KeyError",
+      RuntimeError::AttributeError(_) => "AttributeError",
+      RuntimeError::ValueError(_) => "ValueError",
+      RuntimeError::OverflowError(_) => "OverflowError",
+      RuntimeError::ZeroDivision => "ZeroDivisionError",
+      RuntimeError::RecursionError => "RecursionError",
+      RuntimeError::StopIteration(_) => "StopIteration",
+      RuntimeError::NativeError(_) => "RuntimeError",
+      RuntimeError::UnknownOpcode(_) => "SystemError",
+      RuntimeError::Exception(exc) => &exc.kind.name,
+    }
+  }
+  
+  pub fn message(&self) -> String {
+    match self {
+      RuntimeError::TypeError(msg) | RuntimeError::NameError(msg) | RuntimeError::UnboundLocalError(msg) | RuntimeError::ValueError(msg)
+        | RuntimeError::AttributeError(msg) | RuntimeError::OverflowError(msg) | RuntimeError::NativeError(msg) => msg.clone(),
+      RuntimeError::KeyError(key) => key.repr(),
+      RuntimeError::IndexError => "index out of range".to_string(),
+      RuntimeError::ZeroDivision => "division by zero".to_string(),
+      RuntimeError::RecursionError => "maximum recursion depth exceeded".to_string(),
+      RuntimeError::StopIteration(Value::None) => String::new(),
+      RuntimeError::StopIteration(value) => value.to_string(),
+      RuntimeError::UnknownOpcode(op) => format!("unknown opcode {}", op),
+      RuntimeError::Exception(exc) => exc.message(),
+    }
+  }
+  
+  /// 转为诊断信息：主标注为异常最初的出错位置，回溯经过多个帧时依次标注各调用方的调用位置；
+  /// 递归调用中重复的位置只标注一次
+  pub fn diagnostic(&self) -> Diagnostic {
+    let mut diagnostic = Diagnostic::error(self.message()).with_code(self.name());
+    let RuntimeError::Exception(exc) = self else {
+      return diagnostic;
+    };
+    let traceback = exc.traceback.borrow();
+    let mut seen = Vec::new();
+    for (i, (name, span)) in traceback.iter().enumerate() {
+      if seen.contains(&(name, span)) {
+        continue;
+      }
+      seen.push((name, span));
+      let message = if traceback.len() > 1 { format!("in {}", name) } else { String::new() };
+      diagnostic = if i == 0 {
+        diagnostic.with_primary(*span, message)
+      } else {
+        diagnostic.with_label(*span, message)
+      };
+    }
+    diagnostic
+  }
+}
+
+impl fmt::Display for RuntimeError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.name(), self.message())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// def f(a, b=2, *, k): ...
+  fn function() -> Function {
+    let mut code = CodeObject::new("f");
+    code.arg_count = 2;
+    code.kwonly_arg_count = 1;
+    for name in ["a", "b", "k"] {
+      code.add_varname(name.to_string());
+    }
+    Function {
+      code,
+      globals: Rc::new(RefCell::new(HashMap::new())),
+      defaults: vec![Value::Int(2)],
+      kwdefaults: IndexMap::new(),
+      closure: Vec::new(),
+    }
+  }
+
+  /// 编译并在新的虚拟机中执行源码
+  fn run(source: &str) -> (Result<Value, RuntimeError>, VM) {
+    let mut lexer = cathon_core::ast::Lexer::new(source);
+    let mut parser = cathon_core::ast::Parser::new(&mut lexer);
+    let module = parser.parse().unwrap();
+    let code = cathon_compiler::Compiler::new().compile(&parser.arena, module).unwrap();
+    let mut vm = VM::new();
+    (vm.run(code), vm)
+  }
+
+  #[test]
+  fn assignment_and_unpacking() {
+    // 链式赋值的目标共享同一个值；增量赋值的下标与属性目标只求值一次
+    let source = "\
+class P:
+  pass
+def key():
+  calls[0] += 1
+  return 1
+calls = [0]
+a = b = [1, 2]
+b[0] = 5
+a[key()] *= 3
+p = P()
+p.x = 1
+p.x += 2
+first, (second, third) = 1, [2, 3]
+def message(f):
+  try:
+    f()
+  except ValueError as e:
+    return str(e)
+def few():
+  x, y, z = 1, 2
+def many():
+  x, y = 'abc'
+results = (a, calls[0], p.x, first, second, third)
+errors = (message(few), message(many))
+x, y = 1";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    assert_eq!(global("results"), "([5, 6], 1, 3, 1, 2, 3)");
+    assert_eq!(global("errors"), "('not enough values to unpack (expected 3, got 2)', 'too many values to unpack (expected 2)')");
+    assert_eq!(result.unwrap_err().to_string(), "TypeError: cannot unpack non-iterable int object");
+  }
+
+  #[test]
+  fn if_statements() {
+    // 只执行第一个为真的分支；单行块中以分号分隔的语句都属于该分支
+    let source = "\
+def sign(n):
+  if n > 0:
+    return 'pos'
+  elif n < 0: return 'neg'
+  else:
+    return 'zero'
+def classify(n):
+  seen = []
+  if n: seen = [1]; n = 0
+  if n:
+    seen = [2]
+  elif not n:
+    if seen: seen = (seen, seen)
+  return seen
+signs = (sign(3), sign(-1), sign(0), classify(1), classify(0))";
+    let (result, vm) = run(source);
+    result.unwrap();
+    assert_eq!(vm.globals.borrow()["signs"].to_string(), "('pos', 'neg', 'zero', ([1], [1]), [])");
+  }
+
+  #[test]
+  fn loops() {
+    // break 跳过 else 子句；正常结束 (含零次迭代) 时执行 else；continue 回到循环开头
+    let source = "\
+total = 0
+for i in range(10):
+  if i % 2:
+    continue
+  if i > 6:
+    break
+  total += i
+else:
+  total = -1
+n = 0
+while n < 5:
+  n += 1
+else:
+  n = (n, 'done')
+for x in []:
+  pass
+else:
+  empty = true
+pairs = []
+for a, b in [(1, 2), (3, 4)]:
+  for c in range(a, b + 1):
+    if c == 2:
+      break
+  pairs = (pairs, a + b)
+results = (total, n, empty, pairs)";
+    let (result, vm) = run(source);
+    result.unwrap();
+    assert_eq!(vm.globals.borrow()["results"].to_string(), "(12, (5, 'done'), True, (([], 3), 7))");
+  }
+
+  #[test]
+  fn lambdas_and_conditionals() {
+    // 两种条件表达式都只求值选中的分支；lambda 的代码对象名为 <lambda>，出现在回溯中
+    let source = "\
+pick = lambda c, *, a=1: a if c else undefined_name
+ternary = lambda c: c ? 'yes' : undefined_name
+results = (pick(true), pick(true, a=2), ternary(1), (lambda: 0 ? 1 : 2)())
+(lambda x: 1 / x)(0)";
+    let (result, vm) = run(source);
+    assert_eq!(vm.globals.borrow()["results"].to_string(), "(1, 2, 'yes', 2)");
+    let Err(RuntimeError::Exception(exc)) = result else { panic!("need exception") };
+    let names: Vec<String> = exc.traceback.borrow().iter().map(|(name, _)| name.clone()).collect();
+    assert_eq!(names, vec!["<lambda>", "<module>"]);
+  }
+
+  #[test]
+  fn displays_and_unpacking() {
+    // 展开的元素按书写顺序合并，后出现的键覆盖先出现的键
+    let source = "\
+a = [1, 2]
+d = {'x': 1, 'y': 2}
+first, *mid, last = range(5)
+*init, tail = 'ab'
+results = ([*a, *'bc', 0], (*a,), {*a, 1, 3}, {**d, 'x': 3, **{'z': 4}}, {1: 'a', 1.0: 'b', true: 'c'})
+starred = (first, mid, last, init, tail)
+{**a}";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    assert_eq!(global("results"), "([1, 2, 'b', 'c', 0], (1, 2), {1, 2, 3}, {'x': 3, 'y': 2, 'z': 4}, {1: 'c'})");
+    assert_eq!(global("starred"), "(0, [1, 2, 3], 4, ['a'], 'b')");
+    assert_eq!(result.unwrap_err().to_string(), "TypeError: 'list' object is not a mapping");
+  }
+
+  #[test]
+  fn recursive_repr() {
+    // 包含自身的容器输出 [...] 与 {...}；过深的嵌套抛出 RecursionError 而不是耗尽宿主栈
+    let source = "\
+l = [1]
+l[0] = l
+d = {}
+d[1] = d
+d[2] = [d, l]
+shown = (repr(l), repr(d), str((l, l)))";
+    let (result, vm) = run(source);
+    result.unwrap();
+    assert_eq!(vm.globals.borrow()["shown"].to_string(), "('[[...]]', '{1: {...}, 2: [{...}, [[...]]]}', '([[...]], [[...]])')");
+    assert_eq!(vm.globals.borrow()["l"].to_string(), "[[...]]");
+
+    // 每层 repr 都占用宿主栈，需要更大的测试线程栈
+    let handle = std::thread::Builder::new().stack_size(16 << 20).spawn(|| {
+      let (result, vm) = run("l = []\nfor i in range(1000):\n  l = [l]\nrepr(l)");
+      assert_eq!(result.unwrap_err().to_string(), "RecursionError: maximum recursion depth exceeded");
+      assert!(vm.frames.is_empty() && vm.nesting == 0);
+    }).unwrap();
+    handle.join().unwrap();
+  }
+
+  #[test]
+  fn recursive_equality() {
+    // 同一个容器直接相等；元素通过 == 比较，会调用实例的 __eq__；相互引用的容器抛出 RecursionError
+    let source = "\
+class Any:
+  def __eq__(self, other):
+    return true
+a = [1]
+b = [1]
+a[0] = b
+b[0] = a
+same = (a == a, [a] == [a], (1, {2: a}) == (1, {2: a}))
+elements = ([Any()] == [5], {1: 2} == {1: Any()}, (1, 2) == (1, 3), [1] != [1.0])
+a == b";
+    let handle = std::thread::Builder::new().stack_size(16 << 20).spawn(move || {
+      let (result, vm) = run(source);
+      let global = |name: &str| vm.globals.borrow()[name].to_string();
+      assert_eq!(global("same"), "(True, True, True)");
+      assert_eq!(global("elements"), "(True, True, False, False)");
+      assert_eq!(result.unwrap_err().to_string(), "RecursionError: maximum recursion depth exceeded");
+      assert!(vm.frames.is_empty() && vm.nesting == 0);
+    }).unwrap();
+    handle.join().unwrap();
+  }
+
+  #[test]
+  fn unhashable_keys() {
+    // 元组键的错误指出其中不可哈希的元素
+    let cases = [
+      ("d = {}\nd[(1, [2])] = 3", "list"),
+      ("{(1, ({},))}", "dict"),
+      ("{(1, [2]): 3}", "list"),
+    ];
+    for (source, type_name) in cases {
+      let (result, _) = run(source);
+      assert_eq!(result.unwrap_err().to_string(), format!("TypeError: unhashable type: '{}'", type_name));
+    }
+  }
+
+  #[test]
+  fn comprehensions() {
+    // 推导式在自己的帧中执行，目标变量不泄漏到外层；生成器表达式按需求值
+    let source = "\
+x = 'outer'
+n = 3
+pairs = [(x, y) for x in range(n) if x for y in range(x) if y != 1]
+squares = {x: x * x for x in range(n)}
+evens = {x % 2 for x in range(5)}
+calls = [0]
+def f(v):
+  calls[0] += 1
+  return v
+lazy = (f(v) for v in range(3))
+before = calls[0]
+taken = (next(lazy), calls[0])
+results = (pairs, squares, evens, x, before, taken, [i for i in lazy])
+[1 / x for x in [1, 0]]";
+    let (result, vm) = run(source);
+    assert_eq!(
+      vm.globals.borrow()["results"].to_string(),
+      "([(1, 0), (2, 0)], {0: 0, 1: 1, 2: 4}, {0, 1}, 'outer', 0, (0, 1), [1, 2])",
+    );
+    let Err(RuntimeError::Exception(exc)) = result else { panic!("need exception") };
+    let names: Vec<String> = exc.traceback.borrow().iter().map(|(name, _)| name.clone()).collect();
+    assert_eq!(names, vec!["<listcomp>", "<module>"]);
+  }
+
+  #[test]
+  fn closures() {
+    // 每次调用 counter() 得到独立的 cell；nonlocal 的写入对同一作用域中的其他闭包可见
+    let source = "\
+def counter():
+  n = 0
+  def inc():
+    nonlocal n
+    n += 1
+    return n
+  return inc
+def pair():
+  x = 0
+  def put(v):
+    nonlocal x
+    x = v
+  def get():
+    return x
+  return put, get
+def early():
+  def read():
+    return z
+  first = read()
+  z = 1
+def message(f):
+  try:
+    f()
+  except NameError as e:
+    return (type(e).__name__, str(e))
+a = counter()
+b = counter()
+counts = (a(), a(), b(), a(), b())
+put, get = pair()
+put(5)
+shared = get()
+put(shared + 1)
+shared = (shared, get())
+def shadow():
+  v = v + 1
+errors = (message(shadow), message(early))";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    result.unwrap();
+    assert_eq!(global("counts"), "(1, 2, 1, 3, 2)");
+    assert_eq!(global("shared"), "(5, 6)");
+    assert_eq!(
+      global("errors"),
+      "(('UnboundLocalError', \"cannot access local variable 'v' where it is not associated with a value\"), \
+       ('NameError', \"cannot access free variable 'z' where it is not associated with a value in enclosing scope\"))",
+    );
+  }
+
+  #[test]
+  fn short_circuit_and_chained_comparisons() {
+    // 右操作数在结果已确定时不求值；链式比较的中间操作数只求值一次
+    let source = "\
+calls = [0]
+def f():
+  calls[0] += 1
+  return 2
+lazy = (0 and undefined_name, 1 or undefined_name, not 0, false and f(), null or 'x')
+chained = (1 < f() <= 3, 3 < f() <= 5, 1 < f() > 5, 5 < 1 < f())
+count = calls[0]
+strict = 1 and undefined_name";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    assert_eq!(global("lazy"), "(0, 1, True, False, 'x')");
+    assert_eq!(global("chained"), "(True, False, False, False)");
+    assert_eq!(global("count"), "3");
+    assert_eq!(result.unwrap_err().to_string(), "NameError: name 'undefined_name' is not defined");
+  }
+
+  #[test]
+  fn exception_unwinding() {
+    let source = "def f(x):\n  return 1 / x\ntry:\n  f(0)\nexcept (TypeError, ArithmeticError) as e:\n  caught = e.args\nfinally:\n  done = true\nf(0)";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    assert_eq!(global("caught"), "('division by zero',)");
+    assert_eq!(global("done"), "True");
+    assert!(vm.frames.is_empty());
+
+    let Err(RuntimeError::Exception(exc)) = result else { panic!("uncaught exception") };
+    assert_eq!(exc.kind.name, "ZeroDivisionError");
+    let traceback: Vec<_> = exc.traceback.borrow().iter()
+      .map(|(name, span)| (name.clone(), &source[span.start..span.end]))
+      .collect();
+    assert_eq!(traceback, [("f".to_string(), "1 / x"), ("<module>".to_string(), "f(0)")]);
+  }
+
+  #[test]
+  fn reraise_keeps_traceback() {
+    let source = "try:\n  {}['k']\nexcept KeyError:\n  raise";
+    let (result, _) = run(source);
+    let Err(RuntimeError::Exception(exc)) = result else { panic!("uncaught exception") };
+    assert_eq!(exc.message(), "'k'");
+    let traceback = exc.traceback.borrow();
+    assert_eq!(traceback.len(), 1);
+    assert_eq!(&source[traceback[0].1.start..traceback[0].1.end], "{}['k']");
+
+    let (result, _) = run("raise");
+    assert_eq!(result.unwrap_err().to_string(), "RuntimeError: No active exception to reraise");
+  }
+
+  #[test]
+  fn except_as_name_is_cleared() {
+    // 无论子句正常结束、出错还是被 break 跳出，as 目标都在离开 except 子句时被删除
+    let source = "\
+def normal():
+  try:
+    1/0
+  except ZeroDivisionError as e:
+    pass
+  return e
+def escaped():
+  try:
+    try:
+      1/0
+    except ZeroDivisionError as e:
+      {}['k']
+  except KeyError:
+    pass
+  return e
+def message(f):
+  try:
+    f()
+  except NameError as err:
+    return type(err).__name__
+for i in (1, 2):
+  try:
+    1/0
+  except ZeroDivisionError as e:
+    break
+errors = (message(normal), message(escaped))
+e";
+    let (result, vm) = run(source);
+    assert_eq!(vm.globals.borrow()["errors"].to_string(), "('UnboundLocalError', 'UnboundLocalError')");
+    assert!(!vm.globals.borrow().contains_key("err"));
+    assert_eq!(result.unwrap_err().to_string(), "NameError: name 'e' is not defined");
+  }
+
+  #[test]
+  fn classes_and_inheritance() {
+    let source = "\
+class A:
+  tag = 'a'
+  def __init__(self, x):
+    self.x = x
+  def who(self):
+    return 'A' + self.tag
+class B(A):
+  def who(self):
+    return 'B>' + super().who()
+class C(A):
+  tag = 'c'
+  def who(self):
+    return 'C>' + super().who()
+class D(B, C):
+  def __init__(self):
+    super().__init__(1)
+class Failure(ValueError):
+  def __init__(self, code):
+    super().__init__('failed', code)
+    self.code = code
+d = D()
+who = d.who()
+mro = [cls.__name__ for cls in D.__mro__]
+checks = (isinstance(d, C), isinstance(d, (Failure, B)), issubclass(D, A), issubclass(A, D), d.x)
+try:
+  raise Failure(7)
+except ValueError as e:
+  caught = (e.args, e.code, type(e).__name__)
+A(1, 2)";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    assert_eq!(global("who"), "B>C>Ac");
+    assert_eq!(global("mro"), "['D', 'B', 'C', 'A', 'object']");
+    assert_eq!(global("checks"), "(True, True, True, False, 1)");
+    assert_eq!(global("caught"), "(('failed', 7), 7, 'Failure')");
+    assert_eq!(result.unwrap_err().to_string(), "TypeError: __init__() takes 2 positional arguments but 3 were given");
+  }
+
+  #[test]
+  fn operator_overloading() {
+    let source = "\
+class V:
+  def __init__(self, x):
+    self.x = x
+  def __add__(self, other):
+    if isinstance(other, V):
+      return V(self.x + other.x)
+    return NotImplemented
+  def __radd__(self, other):
+    return V(other + self.x)
+  def __iadd__(self, other):
+    self.x += other
+    return self
+  def __eq__(self, other):
+    return isinstance(other, V) and self.x == other.x
+  def __lt__(self, other):
+    return self.x < other.x
+  def __hash__(self):
+    return self.x
+  def __repr__(self):
+    return f'V({self.x})'
+  def __bool__(self):
+    return self.x != 0
+class Count:
+  def __init__(self, n):
+    self.i = 0
+    self.n = n
+  def __iter__(self):
+    return self
+  def __next__(self):
+    if self.i == self.n:
+      raise StopIteration
+    self.i += 1
+    return self.i
+  def __len__(self):
+    return self.n - self.i
+  def __getitem__(self, i):
+    return i * 2
+  def __call__(self, k):
+    return k + self.n
+a = V(1)
+b = a
+b += 2
+sums = str([a + V(1), 1 + a, a.x])
+cmps = (V(1) == V(1), V(1) != V(1), V(3) > V(2), V(0) or 'zero')
+keys = str({V(1): 'x', V(1): 'y'})
+c = Count(3)
+seen = (c[5], c(1), len(c), [i for i in c], bool(c), next(iter(Count(1))))
+text = str([V(5)])
+V(1) - V(2)";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    assert_eq!(global("sums"), "[V(4), V(4), 3]");
+    assert_eq!(global("cmps"), "(True, False, True, 'zero')");
+    assert_eq!(global("keys"), "{V(1): 'y'}");
+    assert_eq!(global("seen"), "(10, 4, 3, [1, 2, 3], False, 1)");
+    assert_eq!(global("text"), "[V(5)]");
+    assert_eq!(result.unwrap_err().to_string(), "TypeError: unsupported operand type(s) for -: 'V' and 'V'");
+
+    // 内置整数的取负溢出时抛出 OverflowError
+    let (result, _) = run("-(-9223372036854775807 - 1)");
+    assert_eq!(result.unwrap_err().to_string(), "OverflowError: integer overflow in unary -");
+  }
+
+  #[test]
+  fn generators() {
+    let source = "\
+def counter(n):
+  i = 0
+  while i < n:
+    got = yield i
+    if got != null:
+      i = got
+    else:
+      i += 1
+  return 'done'
+def inner():
+  x = yield 1
+  y = yield x
+  return x + y
+def outer():
+  r = yield from inner()
+  yield r
+  yield from [7, 8]
+def guarded(log):
+  try:
+    yield 1
+    yield 2
+  except ValueError as e:
+    yield 'caught ' + str(e)
+  finally:
+    log[0] += 1
+def delegate(log):
+  yield from guarded(log)
+def stubborn():
+  while true:
+    try:
+      yield 1
+    except GeneratorExit:
+      pass
+def bad():
+  raise StopIteration
+  yield 1
+def message(f):
+  try:
+    f()
+  except (RuntimeError, TypeError) as e:
+    return str(e)
+g = counter(5)
+sent = (next(g), g.send(3), next(g))
+try:
+  next(g)
+except StopIteration as e:
+  stop = (e.value, e.args, next(g, 'exhausted'))
+o = outer()
+delegated = str([next(o), o.send(10), o.send(20), next(o), next(o)])
+loop = [x * 2 for x in counter(3)]
+log = [0]
+h = guarded(log)
+d = delegate(log)
+thrown = (next(h), h.throw(ValueError('bad')), h.close(), next(d), d.throw(ValueError('x')), d.close(), log)
+s = stubborn()
+next(s)
+errors = (message(s.close), message(lambda: next(bad())), message(lambda: counter(2).send(1)))
+n = counter(1)
+try:
+  n.throw(KeyError)
+except KeyError as e:
+  finished = (repr(e), next(n, 'finished'))
+counter(3).throw(1)";
+    let (result, vm) = run(source);
+    let global = |name: &str| vm.globals.borrow()[name].to_string();
+    assert_eq!(global("sent"), "(0, 3, 4)");
+    assert_eq!(global("stop"), "('done', ('done',), 'exhausted')");
+    assert_eq!(global("delegated"), "[1, 10, 30, 7, 8]");
+    assert_eq!(global("loop"), "[0, 2, 4]");
+    assert_eq!(global("thrown"), "(1, 'caught bad', None, 1, 'caught x', None, [2])");
+    assert_eq!(
+      global("errors"),
+      "('generator ignored GeneratorExit', 'generator raised StopIteration', \"can't send non-None value to a just-started generator\")",
+    );
+    assert_eq!(global("finished"), "('KeyError()', 'finished')");
+    assert_eq!(result.unwrap_err().to_string(), "TypeError: exceptions must derive from BaseException");
+  }
+
+  #[test]
+  fn recursion_limit() {
+    // 函数调用不在宿主栈上递归，__init__ 由虚拟机发起调用，需要更大的测试线程栈
+    let source = "\
+def r(n):
+  return r(n + 1)
+class A:
+  def __init__(self, n):
+    A(n + 1)
+try:
+  r(0)
+except RecursionError as e:
+  caught = (str(e), isinstance(e, RuntimeError))
+try:
+  A(0)
+except RecursionError:
+  nested = true
+r(0)";
+    let handle = std::thread::Builder::new().stack_size(16 << 20).spawn(move || {
+      let (result, vm) = run(source);
+      let global = |name: &str| vm.globals.borrow()[name].to_string();
+      assert_eq!(global("caught"), "('maximum recursion depth exceeded', True)");
+      assert_eq!(global("nested"), "True");
+      assert!(vm.frames.is_empty() && vm.nesting == 0);
+      assert_eq!(result.unwrap_err().to_string(), "RecursionError: maximum recursion depth exceeded");
+    }).unwrap();
+    handle.join().unwrap();
+  }
+
+  #[test]
+  fn floor_division_and_modulo() {
+    let vm = VM::new();
+    let int = |a: i64, b: i64| (
+      vm.binary_floor_div(Value::Int(a), Value::Int(b)).unwrap(),
+      vm.binary_mod(Value::Int(a), Value::Int(b)).unwrap(),
+    );
+    // 商向负无穷取整，余数与除数同号
+    assert_eq!(int(7, 2), (Value::Int(3), Value::Int(1)));
+    assert_eq!(int(-7, 2), (Value::Int(-4), Value::Int(1)));
+    assert_eq!(int(7, -2), (Value::Int(-4), Value::Int(-1)));
+    assert_eq!(int(-7, -2), (Value::Int(3), Value::Int(-1)));
+    assert_eq!(int(-6, 3), (Value::Int(-2), Value::Int(0)));
+    assert_eq!(vm.binary_mod(Value::Int(i64::MIN), Value::Int(-1)).unwrap(), Value::Int(0));
+    assert_eq!(
+      vm.binary_floor_div(Value::Int(i64::MIN), Value::Int(-1)).unwrap_err().to_string(),
+      "OverflowError: integer overflow in //",
+    );
+    assert!(matches!(vm.binary_mod(Value::Int(1), Value::Int(0)), Err(RuntimeError::ZeroDivision)));
+    // 超出 i64 的结果抛出 OverflowError 而不是回绕
+    for (result, op) in [
+      (vm.binary_add(Value::Int(i64::MAX), Value::Int(1)), "+"),
+      (vm.binary_sub(Value::Int(i64::MIN + 1), Value::Int(2)), "-"),
+      (vm.binary_mul(Value::Int(3037000500), Value::Int(3037000500)), "*"),
+    ] {
+      assert_eq!(result.unwrap_err().to_string(), format!("OverflowError: integer overflow in {}", op));
+    }
+    assert_eq!(vm.binary_mul(Value::Int(-3037000499), Value::Int(3037000499)).unwrap(), Value::Int(-9223372030926249001));
+
+    let float = |a: f64, b: f64| (
+      vm.binary_floor_div(Value::Float(a), Value::Float(b)).unwrap(),
+      vm.binary_mod(Value::Float(a), Value::Float(b)).unwrap(),
+    );
+    assert_eq!(float(-7.5, 2.0), (Value::Float(-4.0), Value::Float(0.5)));
+    assert_eq!(float(7.5, -2.0), (Value::Float(-4.0), Value::Float(-0.5)));
+    assert_eq!(vm.binary_floor_div(Value::Int(7), Value::Float(2.0)).unwrap(), Value::Float(3.0));
+  }
+
+  #[test]
+  fn slice_adjustment() {
+    let slice = |start: Option<i64>, stop: Option<i64>, step: Option<i64>| {
+      let value = |n: Option<i64>| n.map_or(Value::None, Value::Int);
+      Slice { start: value(start), stop: value(stop), step: value(step) }
+    };
+    let indices = |s: Slice, len| slice_indices(&s, len).unwrap().collect::<Vec<_>>();
+    assert_eq!(adjust_slice(&slice(None, None, None), 5).unwrap(), (0, 5, 1));
+    assert_eq!(adjust_slice(&slice(None, None, Some(-1)), 5).unwrap(), (4, -1, -1));
+    assert_eq!(adjust_slice(&slice(Some(-10), Some(10), None), 5).unwrap(), (0, 5, 1));
+    assert_eq!(adjust_slice(&slice(Some(10), Some(-10), Some(-2)), 5).unwrap(), (4, -1, -2));
+    assert_eq!(indices(slice(Some(1), Some(-1), Some(2)), 6), vec![1, 3]);
+    assert_eq!(indices(slice(None, None, Some(-2)), 5), vec![4, 2, 0]);
+    assert_eq!(indices(slice(Some(3), Some(1), None), 5), Vec::<usize>::new());
+    assert!(adjust_slice(&slice(None, None, Some(0)), 5).is_err());
+  }
+
+  #[test]
+  fn range_len() {
+    let (result, vm) = run("a = len(range(10, 0, -3))\nb = len(range(-9223372036854775807, 9223372036854775807, 2))");
+    result.unwrap();
+    assert_eq!(vm.globals.borrow()["a"].to_string(), "4");
+    assert_eq!(vm.globals.borrow()["b"].to_string(), "9223372036854775807");
+    // 长度超出 i64 时与 CPython 一样抛出 OverflowError
+    let (result, _) = run("len(range(-9223372036854775807 - 1, 9223372036854775807))");
+    assert_eq!(result.unwrap_err().to_string(), "OverflowError: Python int too large to convert to C ssize_t");
+  }
+
+  #[test]
+  fn bind_arguments_errors() {
+    let func = function();
+    let kw = |name: &str| vec![(name.to_string(), Value::Int(0))];
+    let message = |args: Vec<Value>, kwargs| match bind_arguments(&func, args, kwargs) {
+      Err(err) => err.message(),
+      Ok(_) => "ok".to_string(),
+    };
+    assert_eq!(message(vec![Value::Int(1)], kw("k")), "ok");
+    assert_eq!(message(vec![], kw("k")), "f() missing 1 required positional argument: 'a'");
+    assert_eq!(message(vec![Value::Int(1)], vec![]), "f() missing 1 required keyword-only argument: 'k'");
+    assert_eq!(message(vec![Value::Int(1); 3], kw("k")), "f() takes from 1 to 2 positional arguments but 3 were given");
+    assert_eq!(message(vec![Value::Int(1)], kw("a")), "f() got multiple values for argument 'a'");
+    assert_eq!(message(vec![Value::Int(1)], kw("z")), "f() got an unexpected keyword argument 'z'");
+    assert_eq!(join_names(&["'a'".to_string(), "'b'".to_string(), "'c'".to_string()]), "'a', 'b', and 'c'");
+  }
+}